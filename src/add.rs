@@ -0,0 +1,113 @@
+use std::{fs, path::Path};
+
+use crate::{
+    config::Config,
+    diagnostics::{self, Diagnostic, Location},
+    files, Category,
+};
+
+fn usage_error(message: String) -> Diagnostic {
+    diagnostics::error("add-invalid", None, message)
+}
+
+// Builds the new line from `@shortcut [amount]` or from
+// `<amount> <category> <payment method> [note]`.
+fn new_line(entry: &[String], config: &Config) -> Result<String, Diagnostic> {
+    let format = &config.csv;
+    let (value, category, payment_method, note) =
+        match entry.first() {
+            Some(first) if first.starts_with('@') => {
+                let name = &first[1..];
+                let Some((_, shortcut)) = config.shortcuts.iter().find(|(n, _)| n == name) else {
+                    return Err(usage_error(format!("Unknown shortcut `{}`", first)));
+                };
+                let value = match entry.get(1) {
+                    Some(amount) => format
+                        .parse_amount(amount)
+                        .map_err(|_| usage_error(format!("Could not parse amount `{}`", amount)))?,
+                    None => shortcut.value,
+                };
+                if entry.len() > 2 {
+                    return Err(usage_error(String::from(
+                        "A shortcut only takes an optional amount",
+                    )));
+                }
+                (
+                    value,
+                    shortcut.category.clone(),
+                    shortcut.payment_method.clone(),
+                    shortcut.note.clone(),
+                )
+            }
+            Some(amount) if entry.len() == 3 || entry.len() == 4 => (
+                format
+                    .parse_amount(amount)
+                    .map_err(|_| usage_error(format!("Could not parse amount `{}`", amount)))?,
+                Category::from(entry[1].as_str()),
+                entry[2].clone(),
+                entry.get(3).cloned().unwrap_or_default(),
+            ),
+            _ => return Err(usage_error(String::from(
+                "Expected `@<shortcut> [amount]` or `<amount> <category> <payment method> [note]`",
+            ))),
+        };
+
+    let category = category.as_written();
+    if [&category, &payment_method, &note]
+        .iter()
+        .any(|x| x.contains(format.delimiter))
+    {
+        return Err(usage_error(format!(
+            "Fields cannot contain `{}`",
+            format.delimiter
+        )));
+    }
+
+    let today = format.format_date(config.today());
+    Ok([
+        format.format_amount(value),
+        today.clone(),
+        category,
+        today,
+        payment_method,
+        note,
+    ]
+    .join(&format.delimiter.to_string()))
+}
+
+pub fn run(entry: &[String], path: &Path, config: &Config) -> Result<(), Diagnostic> {
+    let line = new_line(entry, config)?;
+    let location = Some(Location::file(&path.display().to_string()));
+
+    let _lock = files::lock_data_file(path, true).map_err(|e| {
+        diagnostics::error(
+            "io-lock",
+            location.clone(),
+            format!("Could not lock file: {}", e),
+        )
+    })?;
+    let mut content = fs::read_to_string(path).map_err(|e| {
+        diagnostics::error(
+            "io-read",
+            location.clone(),
+            format!("Could not read file: {}", e),
+        )
+    })?;
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str(&line);
+    content.push('\n');
+
+    if let Err(e) = files::write_atomically(path, content.as_bytes()) {
+        return Err(diagnostics::error(
+            "io-write",
+            location,
+            format!("Could not write file: {}", e),
+        ));
+    }
+    if !files::is_dry_run() {
+        println!("Added `{}` to `{}`.", line, path.display());
+    }
+    Ok(())
+}
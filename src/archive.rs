@@ -0,0 +1,295 @@
+use std::{
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::Datelike;
+
+use crate::{
+    config::Config,
+    diagnostics::{self, Diagnostic, Location},
+    files,
+    json::{self, Value},
+    year_as_i32, StatsCollection,
+};
+
+// Snapshots live in `reports/` next to the data file, one directory per month
+// (a later run in the same month replaces the earlier one).
+pub fn reports_dir(out_path: &Path) -> PathBuf {
+    out_path.with_file_name("reports")
+}
+
+pub fn month_dir(out_path: &Path, config: &Config) -> PathBuf {
+    let today = config.today();
+    reports_dir(out_path).join(format!("{:04}-{:02}", today.year(), today.month()))
+}
+
+// The key numbers of a report, so that runs can be compared without the TeX.
+fn snapshot_json(
+    stats: &StatsCollection,
+    source_name: &str,
+    fingerprints: &[(String, String)],
+    config: &Config,
+) -> String {
+    let today = config.today();
+    let this_month = stats
+        .monthly
+        .iter()
+        .find(|((y, m), _)| *y == year_as_i32(today.year_ce()) && *m == today.month())
+        .map(|(_, x)| x.get_total())
+        .unwrap_or_default();
+    let transactions = stats
+        .yearly
+        .iter()
+        .map(|(_, x)| x.transaction_count)
+        .sum::<u64>();
+
+    let mut out = String::from("{\n");
+    writeln!(
+        out,
+        "  \"generated\": {},",
+        json::string(&today.format("%Y-%m-%d").to_string())
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "  \"version\": {},",
+        json::string(env!("CARGO_PKG_VERSION"))
+    )
+    .unwrap();
+    writeln!(out, "  \"source\": {},", json::string(source_name)).unwrap();
+    // What `verify` checks the data files against.
+    writeln!(out, "  \"data\": {{").unwrap();
+    for (i, (name, sha256)) in fingerprints.iter().enumerate() {
+        let comma = if i + 1 < fingerprints.len() { "," } else { "" };
+        writeln!(
+            out,
+            "    {}: {}{}",
+            json::string(name),
+            json::string(sha256),
+            comma
+        )
+        .unwrap();
+    }
+    writeln!(out, "  }},").unwrap();
+    writeln!(out, "  \"transactions\": {},", transactions).unwrap();
+    writeln!(out, "  \"this_month\": {:.2},", this_month).unwrap();
+    writeln!(
+        out,
+        "  \"last_30_days\": {:.2},",
+        stats.last_30_days.get_total()
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "  \"last_365_days\": {:.2},",
+        stats.last_365_days.get_total()
+    )
+    .unwrap();
+    writeln!(out, "  \"per_day\": {:.2},", stats.last_365_days.per_day).unwrap();
+    // Over the last 365 days, so that months of different snapshots compare.
+    writeln!(out, "  \"by_category\": {{").unwrap();
+    for (i, (c, v)) in stats.last_365_days.by_category.iter().enumerate() {
+        let comma = if i + 1 < stats.last_365_days.by_category.len() {
+            ","
+        } else {
+            ""
+        };
+        writeln!(
+            out,
+            "    {}: {:.2}{}",
+            json::string(&c.to_string()),
+            *v as f64 / 100.0,
+            comma
+        )
+        .unwrap();
+    }
+    out.push_str("  }\n}\n");
+    out
+}
+
+struct Snapshot {
+    month: String, // name of its directory, `YYYY-MM`
+    stats: Value,
+}
+
+impl Snapshot {
+    fn number(&self, key: &str) -> f64 {
+        self.stats
+            .get(key)
+            .and_then(|x| x.as_f64())
+            .unwrap_or_default()
+    }
+
+    fn generated(&self) -> &str {
+        self.stats
+            .get("generated")
+            .and_then(|x| x.as_str())
+            .unwrap_or("?")
+    }
+
+    fn by_category(&self) -> Vec<(String, f64)> {
+        match self.stats.get("by_category") {
+            Some(Value::Object(fields)) => fields
+                .iter()
+                .filter_map(|(k, v)| Some((k.clone(), v.as_f64()?)))
+                .collect(),
+            _ => vec![],
+        }
+    }
+}
+
+fn read_snapshots(dir: &Path) -> Vec<Snapshot> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return vec![];
+    };
+    let mut snapshots = vec![];
+    for entry in entries.flatten() {
+        let path = entry.path().join("stats.json");
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        match json::parse(&content) {
+            Ok(stats) => snapshots.push(Snapshot {
+                month: entry.file_name().to_string_lossy().into_owned(),
+                stats,
+            }),
+            Err(e) => diagnostics::warning(
+                "archive-invalid",
+                Some(Location::file(&path.display().to_string())),
+                format!("Skipping unreadable snapshot: {}", e),
+            ),
+        }
+    }
+    snapshots.sort_by(|a, b| a.month.cmp(&b.month));
+    snapshots
+}
+
+fn index_markdown(snapshots: &[Snapshot]) -> String {
+    let mut out = String::from("# Report snapshots\n\n");
+    out.push_str("| Month | Generated | Transactions | This month | Last 30 days | Last 365 days | Per day |\n");
+    out.push_str("|---|---|---:|---:|---:|---:|---:|\n");
+    for s in snapshots.iter().rev() {
+        writeln!(
+            out,
+            "| [{0}]({0}/report.tex) | {1} | {2} | {3:.2} | {4:.2} | {5:.2} | {6:.2} |",
+            s.month,
+            s.generated(),
+            s.number("transactions"),
+            s.number("this_month"),
+            s.number("last_30_days"),
+            s.number("last_365_days"),
+            s.number("per_day"),
+        )
+        .unwrap();
+    }
+    out
+}
+
+fn write(path: &Path, content: &str) -> Result<(), Diagnostic> {
+    files::write_atomically(path, content.as_bytes()).map_err(|e| {
+        diagnostics::error(
+            "io-write",
+            Some(Location::file(&path.display().to_string())),
+            format!("Could not write file: {}", e),
+        )
+    })
+}
+
+// Saves the stats next to the report already written in `dir`, then rebuilds the index.
+pub fn store(
+    dir: &Path,
+    stats: &StatsCollection,
+    source_name: &str,
+    fingerprints: &[(String, String)],
+    config: &Config,
+) -> Result<(), Diagnostic> {
+    let snapshot = snapshot_json(stats, source_name, fingerprints, config);
+    write(&dir.join("stats.json"), &snapshot)?;
+
+    let reports = dir.parent().unwrap_or(Path::new(""));
+    let mut snapshots = read_snapshots(reports);
+    let month = dir
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned();
+    // In a dry run the new snapshot is not on disk yet.
+    snapshots.retain(|s| s.month != month);
+    snapshots.push(Snapshot {
+        month,
+        stats: json::parse(&snapshot).unwrap(),
+    });
+    snapshots.sort_by(|a, b| a.month.cmp(&b.month));
+    write(&reports.join("index.md"), &index_markdown(&snapshots))
+}
+
+fn change(now: f64, before: Option<f64>) -> String {
+    match before {
+        Some(before) => format!(" ({:+.2})", now - before),
+        None => String::new(),
+    }
+}
+
+pub fn history(out_path: &Path) {
+    let dir = reports_dir(out_path);
+    let snapshots = read_snapshots(&dir);
+    if snapshots.is_empty() {
+        diagnostics::info(
+            "archive-empty",
+            Some(Location::file(&dir.display().to_string())),
+            String::from("No snapshots yet, run a report with `--archive` to start the history."),
+        );
+        return;
+    }
+
+    println!("HISTORY");
+    println!("=======");
+    let mut previous: Option<&Snapshot> = None;
+    for s in snapshots.iter() {
+        let before = |key: &str| previous.map(|p| p.number(key));
+        println!("  - {} (generated {}):", s.month, s.generated());
+        println!("    - This month:    {:.2}", s.number("this_month"));
+        for (label, key) in [
+            ("Last 30 days: ", "last_30_days"),
+            ("Last 365 days:", "last_365_days"),
+            ("Per day:      ", "per_day"),
+        ] {
+            println!(
+                "    - {} {:.2}{}",
+                label,
+                s.number(key),
+                change(s.number(key), before(key))
+            );
+        }
+        previous = Some(s);
+    }
+
+    // What moved the most between the last two runs.
+    if let [.., before, last] = &snapshots[..] {
+        let before = before.by_category();
+        let mut changes = last
+            .by_category()
+            .into_iter()
+            .map(|(c, v)| {
+                let old = before.iter().find(|(x, _)| *x == c).map(|x| x.1);
+                (c, v - old.unwrap_or_default())
+            })
+            .chain(
+                before
+                    .iter()
+                    .filter(|(c, _)| !last.by_category().iter().any(|(x, _)| x == c))
+                    .map(|(c, v)| (c.clone(), -v)),
+            )
+            .filter(|(_, delta)| delta.abs() >= 0.005)
+            .collect::<Vec<_>>();
+        changes.sort_by(|a, b| b.1.abs().total_cmp(&a.1.abs()));
+        if !changes.is_empty() {
+            println!("  Biggest changes over the last 365 days by category:");
+            for (c, delta) in changes.iter().take(5) {
+                println!("    - {}: {:+.2}", c, delta);
+            }
+        }
+    }
+}
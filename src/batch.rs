@@ -0,0 +1,211 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::{Datelike, NaiveDate};
+
+use crate::{
+    card,
+    categorize::Reclassification,
+    config::{self, split_list, Config},
+    daily,
+    diagnostics::{self, Diagnostic, Location},
+    files,
+    filter::Filter,
+    get_stats, period, write_tex_stats, year_as_i32, Category, Transaction,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Format {
+    Tex,
+    Daily,
+    Card,
+}
+
+// One `[job]` of the batch file: which transactions, and what to make of them.
+#[derive(Debug)]
+struct Job {
+    name: String,
+    output: Option<PathBuf>,
+    format: Format,
+    filter: Filter,
+    year: Option<i32>, // for cards
+}
+
+impl Job {
+    fn new(name: &str) -> Self {
+        Self {
+            name: String::from(name),
+            output: None,
+            format: Format::Tex,
+            filter: Filter::default(),
+            year: None,
+        }
+    }
+}
+
+fn parse_jobs(path: &Path, today: NaiveDate) -> Result<Vec<Job>, Diagnostic> {
+    let origin = path.display().to_string();
+    let content = fs::read_to_string(path).map_err(|e| {
+        diagnostics::error(
+            "io-read",
+            Some(Location::file(&origin)),
+            format!("Could not read batch file: {}", e),
+        )
+    })?;
+    // Outputs are relative to the batch file, not to where it is run from.
+    let base = path.parent().unwrap_or(Path::new(""));
+
+    let mut jobs: Vec<Job> = vec![];
+    for entry in config::parse_config(&content, &origin)? {
+        let invalid = |expected: &str| {
+            diagnostics::error(
+                "batch-value",
+                Some(Location::new(&origin, entry.line)),
+                format!(
+                    "Invalid value `{}` for `{}` (expected {})",
+                    entry.value, entry.key, expected
+                ),
+            )
+        };
+        if entry.section.is_empty() {
+            return Err(invalid("to be inside a `[job]` section"));
+        }
+        if jobs.last().is_none_or(|job| job.name != entry.section) {
+            jobs.push(Job::new(&entry.section));
+        }
+        let job = jobs.last_mut().unwrap();
+        let values = split_list(&entry.value)
+            .iter()
+            .flat_map(|x| split_list(x))
+            .collect::<Vec<_>>();
+        let value = values.join(", ");
+        let date = |s: &str| {
+            NaiveDate::parse_from_str(s, "%d/%m/%Y")
+                .map_err(|_| invalid("a date like `31/12/2025`"))
+        };
+        match entry.key.as_str() {
+            "output" => job.output = Some(base.join(&value)),
+            "format" => {
+                job.format = match value.as_str() {
+                    "tex" => Format::Tex,
+                    "daily" => Format::Daily,
+                    "card" => Format::Card,
+                    _ => return Err(invalid("`tex`, `daily` or `card`")),
+                }
+            }
+            "from" => job.filter.from = Some(date(&value)?),
+            "to" => job.filter.to = Some(date(&value)?),
+            "period" => {
+                let (from, to) = period::parse(&value, today)
+                    .ok_or_else(|| invalid("a period like `last month` or `2024-q2`"))?;
+                job.filter.from = Some(from);
+                job.filter.to = Some(to);
+            }
+            "categories" => {
+                job.filter.categories = values.iter().map(|x| Category::from(x.as_str())).collect()
+            }
+            "payment_methods" => job.filter.payment_methods = values,
+            "note" => job.filter.note = Some(value),
+            "year" => job.year = Some(value.parse().map_err(|_| invalid("a year"))?),
+            _ => diagnostics::warning(
+                "batch-unknown-key",
+                Some(Location::new(&origin, entry.line)),
+                format!("Unknown batch key `{}`", entry.key),
+            ),
+        }
+    }
+
+    for job in jobs.iter() {
+        if job.output.is_none() {
+            return Err(diagnostics::error(
+                "batch-value",
+                Some(Location::file(&origin)),
+                format!("Job `{}` has no `output`", job.name),
+            ));
+        }
+    }
+    Ok(jobs)
+}
+
+// Runs every job of the batch file over transactions that were read once.
+pub fn run(
+    batch_path: &Path,
+    transactions: &[Transaction],
+    reclassified: &[Reclassification],
+    source_name: &str,
+    fingerprints: &[(String, String)],
+    filters: &[String],
+    config: &Config,
+) -> Result<(), Diagnostic> {
+    for job in parse_jobs(batch_path, config.today())? {
+        let output = job.output.as_ref().unwrap();
+        let selected = job.filter.apply(transactions);
+        if selected.is_empty() {
+            diagnostics::warning(
+                "batch-empty",
+                None,
+                format!("Job `{}` selects no transactions, skipped", job.name),
+            );
+            continue;
+        }
+        if let Some(dir) = output.parent() {
+            if let Err(e) = files::create_dir_all(dir) {
+                return Err(diagnostics::error(
+                    "io-write",
+                    Some(Location::file(&dir.display().to_string())),
+                    format!("Could not create directory: {}", e),
+                ));
+            }
+        }
+
+        match job.format {
+            Format::Tex => {
+                let stats = get_stats(&selected, config);
+                let reclassified = reclassified
+                    .iter()
+                    .filter(|r| selected.iter().any(|t| t.id == r.transaction_id))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                let title = format!("{} ({})", source_name, job.name);
+                let mut filters = filters.to_vec();
+                filters.extend(job.filter.describe());
+                write_tex_stats(
+                    output,
+                    &stats,
+                    &title,
+                    fingerprints,
+                    &filters,
+                    config,
+                    &reclassified,
+                )?;
+            }
+            Format::Daily => {
+                let from = selected.iter().map(|t| t.date).min().unwrap();
+                let to = job.filter.to.unwrap_or(config.today());
+                let csv = daily::daily_csv(&selected, from, to, config.amortize, &config.csv);
+                if let Err(e) = files::write_atomically(output, csv.as_bytes()) {
+                    return Err(diagnostics::error(
+                        "io-write",
+                        Some(Location::file(&output.display().to_string())),
+                        format!("Could not write file: {}", e),
+                    ));
+                }
+            }
+            Format::Card => {
+                let stats = get_stats(&selected, config);
+                let year = job
+                    .year
+                    .or(job.filter.to.map(|x| x.year()))
+                    .unwrap_or_else(|| year_as_i32(config.today().year_ce()));
+                card::write(output, year, &stats)?;
+                continue;
+            }
+        }
+        if !files::is_dry_run() {
+            println!("Job `{}` saved in `{}`.", job.name, output.display());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+
+use chrono::{Datelike, NaiveDate};
+
+use crate::{
+    categorize,
+    config::Config,
+    days_in_month,
+    diagnostics::{self, Diagnostic},
+    files, is_thin, year_as_i32, Category, Stats, Transaction,
+};
+
+// Spending this much faster than the month goes by gets flagged.
+pub const PACE_ALERT: f64 = 1.25;
+
+// How fast a budget is being used up: the share of it spent against the share
+// of the month gone. A pace of 1 runs out exactly on the last day.
+pub struct Velocity {
+    pub category: Option<Category>, // `None` for all budgeted categories together
+    pub spent: i64,
+    pub budget: i64,
+    pub used: f64,    // 0 to 1 (or more)
+    pub elapsed: f64, // 0 to 1
+    pub thin: bool,   // too few transactions to tell a pace
+}
+
+impl Velocity {
+    pub fn pace(&self) -> f64 {
+        self.used / self.elapsed
+    }
+
+    pub fn too_fast(&self) -> bool {
+        !self.thin && self.pace() > PACE_ALERT
+    }
+}
+
+// Every budgeted category for the month of `today` (whose stats are
+// `this_month`), followed by the overall figure.
+pub fn velocity(this_month: &Stats, today: NaiveDate, config: &Config) -> Vec<Velocity> {
+    let elapsed = today.day() as f64 / days_in_month(today) as f64;
+    let mut velocities = config
+        .budgets
+        .iter()
+        .filter(|(_, budget)| *budget > 0)
+        .map(|(c, budget)| {
+            let spent = this_month
+                .by_category
+                .iter()
+                .find(|(x, _)| x == c)
+                .map_or(0, |(_, v)| *v);
+            Velocity {
+                category: Some(c.clone()),
+                spent,
+                budget: *budget,
+                used: spent as f64 / *budget as f64,
+                elapsed,
+                thin: is_thin(this_month, c, config),
+            }
+        })
+        .collect::<Vec<_>>();
+    if !velocities.is_empty() {
+        let spent = velocities.iter().map(|x| x.spent).sum::<i64>();
+        let budget = velocities.iter().map(|x| x.budget).sum::<i64>();
+        velocities.push(Velocity {
+            category: None,
+            spent,
+            budget,
+            used: spent as f64 / budget as f64,
+            elapsed,
+            thin: false,
+        });
+    }
+    velocities
+}
+
+fn month_index(year: i32, month: u32) -> i32 {
+    year * 12 + month as i32 - 1
+}
+
+fn median(mut xs: Vec<i64>) -> i64 {
+    xs.sort();
+    let n = xs.len();
+    if n == 0 {
+        0
+    } else if n % 2 == 1 {
+        xs[n / 2]
+    } else {
+        (xs[n / 2 - 1] + xs[n / 2]) / 2
+    }
+}
+
+// Median monthly spending per category over the last 12 complete months
+// (fewer if the data is younger), times the configured tightening factor and
+// rounded to whole units. Months without spending in a category count as zero.
+// Uncategorized transactions and categories without spending get no budget.
+pub fn suggest(transactions: &[Transaction], config: &Config) -> (Vec<(Category, i64)>, usize) {
+    let today = config.today();
+    let current = month_index(year_as_i32(today.year_ce()), today.month());
+    let first = transactions
+        .iter()
+        .map(|t| month_index(year_as_i32(t.date.year_ce()), t.date.month()))
+        .min()
+        .unwrap_or(current);
+    let months = ((current - 12).max(first)..current).collect::<Vec<_>>();
+
+    let mut by_category: HashMap<Category, HashMap<i32, i64>> = HashMap::new();
+    for t in transactions {
+        let month = month_index(year_as_i32(t.date.year_ce()), t.date.month());
+        if !months.contains(&month) || categorize::is_uncategorized(&t.category) {
+            continue;
+        }
+        *by_category
+            .entry(t.category.clone())
+            .or_default()
+            .entry(month)
+            .or_insert(0) += t.value;
+    }
+
+    let mut budgets = by_category
+        .into_iter()
+        .filter(|(_, totals)| totals.values().sum::<i64>() > 0)
+        .map(|(category, totals)| {
+            let values = months
+                .iter()
+                .map(|m| *totals.get(m).unwrap_or(&0))
+                .collect();
+            let amount = median(values) as f64 * config.budget_tightening;
+            (category, (amount / 100.0).round() as i64 * 100)
+        })
+        .filter(|(_, amount)| *amount > 0)
+        .collect::<Vec<_>>();
+    budgets.sort_by(|x, y| y.1.cmp(&x.1).then(x.0.to_string().cmp(&y.0.to_string())));
+
+    (budgets, months.len())
+}
+
+pub fn run_suggest(transactions: &[Transaction], config: &Config) -> Result<(), Diagnostic> {
+    let (budgets, months) = suggest(transactions, config);
+    if budgets.is_empty() {
+        diagnostics::info(
+            "budget-no-data",
+            None,
+            String::from("No complete month of data to base budgets on."),
+        );
+        return Ok(());
+    }
+
+    println!("SUGGESTED MONTHLY BUDGETS");
+    println!("=========================");
+    println!(
+        "  (median of the last {} complete month(s), times {})",
+        months, config.budget_tightening
+    );
+    let max_len = budgets
+        .iter()
+        .map(|x| x.0.to_string().len())
+        .max()
+        .unwrap_or_default();
+    for (category, amount) in budgets.iter() {
+        println!(
+            "  - {:<2$}: {:7.2}",
+            category.to_string(),
+            *amount as f64 / 100.0,
+            max_len
+        );
+    }
+
+    let entries = budgets
+        .iter()
+        .map(|(c, a)| (c.to_string(), format!("{:.2}", *a as f64 / 100.0)))
+        .collect::<Vec<_>>();
+    if let Err(e) = config.write_section("budgets", &entries) {
+        return Err(diagnostics::error(
+            "io-write",
+            Some(diagnostics::Location::file(
+                &config.path.display().to_string(),
+            )),
+            format!("Could not write budgets to config file: {}", e),
+        ));
+    }
+    if !files::is_dry_run() {
+        println!("Budgets written to `{}`.", config.path.display());
+    }
+    Ok(())
+}
@@ -0,0 +1,159 @@
+use std::{error::Error, path::Path};
+
+use chrono::NaiveDate;
+use plotters::{
+    prelude::*,
+    style::{full_palette::GREY, RGBColor},
+};
+
+use crate::{
+    diagnostics::{self, Diagnostic, Location},
+    files, Stats, StatsCollection,
+};
+
+const WIDTH: u32 = 1200;
+const HEIGHT: u32 = 630;
+const ACCENT: RGBColor = RGBColor(200, 40, 40);
+
+// A "year in review" picture: totals, top categories and the month by month
+// shape of the year.
+fn draw(
+    path: &Path,
+    year: i32,
+    yearly: &Stats,
+    months: &[(u32, &Stats)],
+) -> Result<(), Box<dyn Error>> {
+    let root = BitMapBackend::new(path, (WIDTH, HEIGHT)).into_drawing_area();
+    root.fill(&WHITE)?;
+    let (left, right) = root.split_horizontally(WIDTH / 2);
+
+    let text = |size: f64| ("sans-serif", size).into_font().color(&BLACK);
+    left.draw(&Text::new(
+        format!("{} in review", year),
+        (50, 40),
+        text(56.0),
+    ))?;
+    left.draw(&Text::new(
+        format!("{:.2}", yearly.get_total()),
+        (50, 130),
+        ("sans-serif", 72.0).into_font().color(&ACCENT),
+    ))?;
+    left.draw(&Text::new(
+        format!(
+            "{:.2} per day, {} transactions",
+            yearly.per_day, yearly.transaction_count
+        ),
+        (50, 215),
+        text(26.0),
+    ))?;
+    if let Some((month, stats)) = months.iter().max_by(|a, b| a.1.total.cmp(&b.1.total)) {
+        left.draw(&Text::new(
+            format!(
+                "Biggest month: {} ({:.2})",
+                NaiveDate::from_ymd_opt(year, *month, 1)
+                    .unwrap()
+                    .format("%B"),
+                stats.get_total()
+            ),
+            (50, 255),
+            text(26.0),
+        ))?;
+    }
+
+    left.draw(&Text::new("Top categories", (50, 330), text(30.0)))?;
+    let top = yearly.by_category.iter().take(3).collect::<Vec<_>>();
+    let max = top.first().map_or(1, |x| x.1.max(1));
+    for (i, (category, value)) in top.iter().enumerate() {
+        let y = 380 + i as i32 * 70;
+        let bar = ((WIDTH / 2 - 100) as i64 * value / max) as i32;
+        left.draw(&Rectangle::new(
+            [(50, y), (50 + bar.max(1), y + 24)],
+            ACCENT.mix(0.3 + 0.7 * (3 - i) as f64 / 3.0).filled(),
+        ))?;
+        left.draw(&Text::new(
+            format!("{}: {:.2}", category, *value as f64 / 100.0),
+            (50, y + 30),
+            text(22.0),
+        ))?;
+    }
+
+    let max = months.iter().map(|x| x.1.total).max().unwrap_or(0).max(1) as f64 / 100.0;
+    let mut chart = ChartBuilder::on(&right)
+        .margin(40)
+        .caption("Month by month", ("sans-serif", 30))
+        .x_label_area_size(30)
+        .build_cartesian_2d((1u32..12u32).into_segmented(), 0.0..max * 1.1)?;
+    chart
+        .configure_mesh()
+        .disable_mesh()
+        .disable_y_axis()
+        .x_labels(12)
+        .x_label_formatter(&|m| match m {
+            SegmentValue::CenterOf(m) => NaiveDate::from_ymd_opt(year, *m, 1)
+                .map(|d| d.format("%b").to_string())
+                .unwrap_or_default(),
+            _ => String::new(),
+        })
+        .x_label_style(("sans-serif", 18))
+        .draw()?;
+    chart.draw_series(
+        Histogram::vertical(&chart)
+            .margin(6)
+            .style(ACCENT.mix(0.8).filled())
+            .data(
+                months
+                    .iter()
+                    .map(|(month, stats)| (*month, stats.get_total())),
+            ),
+    )?;
+    right.draw(&Text::new(
+        format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+        ((WIDTH / 2 - 160) as i32, (HEIGHT - 30) as i32),
+        ("sans-serif", 16.0).into_font().color(&GREY),
+    ))?;
+
+    root.present()?;
+    Ok(())
+}
+
+pub fn write(path: &Path, year: i32, stats: &StatsCollection) -> Result<(), Diagnostic> {
+    let location = Some(Location::file(&path.display().to_string()));
+    let Some((_, yearly)) = stats.yearly.iter().find(|(y, _)| *y == year) else {
+        return Err(diagnostics::error(
+            "card-no-data",
+            None,
+            format!("No transactions in {}", year),
+        ));
+    };
+    let months = stats
+        .monthly
+        .iter()
+        .filter(|((y, _), _)| *y == year)
+        .map(|((_, m), s)| (*m, s))
+        .collect::<Vec<_>>();
+
+    let write_error = |e: &dyn std::fmt::Display| {
+        diagnostics::error(
+            "io-write",
+            location.clone(),
+            format!("Could not write file: {}", e),
+        )
+    };
+    files::check_writable().map_err(|e| write_error(&e))?;
+    if files::is_dry_run() {
+        println!("Would write a year card to `{}`.", path.display());
+        return Ok(());
+    }
+    let temp_path = files::temp_path_for(path);
+    if let Err(e) = draw(&temp_path, year, yearly, &months) {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(diagnostics::error(
+            "card-draw",
+            location,
+            format!("Could not draw card: {}", e),
+        ));
+    }
+    files::commit_temp_file(&temp_path, path).map_err(|e| write_error(&e))?;
+    println!("Year card saved in `{}`.", path.display());
+    Ok(())
+}
@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use strum::IntoEnumIterator;
+
+use crate::{
+    config::Config,
+    diagnostics::{self, Diagnostic, Location},
+    files, Category, Transaction,
+};
+
+// A category change made by battista rather than by whoever wrote the file.
+#[derive(Clone, Debug)]
+pub struct Reclassification {
+    pub transaction_id: String,
+    pub date: chrono::NaiveDate,
+    pub note: String,
+    pub written: String, // category as written in the file
+    pub category: Category,
+    pub reason: String, // which alias or rule did it
+}
+
+pub fn is_uncategorized(c: &Category) -> bool {
+    match c {
+        Category::Unknown => true,
+        Category::Miscellaneous(s) => s.is_empty(),
+        _ => false,
+    }
+}
+
+// What is still without a category once aliases and rules are applied, so
+// that it can be shown up front instead of hiding in the tables.
+pub struct Uncategorized<'a> {
+    pub count: usize,
+    pub total: i64,
+    pub samples: Vec<&'a Transaction>, // the biggest, one per note
+}
+
+pub const UNCATEGORIZED_SAMPLES: usize = 5;
+
+pub fn uncategorized<'a>(
+    transactions: impl Iterator<Item = &'a Transaction>,
+) -> Option<Uncategorized<'a>> {
+    let mut selected = transactions
+        .filter(|t| is_uncategorized(&t.category))
+        .collect::<Vec<_>>();
+    if selected.is_empty() {
+        return None;
+    }
+    let total = selected.iter().map(|t| t.value).sum();
+    let count = selected.len();
+    selected.sort_by_key(|t| std::cmp::Reverse(t.value));
+    let mut samples: Vec<&Transaction> = vec![];
+    for t in selected {
+        if samples.len() == UNCATEGORIZED_SAMPLES {
+            break;
+        }
+        if !samples.iter().any(|x| x.note == t.note) {
+            samples.push(t);
+        }
+    }
+    Some(Uncategorized {
+        count,
+        total,
+        samples,
+    })
+}
+
+// Applies `[aliases]` (written category -> category) and, for transactions
+// without a category, `[rules]` (note substring -> category), returning what
+// was changed so the report can show it.
+pub fn apply(transactions: &mut [Transaction], config: &Config) -> Vec<Reclassification> {
+    let mut changes = vec![];
+    for t in transactions.iter_mut() {
+        let written = t.category.as_written();
+
+        let mut reason = None;
+        if let Some((alias, category)) = config
+            .aliases
+            .iter()
+            .find(|(alias, _)| Category::from(alias.as_str()) == t.category)
+        {
+            t.category = category.clone();
+            reason = Some(format!("alias `{}`", alias));
+        } else if is_uncategorized(&t.category) {
+            let note = t.note.to_lowercase();
+            if let Some((pattern, category)) = config
+                .rules
+                .iter()
+                .find(|(pattern, _)| note.contains(&pattern.to_lowercase()))
+            {
+                t.category = category.clone();
+                reason = Some(format!("rule `{}`", pattern));
+            }
+        }
+
+        if let Some(reason) = reason {
+            if t.category.to_string() != written && Category::from(written.as_str()) != t.category {
+                changes.push(Reclassification {
+                    transaction_id: t.id.clone(),
+                    date: t.date,
+                    note: t.note.clone(),
+                    written,
+                    category: t.category.clone(),
+                    reason,
+                });
+            }
+        }
+    }
+    changes
+}
+
+// A `Miscellaneous (...)` name that is probably a built-in category mistyped.
+pub struct AliasSuggestion {
+    pub written: String,
+    pub category: Category,
+    pub distance: usize,
+    pub count: usize,
+    pub first: NaiveDate,
+    pub last: NaiveDate,
+}
+
+// Optimal string alignment distance, ignoring case: like Levenshtein, but a
+// swap of two neighbouring letters (`Transprotation`) counts as one edit.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.to_lowercase().chars().collect::<Vec<_>>();
+    let b = b.to_lowercase().chars().collect::<Vec<_>>();
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+pub fn suggest_aliases(transactions: &[Transaction]) -> Vec<AliasSuggestion> {
+    let mut written: HashMap<&str, Vec<NaiveDate>> = HashMap::new();
+    for t in transactions {
+        if let Category::Miscellaneous(s) = &t.category {
+            if !s.is_empty() {
+                written.entry(s).or_default().push(t.date);
+            }
+        }
+    }
+
+    let mut suggestions = vec![];
+    for (name, dates) in written {
+        let best = Category::iter()
+            .filter(|c| {
+                !matches!(
+                    c,
+                    Category::Miscellaneous(_) | Category::Unknown | Category::Refunds
+                )
+            })
+            .map(|c| (edit_distance(name, &c.to_string()), c))
+            .min_by_key(|(distance, _)| *distance);
+        let Some((distance, category)) = best else {
+            continue;
+        };
+        // About one typo every five letters; short names get a single one.
+        if distance > (category.to_string().len() / 5).max(1) {
+            continue;
+        }
+        suggestions.push(AliasSuggestion {
+            written: String::from(name),
+            category,
+            distance,
+            count: dates.len(),
+            first: *dates.iter().min().unwrap(),
+            last: *dates.iter().max().unwrap(),
+        });
+    }
+    suggestions.sort_by(|a, b| b.count.cmp(&a.count).then(a.written.cmp(&b.written)));
+    suggestions
+}
+
+// `aliases suggest`: lists likely misspellings and adds them to `[aliases]`,
+// keeping the aliases already there.
+pub fn run_suggest(transactions: &[Transaction], config: &Config) -> Result<(), Diagnostic> {
+    let suggestions = suggest_aliases(transactions);
+    if suggestions.is_empty() {
+        diagnostics::info(
+            "aliases-none",
+            None,
+            String::from("No category looks like a misspelling of a built-in one."),
+        );
+        return Ok(());
+    }
+
+    println!("LIKELY MISSPELLED CATEGORIES");
+    println!("============================");
+    let max_len = suggestions
+        .iter()
+        .map(|x| x.written.len())
+        .max()
+        .unwrap_or_default();
+    for s in suggestions.iter() {
+        println!(
+            "  - {:<6$} -> {} ({} edit(s), {} transaction(s) from {} to {})",
+            s.written,
+            s.category,
+            s.distance,
+            s.count,
+            s.first.format("%d/%m/%Y"),
+            s.last.format("%d/%m/%Y"),
+            max_len
+        );
+    }
+
+    let mut entries = config
+        .aliases
+        .iter()
+        .map(|(alias, c)| (alias.clone(), c.to_string()))
+        .collect::<Vec<_>>();
+    entries.extend(
+        suggestions
+            .iter()
+            .map(|s| (s.written.clone(), s.category.to_string())),
+    );
+    if let Err(e) = config.write_section("aliases", &entries) {
+        return Err(diagnostics::error(
+            "io-write",
+            Some(Location::file(&config.path.display().to_string())),
+            format!("Could not write aliases to config file: {}", e),
+        ));
+    }
+    if !files::is_dry_run() {
+        println!("Aliases written to `{}`.", config.path.display());
+    }
+    Ok(())
+}
@@ -0,0 +1,294 @@
+use std::{env, fmt, fs, path::PathBuf};
+
+use chrono::{
+    DateTime, Datelike, Days, FixedOffset, Local, NaiveDate, NaiveDateTime, Offset, TimeDelta,
+    TimeZone as _, Utc, Weekday,
+};
+
+// Time zone used to decide what "today" is, which matters for the trailing
+// windows (last 30/365 days) and the current month and year.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum TimeZone {
+    #[default]
+    Local,
+    Fixed(FixedOffset), // `UTC`, `+02:00`, `-0530`, ...
+    Named(String),      // IANA name such as `Europe/Zurich`
+}
+
+// Written the way `parse` reads it.
+impl fmt::Display for TimeZone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Local => write!(f, "local"),
+            Self::Fixed(offset) if offset.local_minus_utc() == 0 => write!(f, "UTC"),
+            Self::Fixed(offset) => write!(f, "{}", offset),
+            Self::Named(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+fn zoneinfo_dir() -> PathBuf {
+    env::var_os("TZDIR")
+        .map(PathBuf::from)
+        .unwrap_or(PathBuf::from("/usr/share/zoneinfo"))
+}
+
+fn parse_offset(s: &str) -> Option<FixedOffset> {
+    let (sign, rest) = match s.chars().next()? {
+        '+' => (1, &s[1..]),
+        '-' => (-1, &s[1..]),
+        _ => return None,
+    };
+    let (hours, minutes) = match rest.split_once(':') {
+        Some((h, m)) => (h, m),
+        None if rest.len() == 4 => rest.split_at(2),
+        None => (rest, "0"),
+    };
+    let hours = hours.parse::<i32>().ok()?;
+    let minutes = minutes.parse::<i32>().ok()?;
+    if minutes >= 60 {
+        return None;
+    }
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+// `[+-]hh[:mm[:ss]]` in seconds, as in POSIX `TZ` strings (where it is the
+// time to add to local time to get UTC, so west of Greenwich is positive).
+fn parse_posix_time(s: &str) -> Option<i32> {
+    let (sign, rest) = match s.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let mut seconds = 0;
+    for (i, part) in rest.split(':').enumerate() {
+        if i > 2 || part.is_empty() || !part.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        seconds += part.parse::<i32>().ok()? * [3600, 60, 1][i];
+    }
+    Some(sign * seconds)
+}
+
+// A zone abbreviation, `CET` or `<+03>`, and what follows it.
+fn split_posix_name(s: &str) -> Option<(&str, &str)> {
+    let end = match s.strip_prefix('<') {
+        Some(rest) => rest.find('>')? + 2,
+        None => s
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(s.len()),
+    };
+    (end >= 3).then(|| s.split_at(end))
+}
+
+// The day a daylight saving rule switches on: `Mm.w.d` (day `d` of week `w`
+// of month `m`, week 5 being the last), `Jn` (1 to 365, never counting
+// February 29) or `n` (0 to 365).
+fn posix_rule_date(rule: &str, year: i32) -> Option<NaiveDate> {
+    if let Some(rest) = rule.strip_prefix('M') {
+        let mut parts = rest.split('.').map(|x| x.parse::<u32>().ok());
+        let (month, week, day) = (parts.next()??, parts.next()??, parts.next()??);
+        let weekday = Weekday::try_from(((day + 6) % 7) as u8).ok()?;
+        let mut date = NaiveDate::from_weekday_of_month_opt(year, month, weekday, 1)?;
+        for _ in 1..week.min(5) {
+            match date.checked_add_days(Days::new(7)) {
+                Some(next) if next.month() == month => date = next,
+                _ => break,
+            }
+        }
+        Some(date)
+    } else if let Some(rest) = rule.strip_prefix('J') {
+        let day = rest.parse::<u32>().ok().filter(|x| (1..=365).contains(x))?;
+        let date = NaiveDate::from_yo_opt(year, day)?;
+        let leap = NaiveDate::from_ymd_opt(year, 2, 29).is_some();
+        Some(if leap && day >= 60 {
+            date.succ_opt()?
+        } else {
+            date
+        })
+    } else {
+        NaiveDate::from_yo_opt(year, rule.parse::<u32>().ok()? + 1)
+    }
+}
+
+// The footer of TZif files, e.g. `CET-1CEST,M3.5.0,M10.5.0/3`: the offset east
+// of UTC at `utc`, for times after the last listed transition.
+fn posix_offset(tz: &str, utc: NaiveDateTime) -> Option<i32> {
+    let (_, rest) = split_posix_name(tz)?;
+    let std_end = rest.find(|c: char| c.is_ascii_alphabetic() || c == '<' || c == ',');
+    let (std_offset, rest) = rest.split_at(std_end.unwrap_or(rest.len()));
+    let std_offset = -parse_posix_time(std_offset)?;
+    if rest.is_empty() {
+        return Some(std_offset);
+    }
+    let (_, rest) = split_posix_name(rest)?;
+    let (dst_offset, rules) = rest.split_once(',').unwrap_or((rest, "M3.2.0,M11.1.0"));
+    let dst_offset = match dst_offset {
+        "" => std_offset + 3600,
+        x => -parse_posix_time(x)?,
+    };
+    let (start, end) = rules.split_once(',')?;
+    let switch = |rule: &str, offset: i32| {
+        let (date, time) = rule.split_once('/').unwrap_or((rule, "2"));
+        let local = posix_rule_date(date, (utc + TimeDelta::seconds(std_offset as i64)).year())?
+            .and_hms_opt(0, 0, 0)?
+            + TimeDelta::seconds(parse_posix_time(time)? as i64);
+        Some(local - TimeDelta::seconds(offset as i64))
+    };
+    // DST starts in standard time and ends in daylight saving time.
+    let (start, end) = (switch(start, std_offset)?, switch(end, dst_offset)?);
+    let dst = if start < end {
+        start <= utc && utc < end
+    } else {
+        !(end <= utc && utc < start) // southern hemisphere
+    };
+    Some(if dst { dst_offset } else { std_offset })
+}
+
+// Offset east of UTC of zone `name` at `utc`, read from its TZif file (see
+// `tzfile(5)`): that of the last transition before `utc`, or the footer rule
+// after the last one.
+fn zone_offset(name: &str, utc: NaiveDateTime) -> Option<i32> {
+    let data = fs::read(zoneinfo_dir().join(name)).ok()?;
+    let bytes = |at: usize, n: usize| data.get(at..at + n);
+    // The header's counts: UT/local indicators, standard/wall indicators,
+    // leap seconds, transitions, local time types and abbreviation bytes.
+    let header = |at: usize| -> Option<[usize; 6]> {
+        if bytes(at, 4)? != b"TZif" {
+            return None;
+        }
+        let mut counts = [0; 6];
+        for (i, count) in counts.iter_mut().enumerate() {
+            *count = u32::from_be_bytes(bytes(at + 20 + i * 4, 4)?.try_into().ok()?) as usize;
+        }
+        Some(counts)
+    };
+
+    // Version 2 and later repeat the data with 64-bit times, followed by the footer.
+    let mut start = 0;
+    let mut time_size = 4;
+    let mut counts = header(0)?;
+    if *data.get(4)? >= b'2' {
+        let [ut, std, leap, times, types, chars] = counts;
+        start = 44 + times * 5 + types * 6 + chars + leap * 8 + std + ut;
+        time_size = 8;
+        counts = header(start)?;
+    }
+    let [ut, std, leap, times, types, chars] = counts;
+    let times_at = start + 44;
+    let indices_at = times_at + times * time_size;
+    let types_at = indices_at + times;
+    let offset_of = |index: usize| -> Option<i32> {
+        Some(i32::from_be_bytes(
+            bytes(types_at + index * 6, 4)?.try_into().ok()?,
+        ))
+    };
+
+    let t = utc.and_utc().timestamp();
+    let mut last = None;
+    for i in 0..times {
+        let at = times_at + i * time_size;
+        let time = match time_size {
+            8 => i64::from_be_bytes(bytes(at, 8)?.try_into().ok()?),
+            _ => i32::from_be_bytes(bytes(at, 4)?.try_into().ok()?) as i64,
+        };
+        if time > t {
+            break;
+        }
+        last = Some(i);
+    }
+    if time_size == 8 && last.map_or(times == 0, |i| i + 1 == times) {
+        let footer_at = types_at + types * 6 + chars + leap * 12 + std + ut;
+        let footer = std::str::from_utf8(data.get(footer_at..)?).ok()?;
+        let footer = footer.trim_matches('\n');
+        if !footer.is_empty() {
+            return posix_offset(footer, utc);
+        }
+    }
+    match last {
+        Some(i) => offset_of(*data.get(indices_at + i)? as usize),
+        None => offset_of(0),
+    }
+}
+
+impl TimeZone {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "local" => Some(Self::Local),
+            "UTC" | "utc" | "Z" => Some(Self::Fixed(FixedOffset::east_opt(0).unwrap())),
+            _ => {
+                if let Some(offset) = parse_offset(s) {
+                    Some(Self::Fixed(offset))
+                } else if !s.contains("..") && zone_offset(s, Utc::now().naive_utc()).is_some() {
+                    Some(Self::Named(String::from(s)))
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    pub fn offset_at(&self, utc: NaiveDateTime) -> FixedOffset {
+        match self {
+            Self::Local => Local.offset_from_utc_datetime(&utc).fix(),
+            Self::Fixed(offset) => *offset,
+            // Checked by `parse`, so only a file removed since then ends up in UTC.
+            Self::Named(name) => zone_offset(name, utc)
+                .and_then(FixedOffset::east_opt)
+                .unwrap_or(FixedOffset::east_opt(0).unwrap()),
+        }
+    }
+
+    pub fn now(&self) -> DateTime<FixedOffset> {
+        let now = Utc::now();
+        now.with_timezone(&self.offset_at(now.naive_utc()))
+    }
+
+    pub fn today(&self) -> NaiveDate {
+        self.now().date_naive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    #[test]
+    fn posix_rules_switch_at_the_right_instant() {
+        let zurich = "CET-1CEST,M3.5.0,M10.5.0/3";
+        assert_eq!(posix_offset(zurich, at("2026-03-29 00:59:59")), Some(3600));
+        assert_eq!(posix_offset(zurich, at("2026-03-29 01:00:00")), Some(7200));
+        assert_eq!(posix_offset(zurich, at("2026-10-25 00:59:59")), Some(7200));
+        assert_eq!(posix_offset(zurich, at("2026-10-25 01:00:00")), Some(3600));
+
+        let new_york = "EST5EDT,M3.2.0,M11.1.0";
+        assert_eq!(
+            posix_offset(new_york, at("2026-07-01 12:00:00")),
+            Some(-14400)
+        );
+        assert_eq!(
+            posix_offset(new_york, at("2026-12-01 12:00:00")),
+            Some(-18000)
+        );
+    }
+
+    #[test]
+    fn posix_rules_in_the_southern_hemisphere() {
+        let sydney = "AEST-10AEDT,M10.1.0,M4.1.0/3";
+        assert_eq!(posix_offset(sydney, at("2026-01-01 12:00:00")), Some(39600));
+        assert_eq!(posix_offset(sydney, at("2026-07-01 12:00:00")), Some(36000));
+    }
+
+    #[test]
+    fn posix_zones_without_daylight_saving() {
+        assert_eq!(
+            posix_offset("<+0530>-5:30", at("2026-07-01 12:00:00")),
+            Some(19800)
+        );
+        assert_eq!(posix_offset("UTC0", at("2026-07-01 12:00:00")), Some(0));
+        assert_eq!(posix_offset("nonsense", at("2026-07-01 12:00:00")), None);
+    }
+}
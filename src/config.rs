@@ -0,0 +1,679 @@
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+use chrono::NaiveDate;
+
+use crate::{
+    clock::TimeZone,
+    diagnostics::{self, Diagnostic, Location},
+    files,
+    locale::Locale,
+    metrics::{self, Expr},
+    profiles::{self, Profile},
+    rounding::RoundingMode,
+    scale::AmountScale,
+    source::{parse_amount, CsvFormat},
+    specialdates::SpecialDate,
+    Category,
+};
+
+// What the footer of every report page can show, in this order.
+pub const FOOTER_FIELDS: [&str; 5] = ["version", "generated", "as_of", "data", "filters"];
+
+// A single `key = value` line of the config file.
+#[derive(Clone, Debug)]
+pub struct ConfigEntry {
+    pub section: String, // empty for the top level
+    pub key: String,
+    pub value: String,
+    pub line: usize,
+}
+
+// A template for `add @name`.
+#[derive(Clone, Debug)]
+pub struct Shortcut {
+    pub value: i64,
+    pub category: Category,
+    pub payment_method: String,
+    pub note: String,
+}
+
+// A purchase whose cost is spread over its life, see `durables`.
+#[derive(Clone, Debug, Default)]
+pub struct Durable {
+    pub lifetime_months: Option<u32>,
+    pub uses: Option<u32>,
+}
+
+// Where a setting came from, shown by `battista schema`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Provenance {
+    Default,
+    File(usize), // line in the config file
+    Flag,
+}
+
+// The config file is a small subset of TOML: `[section]` headers, `key = value`
+// lines (values optionally quoted) and `#` comments.
+#[derive(Debug)]
+pub struct Config {
+    pub path: PathBuf,
+    pub rounding: RoundingMode,
+    pub precision: usize,         // decimals of averages and percentages
+    pub scale: AmountScale,       // amounts of report tables and charts, e.g. in thousands
+    pub currency: Option<String>, // only shown, amounts are never converted
+    pub locale: Locale,           // how reports write numbers and dates
+    pub timezone: TimeZone,
+    pub net_refunds: bool, // refunds count against their category instead of a `Refunds` line
+    pub statement_day: Option<u32>, // group by statement periods starting on this day
+    pub amortize: bool,    // per-day averages spread transactions from their date to their end date
+    pub budget_tightening: f64, // factor applied by `budget suggest`
+    pub daily_allowance: Option<i64>, // in cents
+    pub allowance_exclude: Vec<Category>, // not counted against the allowance
+    pub review_threshold: Option<i64>, // queue transactions from this amount on for review
+    pub note_length: usize, // longer notes are shortened in tables, 0 for never
+    pub moving_average_window: usize, // months, for the trend lines of charts
+    pub min_sample: usize, // categories with fewer transactions in a period are marked
+    pub category_order: Vec<Category>, // how tables list categories, instead of by amount
+    pub footer: Vec<String>, // what the report footer shows, from `FOOTER_FIELDS`
+    pub budgets: Vec<(Category, i64)>, // monthly, in cents
+    pub share_targets: Vec<(Category, f64)>, // most of a month's spending, in percent
+    pub aliases: Vec<(String, Category)>, // written category -> category
+    pub rules: Vec<(String, Category)>, // note substring -> category
+    pub shortcuts: Vec<(String, Shortcut)>,
+    pub payment_groups: Vec<(String, String)>, // payment method -> group
+    pub durables: Vec<(String, Durable)>,      // note -> lifetime and usage
+    pub note_groups: Vec<(String, Vec<String>)>, // name -> note patterns
+    pub special_dates: Vec<(String, Vec<SpecialDate>)>, // holidays, vacations, payday
+    pub metrics: Vec<(String, Expr)>,
+    pub profile: Option<Profile>, // reference distribution to compare against
+    pub csv: CsvFormat,
+    provenance: Vec<(String, Provenance)>, // top-level keys, later entries win
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            path: default_config_path(),
+            rounding: RoundingMode::default(),
+            precision: 2,
+            scale: AmountScale::default(),
+            currency: None,
+            locale: Locale::default(),
+            timezone: TimeZone::default(),
+            net_refunds: true,
+            statement_day: None,
+            amortize: false,
+            budget_tightening: 1.0,
+            daily_allowance: None,
+            allowance_exclude: vec![],
+            review_threshold: None,
+            note_length: 40,
+            moving_average_window: 12,
+            min_sample: 5,
+            footer: FOOTER_FIELDS.iter().map(|x| String::from(*x)).collect(),
+            category_order: vec![],
+            budgets: vec![],
+            share_targets: vec![],
+            aliases: vec![],
+            rules: vec![],
+            shortcuts: vec![],
+            payment_groups: vec![],
+            durables: vec![],
+            note_groups: vec![],
+            special_dates: vec![],
+            metrics: vec![],
+            profile: None,
+            csv: CsvFormat::default(),
+            provenance: vec![],
+        }
+    }
+}
+
+pub fn default_config_path() -> PathBuf {
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .unwrap_or_default();
+    base.join(env!("CARGO_PKG_NAME")).join("config.toml")
+}
+
+fn quote_key(key: &str) -> String {
+    if key
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        String::from(key)
+    } else {
+        format!("\"{}\"", key.replace('"', "\\\""))
+    }
+}
+
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+        value[1..value.len() - 1].replace("\\\"", "\"")
+    } else {
+        String::from(value)
+    }
+}
+
+// Cuts a trailing `# comment`, leaving `#` inside quotes alone.
+fn strip_comment(line: &str) -> &str {
+    let mut in_quotes = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '#' if !in_quotes => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+pub fn parse_config(content: &str, origin: &str) -> Result<Vec<ConfigEntry>, Diagnostic> {
+    let mut entries = vec![];
+    let mut section = String::new();
+
+    for (line_idx, line) in content.lines().enumerate() {
+        let line = strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+        if line.starts_with('[') && line.ends_with(']') {
+            section = String::from(line[1..line.len() - 1].trim());
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(diagnostics::error(
+                "config-syntax",
+                Some(Location::new(origin, line_idx + 1)),
+                String::from("Expected `key = value`"),
+            ));
+        };
+        entries.push(ConfigEntry {
+            section: section.clone(),
+            key: unquote(key),
+            value: String::from(value.trim()),
+            line: line_idx + 1,
+        });
+    }
+
+    Ok(entries)
+}
+
+// Splits on commas outside of quotes, unquoting each part.
+pub fn split_list(value: &str) -> Vec<String> {
+    let mut parts = vec![];
+    let mut current = String::new();
+    let mut in_quotes = false;
+    for c in value.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                current.push(c);
+            }
+            ',' if !in_quotes => parts.push(unquote(&std::mem::take(&mut current))),
+            _ => current.push(c),
+        }
+    }
+    parts.push(unquote(&current));
+    parts
+}
+
+// `35`, `35%` or `<= 35%`: targets are always upper bounds.
+fn parse_share(value: &str) -> Option<f64> {
+    let value = value.trim();
+    let value = value
+        .strip_prefix("<=")
+        .or_else(|| value.strip_prefix('≤'))
+        .unwrap_or(value)
+        .trim();
+    let share = value
+        .strip_suffix('%')
+        .unwrap_or(value)
+        .trim()
+        .parse::<f64>()
+        .ok()?;
+    (0.0..=100.0).contains(&share).then_some(share)
+}
+
+// `4.50, Restaurants, card, "coffee"`; the note defaults to the shortcut name.
+fn parse_shortcut(name: &str, value: &str) -> Option<Shortcut> {
+    let parts = split_list(value);
+    if parts.len() < 3 || parts.len() > 4 {
+        return None;
+    }
+    Some(Shortcut {
+        value: parse_amount(&parts[0]).ok()?,
+        category: Category::from(parts[1].as_str()),
+        payment_method: parts[2].clone(),
+        note: parts.get(3).cloned().unwrap_or(String::from(name)),
+    })
+}
+
+// `36 months`, `85 uses` or both, comma separated.
+fn parse_durable(value: &str) -> Option<Durable> {
+    let mut durable = Durable::default();
+    for part in split_list(value) {
+        let (n, unit) = part.trim().split_once(' ')?;
+        let n = n.parse::<u32>().ok().filter(|n| *n > 0)?;
+        match unit.trim() {
+            "month" | "months" if durable.lifetime_months.is_none() => {
+                durable.lifetime_months = Some(n)
+            }
+            "use" | "uses" if durable.uses.is_none() => durable.uses = Some(n),
+            _ => return None,
+        }
+    }
+    Some(durable)
+}
+
+pub fn parse_statement_day(s: &str) -> Option<u32> {
+    s.parse::<u32>().ok().filter(|x| (1..=31).contains(x))
+}
+
+fn invalid_value(entry: &ConfigEntry, origin: &str, expected: &str) -> Diagnostic {
+    diagnostics::error(
+        "config-value",
+        Some(Location::new(origin, entry.line)),
+        format!(
+            "Invalid value `{}` for `{}` (expected {})",
+            entry.value, entry.key, expected
+        ),
+    )
+}
+
+impl Config {
+    // Reads the config at `path`, or at the default location when not given.
+    // A missing file at the default location just means defaults.
+    pub fn load(path: Option<&Path>) -> Result<Self, Diagnostic> {
+        let mut config = Config::default();
+        if let Some(path) = path {
+            config.path = path.to_path_buf();
+        }
+
+        let content = match fs::read_to_string(&config.path) {
+            Ok(content) => content,
+            Err(_) if path.is_none() => return Ok(config),
+            Err(e) => {
+                return Err(diagnostics::error(
+                    "io-read",
+                    Some(Location::file(&config.path.display().to_string())),
+                    format!("Could not read config file: {}", e),
+                ))
+            }
+        };
+
+        let origin = config.path.display().to_string();
+        for entry in parse_config(&content, &origin)? {
+            config.apply(&entry, &origin)?;
+        }
+        if config.csv.delimiter == config.csv.decimal {
+            return Err(diagnostics::error(
+                "config-value",
+                Some(Location::file(&origin)),
+                String::from("The CSV delimiter and decimal separator must differ"),
+            ));
+        }
+        Ok(config)
+    }
+
+    fn apply(&mut self, entry: &ConfigEntry, origin: &str) -> Result<(), Diagnostic> {
+        let value = unquote(&entry.value);
+        if entry.section.is_empty() {
+            self.provenance
+                .push((entry.key.clone(), Provenance::File(entry.line)));
+        }
+        match (entry.section.as_str(), entry.key.as_str()) {
+            ("", "rounding") => {
+                self.rounding = RoundingMode::parse(&value)
+                    .ok_or_else(|| invalid_value(entry, origin, "`half-up` or `bankers`"))?;
+            }
+            ("", "precision") => {
+                self.precision = value
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|x| *x <= 6)
+                    .ok_or_else(|| invalid_value(entry, origin, "a number from 0 to 6"))?;
+            }
+            ("", "scale") => {
+                self.scale = AmountScale::parse(&value)
+                    .ok_or_else(|| invalid_value(entry, origin, "`1`, `k` or `M`"))?;
+            }
+            ("", "timezone") => {
+                self.timezone = TimeZone::parse(&value).ok_or_else(|| {
+                    invalid_value(entry, origin, "`local`, `UTC`, an offset or a zone name")
+                })?;
+            }
+            ("", "refunds") => {
+                self.net_refunds = match value.as_str() {
+                    "net" => true,
+                    "separate" => false,
+                    _ => return Err(invalid_value(entry, origin, "`net` or `separate`")),
+                };
+            }
+            ("", "statement_day") => {
+                self.statement_day = Some(
+                    parse_statement_day(&value)
+                        .ok_or_else(|| invalid_value(entry, origin, "a day from 1 to 31"))?,
+                );
+            }
+            ("", "amortize") => {
+                self.amortize = match value.as_str() {
+                    "true" => true,
+                    "false" => false,
+                    _ => return Err(invalid_value(entry, origin, "`true` or `false`")),
+                };
+            }
+            ("", "budget_tightening") => {
+                self.budget_tightening = value
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|x| *x > 0.0)
+                    .ok_or_else(|| invalid_value(entry, origin, "a positive number"))?;
+            }
+            ("", "profile") => {
+                self.profile = Some(
+                    profiles::builtin(&value)
+                        .ok_or_else(|| invalid_value(entry, origin, "`50/30/20`"))?,
+                );
+            }
+            ("", "daily_allowance") => {
+                self.daily_allowance = Some(
+                    parse_amount(&value)
+                        .ok()
+                        .filter(|x| *x > 0)
+                        .ok_or_else(|| invalid_value(entry, origin, "a positive amount"))?,
+                );
+            }
+            ("", "review_threshold") => {
+                self.review_threshold = Some(
+                    parse_amount(&value)
+                        .ok()
+                        .filter(|x| *x > 0)
+                        .ok_or_else(|| invalid_value(entry, origin, "a positive amount"))?,
+                );
+            }
+            ("", "currency") => {
+                self.currency = Some(value).filter(|x| !x.is_empty());
+            }
+            ("", "note_length") => {
+                self.note_length = value
+                    .parse()
+                    .map_err(|_| invalid_value(entry, origin, "a number of characters"))?;
+            }
+            ("", "moving_average_window") => {
+                self.moving_average_window = value
+                    .parse()
+                    .ok()
+                    .filter(|x| *x > 0)
+                    .ok_or_else(|| invalid_value(entry, origin, "a number of months"))?;
+            }
+            ("", "min_sample") => {
+                self.min_sample = value
+                    .parse()
+                    .map_err(|_| invalid_value(entry, origin, "a number of transactions"))?;
+            }
+            ("", "category_order") => {
+                self.category_order = split_list(&value)
+                    .iter()
+                    .map(|x| Category::from(x.trim()))
+                    .collect();
+            }
+            ("", "footer") => {
+                self.footer = split_list(&value)
+                    .into_iter()
+                    .filter(|x| !x.is_empty()) // `footer = ""` for none
+                    .collect();
+                if self
+                    .footer
+                    .iter()
+                    .any(|x| !FOOTER_FIELDS.contains(&x.as_str()))
+                {
+                    return Err(invalid_value(
+                        entry,
+                        origin,
+                        &format!("some of {}", FOOTER_FIELDS.join(", ")),
+                    ));
+                }
+            }
+            ("", "allowance_exclude") => {
+                self.allowance_exclude = split_list(&value)
+                    .iter()
+                    .map(|x| Category::from(x.trim()))
+                    .collect();
+            }
+            ("csv", "delimiter") => {
+                self.csv.delimiter = match value.as_str() {
+                    "\\t" | "tab" => '\t',
+                    _ if value.chars().count() == 1 => value.chars().next().unwrap(),
+                    _ => return Err(invalid_value(entry, origin, "a single character or `tab`")),
+                };
+            }
+            ("csv", "decimal") => {
+                self.csv.decimal = match value.as_str() {
+                    "." => '.',
+                    "," => ',',
+                    _ => return Err(invalid_value(entry, origin, "`.` or `,`")),
+                };
+            }
+            ("csv", "date_format") => {
+                // Only formats that read back what they write, e.g. `%Y-%m-%d`.
+                let date = NaiveDate::from_ymd_opt(2024, 12, 31).unwrap();
+                let format = CsvFormat {
+                    date_format: value.clone(),
+                    ..CsvFormat::default()
+                };
+                if format.parse_date(&format.format_date(date)) != Some(date) {
+                    return Err(invalid_value(
+                        entry,
+                        origin,
+                        "a date format such as `%Y-%m-%d`",
+                    ));
+                }
+                self.csv.date_format = value;
+            }
+            ("budgets", category) => {
+                let amount =
+                    parse_amount(&value).map_err(|_| invalid_value(entry, origin, "an amount"))?;
+                self.budgets.push((Category::from(category), amount));
+            }
+            ("shares", category) => {
+                let share = parse_share(&value)
+                    .ok_or_else(|| invalid_value(entry, origin, "a share like `<= 35%`"))?;
+                self.share_targets.push((Category::from(category), share));
+            }
+            ("aliases", alias) => {
+                self.aliases
+                    .push((String::from(alias), Category::from(value.as_str())));
+            }
+            ("rules", pattern) => {
+                self.rules
+                    .push((String::from(pattern), Category::from(value.as_str())));
+            }
+            ("shortcuts", name) => {
+                let shortcut = parse_shortcut(name, &entry.value).ok_or_else(|| {
+                    invalid_value(entry, origin, "`amount, category, payment method[, note]`")
+                })?;
+                self.shortcuts.push((String::from(name), shortcut));
+            }
+            ("payment_groups", group) => {
+                // Both `"a, b"` and `"a", "b"` list the methods.
+                for method in split_list(&entry.value).iter().flat_map(|x| split_list(x)) {
+                    self.payment_groups
+                        .push((String::from(method.trim()), String::from(group)));
+                }
+            }
+            ("note_groups", group) => {
+                let patterns = split_list(&entry.value)
+                    .iter()
+                    .flat_map(|x| split_list(x))
+                    .map(|x| String::from(x.trim()))
+                    .filter(|x| !x.is_empty())
+                    .collect::<Vec<_>>();
+                if patterns.is_empty() {
+                    return Err(invalid_value(entry, origin, "a list of note patterns"));
+                }
+                self.note_groups.push((String::from(group), patterns));
+            }
+            ("dates", name) => {
+                let dates = split_list(&entry.value)
+                    .iter()
+                    .flat_map(|x| split_list(x))
+                    .map(|x| SpecialDate::parse(&x))
+                    .collect::<Option<Vec<_>>>()
+                    .filter(|x| !x.is_empty())
+                    .ok_or_else(|| {
+                        invalid_value(
+                            entry,
+                            origin,
+                            "dates like `24/12/2025`, `1/8/2025..15/8/2025` or `every 25`",
+                        )
+                    })?;
+                self.special_dates.push((String::from(name), dates));
+            }
+            ("durables", note) => {
+                let durable = parse_durable(&value).ok_or_else(|| {
+                    invalid_value(entry, origin, "`<n> months`, `<n> uses` or both")
+                })?;
+                self.durables.push((String::from(note), durable));
+            }
+            ("profile", bucket) => {
+                // A `[profile]` section replaces any built-in profile.
+                let parts = split_list(&entry.value);
+                let share = parts[0]
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|x| (0.0..=100.0).contains(x))
+                    .ok_or_else(|| invalid_value(entry, origin, "`<target %>, <category>, ...`"))?;
+                let categories = parts[1..]
+                    .iter()
+                    .map(|x| Category::from(x.as_str()))
+                    .collect();
+                let profile = self.profile.get_or_insert_with(|| Profile {
+                    name: String::from("custom"),
+                    buckets: vec![],
+                });
+                if profile.name != "custom" {
+                    profile.name = String::from("custom");
+                    profile.buckets.clear();
+                }
+                profile
+                    .buckets
+                    .push((String::from(bucket), share, categories));
+                self.provenance
+                    .push((String::from("profile"), Provenance::File(entry.line)));
+            }
+            ("metrics", name) => {
+                let expr = metrics::parse(&value).map_err(|reason| {
+                    diagnostics::error(
+                        "config-value",
+                        Some(Location::new(origin, entry.line)),
+                        format!("Invalid expression for metric `{}`: {}", name, reason),
+                    )
+                })?;
+                self.metrics.push((String::from(name), expr));
+            }
+            _ => {
+                diagnostics::warning(
+                    "config-unknown-key",
+                    Some(Location::new(origin, entry.line)),
+                    format!("Unknown config key `{}`", entry.key),
+                );
+            }
+        }
+        Ok(())
+    }
+
+    pub fn provenance(&self, key: &str) -> Provenance {
+        self.provenance
+            .iter()
+            .rev()
+            .find(|(k, _)| k == key)
+            .map_or(Provenance::Default, |(_, p)| *p)
+    }
+
+    // For settings overridden on the command line.
+    pub fn set_by_flag(&mut self, key: &str) {
+        self.provenance.push((String::from(key), Provenance::Flag));
+    }
+
+    // Methods outside every group are a group of their own.
+    pub fn payment_group<'a>(&'a self, payment_method: &'a str) -> &'a str {
+        self.payment_groups
+            .iter()
+            .find(|(method, _)| method == payment_method)
+            .map_or(payment_method, |(_, group)| group)
+    }
+
+    // Puts the categories of `category_order` first, in that order, keeping the
+    // order of the others (by amount, usually) after them.
+    pub fn sort_categories<T>(&self, categories: &mut [(Category, T)]) {
+        categories.sort_by_key(|(c, _)| {
+            self.category_order
+                .iter()
+                .position(|x| x == c)
+                .unwrap_or(self.category_order.len())
+        });
+    }
+
+    pub fn budget_for(&self, category: &Category) -> Option<i64> {
+        self.budgets
+            .iter()
+            .find(|(c, _)| c == category)
+            .map(|(_, amount)| *amount)
+    }
+
+    // Replaces the whole `[section]` of the config file with `entries`, keeping
+    // everything else (comments included) as it is. The file is created if needed.
+    pub fn write_section(&self, section: &str, entries: &[(String, String)]) -> io::Result<()> {
+        let content = fs::read_to_string(&self.path).unwrap_or_default();
+
+        let mut lines = vec![];
+        let mut in_section = false;
+        let mut written = false;
+        let mut new_section = vec![format!("[{}]", section)];
+        for (key, value) in entries {
+            new_section.push(format!("{} = {}", quote_key(key), value));
+        }
+
+        for line in content.lines() {
+            let trimmed = strip_comment(line).trim();
+            if trimmed.starts_with('[') && trimmed.ends_with(']') {
+                in_section = trimmed[1..trimmed.len() - 1].trim() == section;
+                if in_section {
+                    lines.append(&mut new_section);
+                    written = true;
+                    continue;
+                }
+            }
+            if !in_section {
+                lines.push(String::from(line));
+            }
+        }
+        if !written {
+            if lines.last().is_some_and(|x| !x.trim().is_empty()) {
+                lines.push(String::new());
+            }
+            lines.append(&mut new_section);
+        }
+
+        if let Some(dir) = self.path.parent() {
+            files::create_dir_all(dir)?;
+        }
+        let mut content = lines.join("\n");
+        content.push('\n');
+        files::write_atomically(&self.path, content.as_bytes())
+    }
+
+    pub fn today(&self) -> NaiveDate {
+        self.timezone.today()
+    }
+
+    pub fn round(&self, x: f64) -> f64 {
+        self.rounding.round(x, self.precision)
+    }
+}
@@ -0,0 +1,135 @@
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+use crate::{
+    category_shares,
+    config::Config,
+    diagnostics::{self, Diagnostic},
+    Category, StatsCollection,
+};
+
+// `2024-05` as year and month.
+pub fn parse_month(s: &str) -> Option<(i32, u32)> {
+    let (year, month) = s.split_once('-')?;
+    let year = year.parse().ok()?;
+    let month = month.parse().ok()?;
+    (1..=12).contains(&month).then_some((year, month))
+}
+
+// The month's categories as tab separated values, amounts with the decimal
+// separator of the data files so that the spreadsheet reads them as numbers.
+fn month_tsv(stats: &StatsCollection, month: (i32, u32), config: &Config) -> Option<String> {
+    let (_, monthly) = stats.monthly.iter().find(|(k, _)| *k == month)?;
+    let decimal = |x: String| x.replace('.', &config.csv.decimal.to_string());
+
+    let mut out = String::from("Category\tSpent\tPercentage\tBudget\n");
+    let categories = monthly.ordered_categories(config);
+    for ((c, v), share) in categories
+        .iter()
+        .zip(category_shares(&categories, monthly, config))
+        .filter(|((c, _), _)| *c != Category::Refunds)
+    {
+        let budget = config
+            .budget_for(c)
+            .map(|b| config.csv.format_amount(b))
+            .unwrap_or_default();
+        out.push_str(&format!(
+            "{}\t{}\t{}\t{}\n",
+            c,
+            config.csv.format_amount(*v),
+            decimal(format!("{:.*}", config.precision, share)),
+            budget
+        ));
+    }
+    let refunds = monthly.refunds();
+    if refunds == 0 {
+        out.push_str(&format!(
+            "Total\t{}\t{}\t\n",
+            config.csv.format_amount(monthly.total),
+            decimal(format!("{:.*}", config.precision, 100.0))
+        ));
+    } else {
+        out.push_str(&format!(
+            "Spent\t{}\t{}\t\n",
+            config.csv.format_amount(monthly.spent()),
+            decimal(format!("{:.*}", config.precision, 100.0))
+        ));
+        out.push_str(&format!(
+            "{}\t{}\t\t\n",
+            Category::Refunds,
+            config.csv.format_amount(refunds)
+        ));
+        out.push_str(&format!(
+            "Net\t{}\t\t\n",
+            config.csv.format_amount(monthly.total)
+        ));
+    }
+    Some(out)
+}
+
+// There is no clipboard crate to lean on, so this hands the text to whichever
+// of the usual command line tools is installed.
+const CLIPBOARD_TOOLS: [(&str, &[&str]); 5] = [
+    ("wl-copy", &[]),
+    ("xclip", &["-selection", "clipboard"]),
+    ("xsel", &["--clipboard", "--input"]),
+    ("pbcopy", &[]),
+    ("clip.exe", &[]),
+];
+
+fn to_clipboard(text: &str) -> Option<&'static str> {
+    for (tool, args) in CLIPBOARD_TOOLS {
+        let Ok(mut child) = Command::new(tool)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        else {
+            continue;
+        };
+        let written = child
+            .stdin
+            .take()
+            .is_some_and(|mut stdin| stdin.write_all(text.as_bytes()).is_ok());
+        if child.wait().is_ok_and(|status| status.success()) && written {
+            return Some(tool);
+        }
+    }
+    None
+}
+
+pub fn run(stats: &StatsCollection, month: (i32, u32), config: &Config) -> Result<(), Diagnostic> {
+    let label = format!("{:04}-{:02}", month.0, month.1);
+    let Some(tsv) = month_tsv(stats, month, config) else {
+        return Err(diagnostics::error(
+            "copy-no-data",
+            None,
+            format!("No transactions in {}", label),
+        ));
+    };
+    match to_clipboard(&tsv) {
+        Some(tool) => println!(
+            "Categories of {} copied to the clipboard (with `{}`).",
+            label, tool
+        ),
+        None => {
+            diagnostics::warning(
+                "copy-no-clipboard",
+                None,
+                format!(
+                    "No clipboard tool found (tried {}), printing instead.",
+                    CLIPBOARD_TOOLS
+                        .iter()
+                        .map(|(tool, _)| format!("`{}`", tool))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            );
+            print!("{}", tsv);
+        }
+    }
+    Ok(())
+}
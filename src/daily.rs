@@ -0,0 +1,186 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{Datelike, NaiveDate};
+
+use crate::{source::CsvFormat, Category, Transaction};
+
+// Days with at least one expense. Refunds alone don't make a spending day.
+pub fn spending_days(transactions: &[Transaction]) -> HashSet<NaiveDate> {
+    transactions
+        .iter()
+        .filter(|t| t.value > 0)
+        .map(|t| t.date)
+        .collect()
+}
+
+// Each transaction spread evenly over the days it is valid for, from its date
+// to its end date, in cents per day.
+pub fn allocate<'a>(
+    transactions: impl IntoIterator<Item = &'a Transaction>,
+) -> HashMap<NaiveDate, f64> {
+    let mut allocation: HashMap<NaiveDate, f64> = HashMap::new();
+    for t in transactions {
+        let days = (t.end_date - t.date).num_days() + 1;
+        for day in t.date.iter_days().take(days as usize) {
+            *allocation.entry(day).or_default() += t.value as f64 / days as f64;
+        }
+    }
+    allocation
+}
+
+// What `allocate` puts on the `days` days from `from` on, in cents.
+pub fn allocated(allocation: &HashMap<NaiveDate, f64>, from: NaiveDate, days: i64) -> i64 {
+    from.iter_days()
+        .take(days.max(0) as usize)
+        .filter_map(|d| allocation.get(&d))
+        .sum::<f64>()
+        .round() as i64
+}
+
+// No-spend days between `from` and `to`, both included.
+pub fn count_no_spend_days(spending: &HashSet<NaiveDate>, from: NaiveDate, to: NaiveDate) -> u32 {
+    from.iter_days()
+        .take_while(|d| *d <= to)
+        .filter(|d| !spending.contains(d))
+        .count() as u32
+}
+
+#[derive(Debug, Default)]
+pub struct Streaks {
+    pub current: i64, // no-spend days up to and including today
+    pub longest: i64,
+    pub longest_end: Option<NaiveDate>, // last day of the longest run
+}
+
+pub fn no_spend_streaks(
+    spending: &HashSet<NaiveDate>,
+    from: NaiveDate,
+    today: NaiveDate,
+) -> Streaks {
+    let mut streaks = Streaks::default();
+    let mut run = 0;
+    for day in from.iter_days().take_while(|d| *d <= today) {
+        if spending.contains(&day) {
+            run = 0;
+            continue;
+        }
+        run += 1;
+        if run > streaks.longest {
+            streaks.longest = run;
+            streaks.longest_end = Some(day);
+        }
+    }
+    streaks.current = run;
+    streaks
+}
+
+// One day of the current month measured against the daily allowance.
+#[derive(Debug)]
+pub struct AllowanceDay {
+    pub date: NaiveDate,
+    pub spent: i64,
+    pub balance: i64, // allowance accumulated since the 1st minus spending
+}
+
+impl AllowanceDay {
+    pub fn is_over(&self, allowance: i64) -> bool {
+        self.spent > allowance
+    }
+}
+
+// From the first of the month containing `today` up to `today`. Categories in
+// `excluded` (typically rent and other fixed costs) don't count.
+pub fn allowance_balance(
+    transactions: &[Transaction],
+    allowance: i64,
+    excluded: &[Category],
+    today: NaiveDate,
+) -> Vec<AllowanceDay> {
+    let month_start = today.with_day(1).unwrap();
+    let mut days = vec![];
+    let mut balance = 0;
+    for date in month_start.iter_days().take_while(|d| *d <= today) {
+        let spent = transactions
+            .iter()
+            .filter(|t| t.date == date && !excluded.contains(&t.category))
+            .map(|t| t.value)
+            .sum::<i64>();
+        balance += allowance - spent;
+        days.push(AllowanceDay {
+            date,
+            spent,
+            balance,
+        });
+    }
+    days
+}
+
+// Per-day series from `from` to `to` as a CSV file: the total, its average
+// over the 30 days ending that day (fewer at the start of the series) and one
+// column per category. With `amortize`, days are what `allocate` puts on them.
+pub fn daily_csv(
+    transactions: &[Transaction],
+    from: NaiveDate,
+    to: NaiveDate,
+    amortize: bool,
+    format: &CsvFormat,
+) -> String {
+    let delimiter = format.delimiter.to_string();
+    let mut categories = transactions
+        .iter()
+        .map(|t| t.category.clone())
+        .collect::<Vec<_>>();
+    categories.sort_by_key(|c| c.to_string());
+    categories.dedup();
+
+    let days = from
+        .iter_days()
+        .take_while(|d| *d <= to)
+        .collect::<Vec<_>>();
+    let mut totals = vec![0; days.len()];
+    let mut by_category = vec![vec![0; categories.len()]; days.len()];
+    if amortize {
+        let allocation = allocate(transactions);
+        for (i, date) in days.iter().enumerate() {
+            totals[i] = allocated(&allocation, *date, 1);
+        }
+        for (j, category) in categories.iter().enumerate() {
+            let allocation = allocate(transactions.iter().filter(|t| t.category == *category));
+            for (i, date) in days.iter().enumerate() {
+                by_category[i][j] = allocated(&allocation, *date, 1);
+            }
+        }
+    } else {
+        for t in transactions
+            .iter()
+            .filter(|t| from <= t.date && t.date <= to)
+        {
+            let day = (t.date - from).num_days() as usize;
+            totals[day] += t.value;
+            let category = categories.iter().position(|c| *c == t.category).unwrap();
+            by_category[day][category] += t.value;
+        }
+    }
+
+    let mut header = vec![
+        String::from("Date"),
+        String::from("Total"),
+        String::from("Last 30 days average"),
+    ];
+    header.extend(categories.iter().map(|c| c.to_string()));
+    let mut out = header.join(&delimiter);
+    out.push('\n');
+    for (i, date) in days.iter().enumerate() {
+        let window = &totals[i.saturating_sub(29)..=i];
+        let average = (window.iter().sum::<i64>() as f64 / window.len() as f64).round() as i64;
+        let mut row = vec![
+            format.format_date(*date),
+            format.format_amount(totals[i]),
+            format.format_amount(average),
+        ];
+        row.extend(by_category[i].iter().map(|x| format.format_amount(*x)));
+        out.push_str(&row.join(&delimiter));
+        out.push('\n');
+    }
+    out
+}
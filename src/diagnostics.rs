@@ -0,0 +1,204 @@
+use std::{
+    cell::RefCell,
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+use crate::json;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Info => "info",
+                Self::Warning => "warning",
+                Self::Error => "error",
+            }
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Location {
+    pub origin: String, // file name, `<stdin>`, ...
+    pub line: Option<usize>,
+}
+
+impl Location {
+    pub fn new(origin: &str, line: usize) -> Self {
+        Self {
+            origin: String::from(origin),
+            line: Some(line),
+        }
+    }
+
+    pub fn file(origin: &str) -> Self {
+        Self {
+            origin: String::from(origin),
+            line: None,
+        }
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}", self.origin, line),
+            None => write!(f, "{}", self.origin),
+        }
+    }
+}
+
+// Anything worth telling the user that is not part of the regular output.
+// `code` is a stable, machine readable identifier (e.g. `parse-date`), so
+// front-ends can react to specific problems without matching on messages.
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+    pub code: &'static str,
+    pub severity: Severity,
+    pub location: Option<Location>,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn to_json(&self) -> String {
+        let location = match &self.location {
+            Some(l) => format!(
+                "{{\"origin\":{},\"line\":{}}}",
+                json::string(&l.origin),
+                l.line
+                    .map(|x| x.to_string())
+                    .unwrap_or(String::from("null"))
+            ),
+            None => String::from("null"),
+        };
+        format!(
+            "{{\"code\":{},\"severity\":{},\"location\":{},\"message\":{}}}",
+            json::string(self.code),
+            json::string(&self.severity.to_string()),
+            location,
+            json::string(&self.message)
+        )
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[{}] {}",
+            self.severity.to_string().to_uppercase(),
+            self.message
+        )?;
+        if let Some(location) = &self.location {
+            write!(f, " in {}", location)?;
+        }
+        Ok(())
+    }
+}
+
+static JSON_OUTPUT: AtomicBool = AtomicBool::new(false);
+static PRINT: AtomicBool = AtomicBool::new(false);
+static COLLECTED: Mutex<Vec<Diagnostic>> = Mutex::new(Vec::new());
+
+thread_local! {
+    static CAPTURED: RefCell<Option<Vec<Diagnostic>>> = const { RefCell::new(None) };
+}
+
+// With `--format json` every diagnostic is written as one JSON object per line on stderr.
+pub fn set_json_output(enabled: bool) {
+    JSON_OUTPUT.store(enabled, Ordering::Relaxed);
+}
+
+// The command line prints diagnostics as they come. As a library nothing is
+// printed, they are only collected for `take`.
+pub fn set_print(enabled: bool) {
+    PRINT.store(enabled, Ordering::Relaxed);
+}
+
+pub fn emit(diagnostic: Diagnostic) {
+    let diagnostic = CAPTURED.with_borrow_mut(|captured| match captured {
+        Some(held) => {
+            held.push(diagnostic);
+            None
+        }
+        None => Some(diagnostic),
+    });
+    let Some(diagnostic) = diagnostic else {
+        return;
+    };
+    if PRINT.load(Ordering::Relaxed) {
+        print(&diagnostic);
+    }
+    COLLECTED.lock().unwrap().push(diagnostic);
+}
+
+fn print(diagnostic: &Diagnostic) {
+    if JSON_OUTPUT.load(Ordering::Relaxed) {
+        eprintln!("{}", diagnostic.to_json());
+    } else if diagnostic.severity == Severity::Info {
+        println!("{}", diagnostic);
+    } else {
+        eprintln!("{}", diagnostic);
+    }
+}
+
+// Runs `f` holding back what it emits, so that work done in parallel can
+// report in a fixed order with `replay`.
+pub fn capture<T>(f: impl FnOnce() -> T) -> (T, Vec<Diagnostic>) {
+    CAPTURED.set(Some(vec![]));
+    let result = f();
+    (result, CAPTURED.take().unwrap_or_default())
+}
+
+pub fn replay(diagnostics: Vec<Diagnostic>) {
+    for diagnostic in diagnostics {
+        emit(diagnostic);
+    }
+}
+
+// Everything emitted so far, for callers that want to present diagnostics themselves.
+pub fn take() -> Vec<Diagnostic> {
+    std::mem::take(&mut *COLLECTED.lock().unwrap())
+}
+
+pub fn info(code: &'static str, location: Option<Location>, message: String) {
+    emit(Diagnostic {
+        code,
+        severity: Severity::Info,
+        location,
+        message,
+    });
+}
+
+pub fn warning(code: &'static str, location: Option<Location>, message: String) {
+    emit(Diagnostic {
+        code,
+        severity: Severity::Warning,
+        location,
+        message,
+    });
+}
+
+// Errors are not emitted but returned, up to the command line's `run`, which
+// prints them and exits (library callers get them as the `Err`).
+pub fn error(code: &'static str, location: Option<Location>, message: String) -> Diagnostic {
+    Diagnostic {
+        code,
+        severity: Severity::Error,
+        location,
+        message,
+    }
+}
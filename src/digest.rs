@@ -0,0 +1,195 @@
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::{Datelike, NaiveDate, NaiveDateTime};
+
+use crate::{
+    config::Config,
+    days_in_month,
+    diagnostics::{self, Diagnostic, Location},
+    files, hash, note_for_terminal, Category, Transaction,
+};
+
+// What `--since` refers to.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Since {
+    #[default]
+    LastRun,
+    Date(NaiveDate),
+}
+
+impl Since {
+    pub fn parse(s: &str) -> Option<Self> {
+        if s == "last-run" {
+            Some(Self::LastRun)
+        } else {
+            NaiveDate::parse_from_str(s, "%d/%m/%Y")
+                .ok()
+                .map(Self::Date)
+        }
+    }
+}
+
+// The watermark: when the last digest ran and which transactions existed then.
+// One file per set of inputs, named after a hash of their canonical paths.
+fn watermark_path(state_key: &str) -> PathBuf {
+    files::state_dir().join(format!(
+        "digest-{:016x}.txt",
+        hash::fnv1a(state_key.as_bytes())
+    ))
+}
+
+fn read_watermark(path: &Path) -> Option<(NaiveDateTime, HashSet<String>)> {
+    let content = fs::read_to_string(path).ok()?;
+    let mut lines = content.lines();
+    let last_run = NaiveDateTime::parse_from_str(lines.next()?, "%Y-%m-%dT%H:%M:%S").ok()?;
+    Some((last_run, lines.map(String::from).collect()))
+}
+
+// Not in read-only mode, where the digest is shown but the next one still
+// compares against the previous watermark.
+fn write_watermark(path: &Path, ids: &[String], config: &Config) -> Result<(), Diagnostic> {
+    if files::is_read_only() {
+        return Ok(());
+    }
+    let mut content = config
+        .timezone
+        .now()
+        .format("%Y-%m-%dT%H:%M:%S")
+        .to_string();
+    for id in ids {
+        content.push('\n');
+        content.push_str(id);
+    }
+    content.push('\n');
+    let result = files::create_dir_all(&files::state_dir())
+        .and_then(|_| files::write_atomically(path, content.as_bytes()));
+    if let Err(e) = result {
+        return Err(diagnostics::error(
+            "io-write",
+            Some(Location::file(&path.display().to_string())),
+            format!("Could not save digest state: {}", e),
+        ));
+    }
+    Ok(())
+}
+
+// Month-end spending if the current month continues at the pace so far.
+fn projection<'a>(transactions: impl Iterator<Item = &'a Transaction>, today: NaiveDate) -> f64 {
+    let spent = transactions
+        .filter(|t| t.date.year() == today.year() && t.date.month() == today.month())
+        .map(|t| t.value)
+        .sum::<i64>();
+    spent as f64 / 100.0 / today.day() as f64 * days_in_month(today) as f64
+}
+
+type NewFilter = Box<dyn Fn(&Transaction) -> bool>;
+
+// `transactions` are what the period and filters left, `read_ids` every
+// transaction read, which is what the watermark records.
+pub fn run(
+    transactions: &[Transaction],
+    read_ids: &[String],
+    state_key: &str,
+    since: &Since,
+    config: &Config,
+) -> Result<(), Diagnostic> {
+    let today = config.today();
+    let watermark = watermark_path(state_key);
+
+    let (title, is_new): (String, NewFilter) = match since {
+        Since::Date(date) => {
+            let date = *date;
+            (
+                format!("dated since {}", date.format("%d/%m/%Y")),
+                Box::new(move |t: &Transaction| t.date >= date),
+            )
+        }
+        Since::LastRun => match read_watermark(&watermark) {
+            Some((last_run, seen)) => (
+                format!("added since {}", last_run.format("%d/%m/%Y %H:%M")),
+                Box::new(move |t: &Transaction| !seen.contains(&t.id)),
+            ),
+            None => {
+                write_watermark(&watermark, read_ids, config)?;
+                let message = if files::is_read_only() {
+                    "No baseline yet: no previous digest run, and none is saved in read-only mode."
+                } else {
+                    "No baseline yet: no previous digest run; the next one will show what is added from now on."
+                };
+                diagnostics::info("digest-first-run", None, String::from(message));
+                return Ok(());
+            }
+        },
+    };
+
+    let new = transactions
+        .iter()
+        .filter(|t| is_new(t))
+        .collect::<Vec<_>>();
+
+    println!("DIGEST");
+    println!("======");
+    println!(
+        "  {} transaction(s) {}, {:.2} in total",
+        new.len(),
+        title,
+        new.iter().map(|t| t.value).sum::<i64>() as f64 / 100.0
+    );
+
+    if !new.is_empty() {
+        let mut by_category: Vec<(Category, i64)> = vec![];
+        for t in new.iter() {
+            match by_category.iter_mut().find(|(c, _)| *c == t.category) {
+                Some((_, v)) => *v += t.value,
+                None => by_category.push((t.category.clone(), t.value)),
+            }
+        }
+        by_category.sort_by_key(|x| -x.1);
+        config.sort_categories(&mut by_category);
+
+        println!("    - Categories:");
+        let max_len = by_category
+            .iter()
+            .map(|x| x.0.to_string().len())
+            .max()
+            .unwrap_or_default();
+        for (c, v) in by_category.iter() {
+            println!(
+                "       - {:<2$}: {:7.2}",
+                c.to_string(),
+                *v as f64 / 100.0,
+                max_len
+            );
+        }
+
+        println!("    - Transactions:");
+        for t in new.iter() {
+            println!(
+                "       - {} {:7.2} {} ({})",
+                t.date.format("%d/%m/%Y"),
+                t.value as f64 / 100.0,
+                t.category,
+                note_for_terminal(&t.note, config)
+            );
+        }
+    }
+
+    let with_new = projection(transactions.iter(), today);
+    let without_new = projection(transactions.iter().filter(|t| !is_new(t)), today);
+    println!(
+        "  Projected spending for {}: {:.2} ({:+.2} from the above)",
+        today.format("%B"),
+        with_new,
+        with_new - without_new
+    );
+    println!("======");
+
+    if *since == Since::LastRun {
+        write_watermark(&watermark, read_ids, config)?;
+    }
+    Ok(())
+}
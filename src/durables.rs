@@ -0,0 +1,64 @@
+use chrono::{Datelike, NaiveDate};
+
+use crate::{config::Config, Transaction};
+
+// What a durable purchase has cost so far per month owned and per use. All
+// transactions with the durable's note count towards its price, so repairs
+// and accessories can be logged under the same note.
+#[derive(Debug)]
+pub struct DurableCost {
+    pub name: String,
+    pub bought: NaiveDate,
+    pub price: i64,
+    pub months_owned: u32,
+    pub uses: Option<u32>,
+    pub lifetime_left: Option<i64>, // months, negative once outlived
+}
+
+impl DurableCost {
+    pub fn per_month(&self) -> f64 {
+        self.price as f64 / 100.0 / self.months_owned as f64
+    }
+
+    pub fn per_use(&self) -> Option<f64> {
+        self.uses
+            .map(|uses| self.price as f64 / 100.0 / uses as f64)
+    }
+}
+
+// Whole months from `from` to `to`.
+fn months_between(from: NaiveDate, to: NaiveDate) -> i64 {
+    let months = (to.year() - from.year()) as i64 * 12 + to.month() as i64 - from.month() as i64;
+    if to.day() < from.day() {
+        months - 1
+    } else {
+        months
+    }
+}
+
+pub fn durable_costs(transactions: &[Transaction], config: &Config) -> Vec<DurableCost> {
+    let today = config.today();
+    let mut costs = vec![];
+    for (note, durable) in config.durables.iter() {
+        let purchases = transactions
+            .iter()
+            .filter(|t| t.note.eq_ignore_ascii_case(note))
+            .collect::<Vec<_>>();
+        let Some(bought) = purchases.iter().map(|t| t.date).min() else {
+            continue;
+        };
+        let months_owned = months_between(bought, today);
+        costs.push(DurableCost {
+            name: note.clone(),
+            bought,
+            price: purchases.iter().map(|t| t.value).sum(),
+            months_owned: months_owned.max(1) as u32,
+            uses: durable.uses,
+            lifetime_left: durable
+                .lifetime_months
+                .map(|lifetime| lifetime as i64 - months_owned),
+        });
+    }
+    costs.sort_by_key(|x| x.bought);
+    costs
+}
@@ -0,0 +1,188 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+static DRY_RUN: AtomicBool = AtomicBool::new(false);
+static READ_ONLY: AtomicBool = AtomicBool::new(false);
+
+// With `--dry-run` nothing is written: every write only reports what it would do.
+pub fn set_dry_run(enabled: bool) {
+    DRY_RUN.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_dry_run() -> bool {
+    DRY_RUN.load(Ordering::Relaxed)
+}
+
+// With `--read-only` every write fails, so that not even state files change.
+pub fn set_read_only(enabled: bool) {
+    READ_ONLY.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_read_only() -> bool {
+    READ_ONLY.load(Ordering::Relaxed)
+}
+
+// To be called before writing anything that doesn't go through `write_atomically`.
+pub fn check_writable() -> io::Result<()> {
+    if is_read_only() {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "refusing to write in read-only mode",
+        ));
+    }
+    Ok(())
+}
+
+// Hidden sibling of `path` that is only ever locked, never written or
+// renamed, so that the lock outlives `write_atomically` replacing the data file.
+pub fn lock_path_for(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|x| x.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{}.lock", name))
+}
+
+// Advisory lock on the data file, held until the returned `File` is dropped.
+// Readers (report generation) take a shared lock, writers an exclusive one
+// around the whole read-modify-write, so a cron-driven report never sees a
+// half-rewritten file. In read-only and dry-run mode the lock file is not
+// created, and readers also go without when its directory is read-only.
+pub fn lock_data_file(path: &Path, exclusive: bool) -> io::Result<Option<File>> {
+    let lock_path = lock_path_for(path);
+    let mut options = OpenOptions::new();
+    options.read(true);
+    if !(is_read_only() || is_dry_run()) {
+        options.write(true).create(true).truncate(false);
+    }
+    let file = match options.open(&lock_path) {
+        Ok(file) => file,
+        Err(e) if exclusive && e.kind() != io::ErrorKind::NotFound => return Err(e),
+        Err(_) => match File::open(&lock_path) {
+            Ok(file) => file,
+            Err(_) => return Ok(None),
+        },
+    };
+    if exclusive {
+        file.lock()?;
+    } else {
+        file.lock_shared()?;
+    }
+    Ok(Some(file))
+}
+
+// Hidden sibling of `path`, in the same directory so that the final rename
+// stays on one filesystem. The extension is kept because some writers (e.g.
+// the plotting backend) pick the output format from it.
+pub fn temp_path_for(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|x| x.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut temp_name = format!(".{}.{}.tmp", name, process::id());
+    if let Some(ext) = path.extension() {
+        temp_name.push('.');
+        temp_name.push_str(&ext.to_string_lossy());
+    }
+    path.with_file_name(temp_name)
+}
+
+// Moves a fully written temporary file over `path`: fsync, rename, then fsync
+// the directory so the rename itself survives a crash.
+pub fn commit_temp_file(temp_path: &Path, path: &Path) -> io::Result<()> {
+    File::open(temp_path)?.sync_all()?;
+    if let Err(e) = fs::rename(temp_path, path) {
+        let _ = fs::remove_file(temp_path);
+        return Err(e);
+    }
+    if let Some(dir) = path.parent() {
+        let dir = if dir.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            dir
+        };
+        // Not every platform allows opening a directory, so this is best effort.
+        if let Ok(dir) = File::open(dir) {
+            let _ = dir.sync_all();
+        }
+    }
+    Ok(())
+}
+
+// Lines removed and added going from `old` to `new`, counting everything
+// between the common first and last lines as changed. Good enough to tell an
+// append from a rewrite.
+fn line_changes(old: &str, new: &str) -> (usize, usize) {
+    let old = old.lines().collect::<Vec<_>>();
+    let new = new.lines().collect::<Vec<_>>();
+    let prefix = old
+        .iter()
+        .zip(new.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    (old.len() - prefix - suffix, new.len() - prefix - suffix)
+}
+
+fn describe_write(path: &Path, contents: &[u8]) {
+    let changes = match (fs::read(path), std::str::from_utf8(contents)) {
+        (Ok(old), Ok(new)) => match std::str::from_utf8(&old) {
+            Ok(old) => match line_changes(old, new) {
+                (0, 0) => String::from(", which already has this content"),
+                (removed, added) => {
+                    format!(", replacing it: -{} +{} lines", removed, added)
+                }
+            },
+            Err(_) => String::from(", replacing it"),
+        },
+        (Ok(_), Err(_)) => String::from(", replacing it"),
+        (Err(_), _) => String::new(),
+    };
+    println!(
+        "Would write {} bytes to `{}`{}.",
+        contents.len(),
+        path.display(),
+        changes
+    );
+}
+
+pub fn create_dir_all(dir: &Path) -> io::Result<()> {
+    if is_dry_run() || is_read_only() {
+        return Ok(());
+    }
+    fs::create_dir_all(dir)
+}
+
+pub fn write_atomically(path: &Path, contents: &[u8]) -> io::Result<()> {
+    check_writable()?;
+    if is_dry_run() {
+        describe_write(path, contents);
+        return Ok(());
+    }
+    let temp_path = temp_path_for(path);
+    let result = File::create(&temp_path).and_then(|mut f| f.write_all(contents));
+    if let Err(e) = result {
+        let _ = fs::remove_file(&temp_path);
+        return Err(e);
+    }
+    commit_temp_file(&temp_path, path)
+}
+
+// Where battista keeps what it needs to remember between runs.
+pub fn state_dir() -> PathBuf {
+    std::env::var_os("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/state")))
+        .unwrap_or_default()
+        .join(env!("CARGO_PKG_NAME"))
+}
@@ -0,0 +1,180 @@
+use std::io::{self, BufRead, Write};
+
+use chrono::NaiveDate;
+
+use crate::{
+    config::{split_list, Config},
+    get_stats, period, print_stats,
+    source::format_amount,
+    Category, Transaction,
+};
+
+// Which transactions to look at. Empty lists and missing dates don't restrict
+// anything, so the default filter takes everything.
+#[derive(Clone, Debug, Default)]
+pub struct Filter {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub categories: Vec<Category>,
+    pub payment_methods: Vec<String>,
+    pub note: Option<String>, // substring, ignoring case
+}
+
+impl Filter {
+    pub fn matches(&self, t: &Transaction) -> bool {
+        self.from.is_none_or(|from| t.date >= from)
+            && self.to.is_none_or(|to| t.date <= to)
+            && (self.categories.is_empty() || self.categories.contains(&t.category))
+            && (self.payment_methods.is_empty() || self.payment_methods.contains(&t.payment_method))
+            && self
+                .note
+                .as_ref()
+                .is_none_or(|note| t.note.to_lowercase().contains(&note.to_lowercase()))
+    }
+
+    // The selected transactions, in their original order, ready for `get_stats`.
+    pub fn apply(&self, transactions: &[Transaction]) -> Vec<Transaction> {
+        transactions
+            .iter()
+            .filter(|t| self.matches(t))
+            .cloned()
+            .collect()
+    }
+
+    // `None` when it takes everything.
+    pub fn describe(&self) -> Option<String> {
+        let mut parts = vec![];
+        if let Some(from) = self.from {
+            parts.push(format!("from {}", from.format("%d/%m/%Y")));
+        }
+        if let Some(to) = self.to {
+            parts.push(format!("to {}", to.format("%d/%m/%Y")));
+        }
+        if !self.categories.is_empty() {
+            let categories = self
+                .categories
+                .iter()
+                .map(|c| c.to_string())
+                .collect::<Vec<_>>();
+            parts.push(format!("category {}", categories.join(", ")));
+        }
+        if !self.payment_methods.is_empty() {
+            parts.push(format!("method {}", self.payment_methods.join(", ")));
+        }
+        if let Some(note) = &self.note {
+            parts.push(format!("note \"{}\"", note));
+        }
+        (!parts.is_empty()).then(|| parts.join("; "))
+    }
+}
+
+fn print_help() {
+    println!("  category <a, b, ...>  only these categories");
+    println!("  method <a, b, ...>    only these payment methods");
+    println!("  note <text>           only notes containing <text>");
+    println!("  from <dd/mm/yyyy>     only from this day on");
+    println!("  to <dd/mm/yyyy>       only up to this day");
+    println!("  period <period>       only `last month`, `ytd`, `2024-q2`, `last 90 days`, ...");
+    println!("  clear [what]          drop one restriction (e.g. `clear note`), or all of them");
+    println!("  show                  statistics of the selected transactions");
+    println!("  quit");
+}
+
+// `explore`: narrows down the transactions read once at startup, one
+// restriction at a time, recomputing the statistics of what is left on demand.
+pub fn run(transactions: &[Transaction], config: &Config) {
+    println!(
+        "{} transaction(s) loaded, type `help` for commands.",
+        transactions.len()
+    );
+    let mut filter = Filter::default();
+    let mut lines = io::stdin().lock().lines();
+    loop {
+        print!("> ");
+        io::stdout().flush().unwrap();
+        let Some(Ok(line)) = lines.next() else {
+            println!();
+            break;
+        };
+        let (command, value) = line.trim().split_once(' ').unwrap_or((line.trim(), ""));
+        let value = value.trim();
+        let date = |s: &str| NaiveDate::parse_from_str(s, "%d/%m/%Y").ok();
+        match command {
+            "" => continue,
+            "quit" | "exit" => break,
+            "help" => {
+                print_help();
+                continue;
+            }
+            "show" => {
+                let selected = filter.apply(transactions);
+                if selected.is_empty() {
+                    println!("No transaction matches.");
+                } else {
+                    print_stats(&get_stats(&selected, config), config);
+                }
+                continue;
+            }
+            "category" => {
+                filter.categories = split_list(value)
+                    .iter()
+                    .map(|x| Category::from(x.as_str()))
+                    .collect()
+            }
+            "method" => filter.payment_methods = split_list(value),
+            "note" => filter.note = (!value.is_empty()).then(|| String::from(value)),
+            "from" | "to" => {
+                let Some(d) = date(value) else {
+                    println!("Invalid date `{}` (expected e.g. 31/12/2025).", value);
+                    continue;
+                };
+                if command == "from" {
+                    filter.from = Some(d);
+                } else {
+                    filter.to = Some(d);
+                }
+            }
+            "period" => {
+                let Some((from, to)) = period::parse(value, config.today()) else {
+                    println!(
+                        "Invalid period `{}` (expected e.g. `last month`, `2024-q2`).",
+                        value
+                    );
+                    continue;
+                };
+                filter.from = Some(from);
+                filter.to = Some(to);
+            }
+            "clear" => match value {
+                "" => filter = Filter::default(),
+                "category" => filter.categories.clear(),
+                "method" => filter.payment_methods.clear(),
+                "note" => filter.note = None,
+                "from" => filter.from = None,
+                "to" => filter.to = None,
+                "period" => (filter.from, filter.to) = (None, None),
+                _ => {
+                    println!("Nothing called `{}` to clear.", value);
+                    continue;
+                }
+            },
+            _ => {
+                println!("Unknown command `{}`, type `help` for commands.", command);
+                continue;
+            }
+        }
+
+        let (count, total) = transactions
+            .iter()
+            .filter(|t| filter.matches(t))
+            .fold((0, 0), |(count, total), t| (count + 1, total + t.value));
+        println!(
+            "{}: {} transaction(s), {} in total",
+            filter
+                .describe()
+                .unwrap_or_else(|| String::from("everything")),
+            count,
+            format_amount(total)
+        );
+    }
+}
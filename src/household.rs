@@ -0,0 +1,96 @@
+use std::path::Path;
+
+use crate::{
+    categorize::Reclassification,
+    config::Config,
+    diagnostics::{self, Diagnostic, Location},
+    files, get_stats, write_tex_stats, Transaction,
+};
+
+// `household`: with one data file per person, the report of everyone together
+// and one per person, all from the same transactions (so over the same
+// period) into `household/` next to the first file.
+pub fn run(
+    out_path: &Path,
+    transactions: &[Transaction],
+    reclassified: &[Reclassification],
+    fingerprints: &[(String, String)],
+    filters: &[String],
+    config: &Config,
+) -> Result<(), Diagnostic> {
+    let mut people: Vec<&str> = vec![];
+    for t in transactions {
+        if !people.contains(&t.source.as_str()) {
+            people.push(&t.source);
+        }
+    }
+    if people.len() < 2 {
+        return Err(diagnostics::error(
+            "household-one-person",
+            None,
+            String::from("`household` needs one data file per person"),
+        ));
+    }
+
+    let dir = out_path.with_file_name("household");
+    if let Err(e) = files::create_dir_all(&dir) {
+        return Err(diagnostics::error(
+            "io-write",
+            Some(Location::file(&dir.display().to_string())),
+            format!("Could not create directory: {}", e),
+        ));
+    }
+
+    let household = dir.join("household.tex");
+    write_tex_stats(
+        &household,
+        &get_stats(transactions, config),
+        &people.join(", "),
+        fingerprints,
+        filters,
+        config,
+        reclassified,
+    )?;
+    if !files::is_dry_run() {
+        println!("Household report saved in `{}`.", household.display());
+    }
+
+    for person in people {
+        let selected = transactions
+            .iter()
+            .filter(|t| t.source == person)
+            .cloned()
+            .collect::<Vec<_>>();
+        let reclassified = reclassified
+            .iter()
+            .filter(|r| selected.iter().any(|t| t.id == r.transaction_id))
+            .cloned()
+            .collect::<Vec<_>>();
+        // Only the person's own file, so that their report can be verified alone.
+        let fingerprints = fingerprints
+            .iter()
+            .filter(|(name, _)| {
+                Path::new(name)
+                    .file_stem()
+                    .is_some_and(|x| x.to_string_lossy() == person)
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        let mut filters = filters.to_vec();
+        filters.push(format!("only {}", person));
+        let path = dir.join(format!("{}.tex", person));
+        write_tex_stats(
+            &path,
+            &get_stats(&selected, config),
+            person,
+            &fingerprints,
+            &filters,
+            config,
+            &reclassified,
+        )?;
+        if !files::is_dry_run() {
+            println!("Report of {} saved in `{}`.", person, path.display());
+        }
+    }
+    Ok(())
+}
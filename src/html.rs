@@ -0,0 +1,516 @@
+use std::fmt::Write;
+
+use strum::IntoEnumIterator;
+
+use crate::{
+    categorize, category_label, category_shares, config::Config, note_label, report::Section,
+    shares, transactions_label, truncate_note, Category, Links, Stats, StatsCollection,
+};
+
+const STYLE: &str = "body { font-family: sans-serif; max-width: 50em; margin: auto; } \
+    table { border-collapse: collapse; margin: 1em 0; } \
+    caption { font-weight: bold; } \
+    th, td { border-bottom: 1px solid #ccc; padding: 0.2em 0.8em; text-align: left; } \
+    td.amount { text-align: right; }";
+
+// The report of `render_tex_stats` as a single HTML page, for the sections
+// that are tables and lists. Charts and the other sections are only written
+// to the TeX report.
+pub fn render_html_stats(
+    stats: &StatsCollection,
+    title: &str,
+    config: &Config,
+    sections: &[Section],
+) -> String {
+    let links = Links::new(sections);
+    let mut buf = String::new();
+    writeln!(buf, "<!DOCTYPE html>").unwrap();
+    writeln!(buf, "<html lang=\"en\">").unwrap();
+    writeln!(buf, "<head>").unwrap();
+    writeln!(buf, "  <meta charset=\"utf-8\">").unwrap();
+    writeln!(
+        buf,
+        "  <title>Spending report from {}</title>",
+        escape_html(title)
+    )
+    .unwrap();
+    writeln!(buf, "  <style>{}</style>", STYLE).unwrap();
+    writeln!(buf, "</head>").unwrap();
+    writeln!(buf, "<body>").unwrap();
+    writeln!(
+        buf,
+        "  <h1>Spending report from <code>{}</code></h1>",
+        escape_html(title)
+    )
+    .unwrap();
+    writeln!(
+        buf,
+        "  <p>{} {}, {}</p>",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION"),
+        config.locale.long_date(config.today())
+    )
+    .unwrap();
+    for section in Section::iter().filter(|x| sections.contains(x)) {
+        match section {
+            Section::Overview => write_overview(&mut buf, stats, config),
+            Section::Uncategorized => write_uncategorized(&mut buf, stats, config, links),
+            Section::Yearly => write_yearly(&mut buf, stats, config, links),
+            Section::Monthly => write_monthly(&mut buf, stats, config, links),
+            Section::Transactions => write_transactions(&mut buf, stats, config, links),
+            Section::FullNotes => write_full_notes(&mut buf, stats, config),
+            _ => {}
+        }
+    }
+    writeln!(buf, "</body>").unwrap();
+    writeln!(buf, "</html>").unwrap();
+    buf
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn amount(value: i64, config: &Config) -> String {
+    config
+        .locale
+        .number(config.scale.apply(value as f64 / 100.0), 2)
+}
+
+// Like the TeX tables, a share that rounds to 100% is written as `100`.
+fn percentage(share: f64, config: &Config) -> String {
+    let share = config.locale.number(share, config.precision);
+    if share == config.locale.number(100.0, config.precision) {
+        String::from("100%")
+    } else {
+        format!("{}%", share)
+    }
+}
+
+// `text` (already escaped), as a link to `label` if its section is in the page.
+fn link_to(linked: bool, label: &str, text: &str) -> String {
+    if linked {
+        format!("<a href=\"#{}\">{}</a>", escape_html(label), text)
+    } else {
+        String::from(text)
+    }
+}
+
+// Shortened notes link to their full text, like `note_for_tex`.
+fn note_for_html(note: &str, config: &Config, links: Links) -> String {
+    match truncate_note(note, config) {
+        Some(short) => link_to(
+            links.full_notes,
+            &note_label(note),
+            &format!("{}…", escape_html(short)),
+        ),
+        None => escape_html(note),
+    }
+}
+
+fn write_row(buf: &mut String, cells: &[String]) {
+    write!(buf, "      <tr>").unwrap();
+    for (i, cell) in cells.iter().enumerate() {
+        if i == 0 {
+            write!(buf, "<td>{}</td>", cell).unwrap();
+        } else {
+            write!(buf, "<td class=\"amount\">{}</td>", cell).unwrap();
+        }
+    }
+    writeln!(buf, "</tr>").unwrap();
+}
+
+fn write_header(buf: &mut String, caption: Option<&str>, headers: &[&str]) {
+    writeln!(buf, "    <table>").unwrap();
+    if let Some(caption) = caption {
+        writeln!(buf, "      <caption>{}</caption>", caption).unwrap();
+    }
+    write!(buf, "      <tr>").unwrap();
+    for header in headers {
+        write!(buf, "<th>{}</th>", escape_html(header)).unwrap();
+    }
+    writeln!(buf, "</tr>").unwrap();
+}
+
+fn write_overview(buf: &mut String, stats: &StatsCollection, config: &Config) {
+    writeln!(buf, "  <h2>Overview</h2>").unwrap();
+    if let Some(currency) = &config.currency {
+        writeln!(
+            buf,
+            "  <p>All amounts are in {}.</p>",
+            escape_html(currency)
+        )
+        .unwrap();
+    }
+    if config.amortize {
+        writeln!(
+            buf,
+            "  <p>Per-day averages spread each transaction evenly from its date to its end date, so that long-lasting purchases don't show up as spikes; totals still follow the payment dates.</p>"
+        )
+        .unwrap();
+    }
+    if config.min_sample > 1 {
+        writeln!(
+            buf,
+            "  <p>Categories marked with † have fewer than {} transactions in the period, too few to read much into.</p>",
+            config.min_sample
+        )
+        .unwrap();
+    }
+    for (name, period) in [
+        ("Last 30 days", &stats.last_30_days),
+        ("Last 365 days", &stats.last_365_days),
+    ] {
+        writeln!(buf, "  <h3>{}</h3>", name).unwrap();
+        writeln!(buf, "  <ul>").unwrap();
+        writeln!(
+            buf,
+            "    <li>{}{} spent ({}{} in average per day);</li>",
+            amount(period.total, config),
+            config.scale.suffix(),
+            config
+                .locale
+                .number(config.scale.apply(period.per_day), config.precision),
+            config.scale.suffix()
+        )
+        .unwrap();
+        writeln!(
+            buf,
+            "    <li>{} transactions ({}{} in average per transaction).</li>",
+            period.transaction_count,
+            config.locale.number(
+                config.scale.apply(period.average_transaction),
+                config.precision
+            ),
+            config.scale.suffix()
+        )
+        .unwrap();
+        writeln!(buf, "  </ul>").unwrap();
+    }
+}
+
+fn write_uncategorized(buf: &mut String, stats: &StatsCollection, config: &Config, links: Links) {
+    let all = stats.yearly.iter().flat_map(|(_, x)| x.transactions.iter());
+    let Some(uncategorized) = categorize::uncategorized(all) else {
+        return;
+    };
+
+    writeln!(buf, "  <h3>Uncategorized</h3>").unwrap();
+    writeln!(
+        buf,
+        "  <p><strong>{} transaction(s), {} in total, have no category.</strong> The biggest of them:</p>",
+        uncategorized.count,
+        config.locale.number(uncategorized.total as f64 / 100.0, 2)
+    )
+    .unwrap();
+    write_header(buf, None, &["Date", "Amount", "Note"]);
+    for t in uncategorized.samples.iter() {
+        write_row(
+            buf,
+            &[
+                config.locale.date(t.date),
+                config.locale.number(t.value as f64 / 100.0, 2),
+                note_for_html(&t.note, config, links),
+            ],
+        );
+    }
+    writeln!(buf, "    </table>").unwrap();
+}
+
+// The category, payment method and note tables of `periods`, one table per
+// period. Monthly categories link to their transactions.
+fn write_breakdowns<K>(
+    buf: &mut String,
+    periods: &[(K, Stats)],
+    name: impl Fn(&K) -> String,
+    month: impl Fn(&K) -> Option<(i32, u32)>,
+    config: &Config,
+    links: Links,
+) {
+    let spent = config.scale.label("Spent");
+
+    writeln!(buf, "  <h3>By category</h3>").unwrap();
+    for (k, period) in periods {
+        write_header(
+            buf,
+            Some(&name(k)),
+            &["Category", spent.as_str(), "Percentage"],
+        );
+        let categories = period.ordered_categories(config);
+        let listing = |i: usize| {
+            month(k)
+                .filter(|_| links.transactions)
+                .map(|(y, m)| transactions_label(y, m, Some(i)))
+        };
+        for (i, ((cat, value), share)) in categories
+            .iter()
+            .zip(category_shares(&categories, period, config))
+            .enumerate()
+            .filter(|(_, ((c, _), _))| *c != Category::Refunds)
+        {
+            let label = escape_html(&category_label(period, cat, config, "†"));
+            write_row(
+                buf,
+                &[
+                    match listing(i) {
+                        Some(target) => link_to(true, &target, &label),
+                        None => label,
+                    },
+                    amount(*value, config),
+                    percentage(share, config),
+                ],
+            );
+        }
+        let refunds = period.refunds();
+        if refunds != 0 {
+            let label = Category::Refunds.to_string();
+            let label = match categories
+                .iter()
+                .position(|(c, _)| *c == Category::Refunds)
+                .and_then(listing)
+            {
+                Some(target) => link_to(true, &target, &label),
+                None => label,
+            };
+            write_row(buf, &[label, amount(refunds, config), String::new()]);
+            write_row(
+                buf,
+                &[
+                    String::from("<strong>Net</strong>"),
+                    amount(period.total, config),
+                    String::new(),
+                ],
+            );
+        }
+        writeln!(buf, "    </table>").unwrap();
+    }
+
+    writeln!(buf, "  <h3>By payment method</h3>").unwrap();
+    for (k, period) in periods {
+        write_header(
+            buf,
+            Some(&name(k)),
+            &["Payment method", spent.as_str(), "Percentage"],
+        );
+        for ((pm, value), share) in period.by_payment_method.iter().zip(shares(
+            &period.by_payment_method,
+            period.total,
+            config,
+        )) {
+            write_row(
+                buf,
+                &[
+                    escape_html(pm),
+                    amount(*value, config),
+                    percentage(share, config),
+                ],
+            );
+        }
+        writeln!(buf, "    </table>").unwrap();
+    }
+
+    writeln!(buf, "  <h3>By note</h3>").unwrap();
+    for (k, period) in periods {
+        write_header(buf, Some(&name(k)), &["Note", spent.as_str(), "Percentage"]);
+        for ((note, value), share) in
+            period
+                .by_note
+                .iter()
+                .zip(shares(&period.by_note, period.total, config))
+        {
+            write_row(
+                buf,
+                &[
+                    note_for_html(note, config, links),
+                    amount(*value, config),
+                    percentage(share, config),
+                ],
+            );
+        }
+        writeln!(buf, "    </table>").unwrap();
+    }
+}
+
+fn write_yearly(buf: &mut String, stats: &StatsCollection, config: &Config, links: Links) {
+    writeln!(buf, "  <h2>Yearly spending</h2>").unwrap();
+    write_header(
+        buf,
+        None,
+        &[
+            "Year",
+            config.scale.label("Spent").as_str(),
+            config.scale.label("Daily Average").as_str(),
+        ],
+    );
+    for (year, yearly) in stats.yearly.iter() {
+        write_row(
+            buf,
+            &[
+                year.to_string(),
+                amount(yearly.total, config),
+                config
+                    .locale
+                    .number(config.scale.apply(yearly.per_day), config.precision),
+            ],
+        );
+    }
+    writeln!(buf, "    </table>").unwrap();
+    write_breakdowns(
+        buf,
+        &stats.yearly,
+        |y| y.to_string(),
+        |_| None,
+        config,
+        links,
+    );
+}
+
+fn write_monthly(buf: &mut String, stats: &StatsCollection, config: &Config, links: Links) {
+    let name = |(y, m): &(i32, u32)| format!("{} {}", config.locale.month(*m), y);
+    writeln!(buf, "  <h2>Monthly spending</h2>").unwrap();
+    write_header(
+        buf,
+        None,
+        &[
+            "Month",
+            config.scale.label("Spent").as_str(),
+            config.scale.label("Daily average").as_str(),
+        ],
+    );
+    for ((y, m), monthly) in stats.monthly.iter() {
+        write_row(
+            buf,
+            &[
+                link_to(
+                    links.transactions,
+                    &transactions_label(*y, *m, None),
+                    &name(&(*y, *m)),
+                ),
+                amount(monthly.total, config),
+                config
+                    .locale
+                    .number(config.scale.apply(monthly.per_day), config.precision),
+            ],
+        );
+    }
+    writeln!(buf, "    </table>").unwrap();
+    write_breakdowns(buf, &stats.monthly, name, |k| Some(*k), config, links);
+}
+
+fn write_transactions(buf: &mut String, stats: &StatsCollection, config: &Config, links: Links) {
+    writeln!(buf, "  <h2>Transactions</h2>").unwrap();
+    for ((y, m), monthly) in stats.monthly.iter() {
+        writeln!(
+            buf,
+            "  <h3 id=\"{}\">{} {}</h3>",
+            escape_html(&transactions_label(*y, *m, None)),
+            config.locale.month(*m),
+            y
+        )
+        .unwrap();
+        for (i, (category, value)) in monthly.ordered_categories(config).iter().enumerate() {
+            writeln!(buf, "    <table>").unwrap();
+            writeln!(
+                buf,
+                "      <caption id=\"{}\">{} ({})</caption>",
+                escape_html(&transactions_label(*y, *m, Some(i))),
+                escape_html(&category.to_string()),
+                config.locale.number(*value as f64 / 100.0, 2)
+            )
+            .unwrap();
+            for t in monthly
+                .transactions
+                .iter()
+                .filter(|t| t.category == *category)
+            {
+                write_row(
+                    buf,
+                    &[
+                        config.locale.date(t.date),
+                        config.locale.number(t.value as f64 / 100.0, 2),
+                        escape_html(&t.payment_method),
+                        note_for_html(&t.note, config, links),
+                    ],
+                );
+            }
+            writeln!(buf, "    </table>").unwrap();
+        }
+    }
+}
+
+fn write_full_notes(buf: &mut String, stats: &StatsCollection, config: &Config) {
+    let mut notes = stats
+        .monthly
+        .iter()
+        .flat_map(|(_, x)| x.transactions.iter().map(|t| t.note.as_str()))
+        .filter(|x| truncate_note(x, config).is_some())
+        .collect::<Vec<_>>();
+    if notes.is_empty() {
+        return;
+    }
+    notes.sort();
+    notes.dedup();
+
+    writeln!(buf, "  <h2>Full notes</h2>").unwrap();
+    writeln!(buf, "  <ul>").unwrap();
+    for note in notes {
+        writeln!(
+            buf,
+            "    <li id=\"{}\">{}</li>",
+            escape_html(&note_label(note)),
+            escape_html(note)
+        )
+        .unwrap();
+    }
+    writeln!(buf, "  </ul>").unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+    use crate::Transaction;
+
+    fn stats() -> StatsCollection {
+        let date = NaiveDate::from_ymd_opt(2024, 5, 3).unwrap();
+        StatsCollection::new(&[Transaction::new(
+            1250,
+            date,
+            "Grocery",
+            "Twint",
+            "Bread & <butter> for the whole week, and some more of it",
+        )])
+    }
+
+    #[test]
+    fn escapes_text_and_links() {
+        let html = render_html_stats(
+            &stats(),
+            "a <b>",
+            &Config::default(),
+            &Section::iter().collect::<Vec<_>>(),
+        );
+        assert!(html.contains("<code>a &lt;b&gt;</code>"));
+        assert!(html.contains("<li id=\"note:"));
+        assert!(html.contains("Bread &amp; &lt;butter&gt; for the whole week, and some more of it"));
+        assert!(html.contains("<a href=\"#tx:2024-05\">May 2024</a>"));
+        assert!(html.contains("<a href=\"#note:"));
+    }
+
+    #[test]
+    fn links_only_to_selected_sections() {
+        let html = render_html_stats(
+            &stats(),
+            "a",
+            &Config::default(),
+            &[Section::Yearly, Section::Monthly],
+        );
+        assert!(html.contains("May 2024"));
+        assert!(!html.contains("<a href"));
+        assert!(!html.contains("id=\""));
+    }
+}
@@ -0,0 +1,211 @@
+use std::{
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+};
+
+use chrono::NaiveDate;
+
+use crate::{
+    diagnostics::{self, Diagnostic, Location},
+    files,
+    schema::COLUMNS,
+    source::CsvFormat,
+    Category,
+};
+
+const DATE_FORMATS: [&str; 4] = ["%-d/%-m/%Y", "%d.%m.%Y", "%Y-%m-%d", "%m/%d/%Y"];
+
+struct Prompt<'a> {
+    lines: io::Lines<io::StdinLock<'a>>,
+}
+
+impl Prompt<'_> {
+    // The answer, trimmed, or `default` for an empty one (or end of input).
+    fn ask(&mut self, question: &str, default: &str) -> String {
+        if default.is_empty() {
+            print!("{}: ", question);
+        } else {
+            print!("{} [{}]: ", question, default);
+        }
+        io::stdout().flush().unwrap();
+        let answer = self
+            .lines
+            .next()
+            .and_then(|x| x.ok())
+            .map(|x| String::from(x.trim()))
+            .unwrap_or_default();
+        if answer.is_empty() {
+            String::from(default)
+        } else {
+            answer
+        }
+    }
+
+    fn ask_until(
+        &mut self,
+        question: &str,
+        default: &str,
+        accept: impl Fn(&str) -> bool,
+    ) -> String {
+        loop {
+            let answer = self.ask(question, default);
+            if accept(&answer) {
+                return answer;
+            }
+            println!("  `{}` is not valid here, try again.", answer);
+        }
+    }
+}
+
+fn round_trips(date_format: &str) -> bool {
+    let date = NaiveDate::from_ymd_opt(2024, 12, 31).unwrap();
+    let format = CsvFormat {
+        date_format: String::from(date_format),
+        ..CsvFormat::default()
+    };
+    format.parse_date(&format.format_date(date)) == Some(date)
+}
+
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('"', "\\\""))
+}
+
+fn write(path: &Path, content: &str) -> Result<(), Diagnostic> {
+    let location = Some(Location::file(&path.display().to_string()));
+    if let Some(dir) = path.parent().filter(|x| !x.as_os_str().is_empty()) {
+        if let Err(e) = files::create_dir_all(dir) {
+            return Err(diagnostics::error(
+                "io-write",
+                location,
+                format!("Could not create directory: {}", e),
+            ));
+        }
+    }
+    if let Err(e) = files::write_atomically(path, content.as_bytes()) {
+        return Err(diagnostics::error(
+            "io-write",
+            location,
+            format!("Could not write file: {}", e),
+        ));
+    }
+    if !files::is_dry_run() {
+        println!("Written `{}`.", path.display());
+    }
+    Ok(())
+}
+
+// `battista init`: asks for the basics and writes a config file (and, if
+// wanted, an empty data file with the header line).
+pub fn run(config_path: &Path, data_path: Option<&Path>) -> Result<(), Diagnostic> {
+    let mut prompt = Prompt {
+        lines: io::stdin().lock().lines(),
+    };
+    println!("Setting up {}.", env!("CARGO_PKG_NAME"));
+    if config_path.exists() {
+        let answer = prompt.ask(
+            &format!("`{}` exists, replace it? (y/n)", config_path.display()),
+            "n",
+        );
+        if !answer.eq_ignore_ascii_case("y") {
+            println!("Nothing changed.");
+            return Ok(());
+        }
+    }
+
+    let currency = prompt.ask(
+        "Currency, as shown next to totals (e.g. CHF, empty for none)",
+        "",
+    );
+    let decimal = prompt.ask_until("Decimal separator (`.` or `,`)", ".", |x| {
+        x == "." || x == ","
+    });
+    let delimiter = prompt.ask_until("Field separator (a single character or `tab`)", ";", |x| {
+        (x == "tab" || x.chars().count() == 1) && x != decimal
+    });
+    println!("Date formats:");
+    let example = NaiveDate::from_ymd_opt(2025, 3, 31).unwrap();
+    for (i, format) in DATE_FORMATS.iter().enumerate() {
+        println!("  {}. {}", i + 1, example.format(format));
+    }
+    let date_format = prompt.ask_until(
+        "Date format (a number above or a chrono format)",
+        "1",
+        |x| {
+            x.parse::<usize>()
+                .is_ok_and(|i| (1..=DATE_FORMATS.len()).contains(&i))
+                || round_trips(x)
+        },
+    );
+    let date_format = match date_format.parse::<usize>() {
+        Ok(i) => String::from(DATE_FORMATS[i - 1]),
+        Err(_) => date_format,
+    };
+
+    println!("Aliases map how you write a category to a built-in one, e.g. `Food = Grocery`.");
+    let mut aliases = vec![];
+    loop {
+        let answer = prompt.ask("Alias (empty to finish)", "");
+        if answer.is_empty() {
+            break;
+        }
+        let Some((alias, category)) = answer.split_once('=') else {
+            println!("  Write it as `name = category`.");
+            continue;
+        };
+        let category = Category::from(category.trim());
+        if let Category::Miscellaneous(name) = &category {
+            println!(
+                "  `{}` is not a built-in category, it becomes `{}`.",
+                name, category
+            );
+        }
+        aliases.push((String::from(alias.trim()), category));
+    }
+
+    let mut content = format!("# Written by `{} init`.\n", env!("CARGO_PKG_NAME"));
+    if !currency.is_empty() {
+        content.push_str(&format!("currency = {}\n", quote(&currency)));
+    }
+    content.push_str("\n[csv]\n");
+    content.push_str(&format!(
+        "delimiter = {}\n",
+        if delimiter == "tab" {
+            String::from("tab")
+        } else {
+            quote(&delimiter)
+        }
+    ));
+    content.push_str(&format!("decimal = {}\n", quote(&decimal)));
+    content.push_str(&format!("date_format = {}\n", quote(&date_format)));
+    if !aliases.is_empty() {
+        content.push_str("\n[aliases]\n");
+        for (alias, category) in aliases.iter() {
+            content.push_str(&format!(
+                "{} = {}\n",
+                quote(alias),
+                quote(&category.to_string())
+            ));
+        }
+    }
+    write(config_path, &content)?;
+
+    let default = data_path
+        .map(|x| x.display().to_string())
+        .unwrap_or_default();
+    let data_path = prompt.ask("Create a starter data file at (empty to skip)", &default);
+    if !data_path.is_empty() {
+        let data_path = PathBuf::from(data_path);
+        if data_path.exists() {
+            println!("`{}` exists, leaving it as it is.", data_path.display());
+        } else {
+            let delimiter = if delimiter == "tab" { "\t" } else { &delimiter };
+            let header = COLUMNS[..6]
+                .iter()
+                .map(|(name, _)| *name)
+                .collect::<Vec<_>>()
+                .join(delimiter);
+            write(&data_path, &format!("{}\n", header))?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,180 @@
+// Just enough JSON writing for our outputs, without pulling in serde.
+
+pub fn string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Number(x) => Some(*x),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> &[Value] {
+        match self {
+            Value::Array(values) => values,
+            _ => &[],
+        }
+    }
+}
+
+// And just enough reading to load back what we wrote, and the answers of the
+// GitHub API.
+pub fn parse(s: &str) -> Result<Value, String> {
+    let mut chars = s.chars().peekable();
+    let value = parse_value(&mut chars)?;
+    skip_whitespace(&mut chars);
+    match chars.next() {
+        None => Ok(value),
+        Some(c) => Err(format!("unexpected `{}` after the value", c)),
+    }
+}
+
+type Chars<'a> = std::iter::Peekable<std::str::Chars<'a>>;
+
+fn skip_whitespace(chars: &mut Chars) {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+}
+
+fn parse_value(chars: &mut Chars) -> Result<Value, String> {
+    skip_whitespace(chars);
+    match chars.peek() {
+        Some('{') => parse_object(chars),
+        Some('[') => parse_array(chars),
+        Some(c) if c.is_ascii_alphabetic() => {
+            let mut word = String::new();
+            while let Some(c) = chars.next_if(|c| c.is_ascii_alphabetic()) {
+                word.push(c);
+            }
+            match word.as_str() {
+                "null" => Ok(Value::Null),
+                "true" => Ok(Value::Bool(true)),
+                "false" => Ok(Value::Bool(false)),
+                _ => Err(format!("unexpected `{}`", word)),
+            }
+        }
+        Some('"') => parse_string(chars).map(Value::String),
+        Some(c) if *c == '-' || c.is_ascii_digit() => {
+            let mut number = String::new();
+            while let Some(c) =
+                chars.next_if(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E'))
+            {
+                number.push(c);
+            }
+            number
+                .parse()
+                .map(Value::Number)
+                .map_err(|_| format!("invalid number `{}`", number))
+        }
+        Some(c) => Err(format!("unexpected `{}`", c)),
+        None => Err(String::from("unexpected end of input")),
+    }
+}
+
+fn parse_object(chars: &mut Chars) -> Result<Value, String> {
+    chars.next(); // {
+    let mut fields = vec![];
+    loop {
+        skip_whitespace(chars);
+        if chars.next_if_eq(&'}').is_some() {
+            return Ok(Value::Object(fields));
+        }
+        if !fields.is_empty() {
+            if chars.next_if_eq(&',').is_none() {
+                return Err(String::from("expected `,` or `}`"));
+            }
+            skip_whitespace(chars);
+        }
+        if chars.peek() != Some(&'"') {
+            return Err(String::from("expected a key"));
+        }
+        let key = parse_string(chars)?;
+        skip_whitespace(chars);
+        if chars.next_if_eq(&':').is_none() {
+            return Err(format!("expected `:` after `{}`", key));
+        }
+        fields.push((key, parse_value(chars)?));
+    }
+}
+
+fn parse_array(chars: &mut Chars) -> Result<Value, String> {
+    chars.next(); // [
+    let mut values = vec![];
+    loop {
+        skip_whitespace(chars);
+        if chars.next_if_eq(&']').is_some() {
+            return Ok(Value::Array(values));
+        }
+        if !values.is_empty() && chars.next_if_eq(&',').is_none() {
+            return Err(String::from("expected `,` or `]`"));
+        }
+        values.push(parse_value(chars)?);
+    }
+}
+
+fn parse_string(chars: &mut Chars) -> Result<String, String> {
+    chars.next(); // "
+    let mut out = String::new();
+    loop {
+        match chars.next() {
+            Some('"') => return Ok(out),
+            Some('\\') => match chars.next() {
+                Some('n') => out.push('\n'),
+                Some('r') => out.push('\r'),
+                Some('t') => out.push('\t'),
+                Some('u') => {
+                    let code = chars.by_ref().take(4).collect::<String>();
+                    let c = u32::from_str_radix(&code, 16)
+                        .ok()
+                        .and_then(char::from_u32)
+                        .ok_or(format!("invalid escape `\\u{}`", code))?;
+                    out.push(c);
+                }
+                Some(c) => out.push(c),
+                None => return Err(String::from("unterminated string")),
+            },
+            Some(c) => out.push(c),
+            None => return Err(String::from("unterminated string")),
+        }
+    }
+}
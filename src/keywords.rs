@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+
+use crate::{Category, Transaction};
+
+// Words that say nothing about what the money was for.
+const STOPWORDS: [&str; 24] = [
+    "and", "the", "for", "with", "from", "of", "to", "in", "at", "on", "by", "my", "our", "per",
+    "und", "der", "die", "das", "mit", "für", "von", "del", "della", "con",
+];
+
+// A word of the notes, with what the transactions mentioning it add up to.
+// Themes like "birthday" or "vacation" cut across categories, which is what
+// this is for.
+#[derive(Debug)]
+pub struct Keyword {
+    pub word: String,
+    pub count: u64,
+    pub total: i64,
+    pub categories: Vec<(Category, i64)>, // biggest first
+}
+
+// Lowercase words of at least three letters, each counted once per note.
+fn words(note: &str) -> Vec<String> {
+    let mut words = note
+        .split(|c: char| !c.is_alphanumeric())
+        .map(str::to_lowercase)
+        .filter(|w| {
+            w.chars().count() >= 3
+                && !w.chars().all(|c| c.is_numeric())
+                && !STOPWORDS.contains(&w.as_str())
+        })
+        .collect::<Vec<_>>();
+    words.sort();
+    words.dedup();
+    words
+}
+
+// Most spent on first. Refunds are left out, and so are words of a single
+// transaction, which are better seen in the transactions themselves.
+pub fn keywords(transactions: &[Transaction]) -> Vec<Keyword> {
+    let mut keywords: HashMap<String, Keyword> = HashMap::new();
+    for t in transactions.iter().filter(|t| t.value > 0) {
+        for word in words(&t.note) {
+            let keyword = keywords.entry(word.clone()).or_insert_with(|| Keyword {
+                word,
+                count: 0,
+                total: 0,
+                categories: vec![],
+            });
+            keyword.count += 1;
+            keyword.total += t.value;
+            match keyword
+                .categories
+                .iter_mut()
+                .find(|(c, _)| *c == t.category)
+            {
+                Some((_, v)) => *v += t.value,
+                None => keyword.categories.push((t.category.clone(), t.value)),
+            }
+        }
+    }
+
+    let mut keywords = keywords
+        .into_values()
+        .filter(|k| k.count > 1)
+        .collect::<Vec<_>>();
+    for k in keywords.iter_mut() {
+        k.categories.sort_by_key(|x| std::cmp::Reverse(x.1));
+    }
+    keywords.sort_by(|x, y| {
+        y.total
+            .cmp(&x.total)
+            .then(y.count.cmp(&x.count))
+            .then(x.word.cmp(&y.word))
+    });
+    keywords
+}
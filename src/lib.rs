@@ -0,0 +1,4354 @@
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    env,
+    fmt::{self, Debug},
+    hash::Hash,
+    io::Write,
+    ops::{Deref, Range},
+    path::{Path, PathBuf},
+    process::exit,
+    sync::{
+        atomic::{AtomicUsize, Ordering as AtomicOrdering},
+        Arc, OnceLock,
+    },
+    thread,
+};
+
+mod add;
+mod archive;
+mod batch;
+mod budget;
+mod card;
+mod categorize;
+mod clock;
+mod config;
+mod copy;
+mod daily;
+mod diagnostics;
+mod digest;
+mod durables;
+mod files;
+mod filter;
+mod hash;
+mod household;
+mod html;
+mod init;
+mod json;
+mod keywords;
+mod locale;
+mod merchants;
+mod metrics;
+mod notegroups;
+mod period;
+mod pivot;
+mod profiles;
+mod report;
+mod review;
+mod rounding;
+mod roundup;
+mod sankey;
+mod scale;
+mod schema;
+mod selfupdate;
+mod source;
+mod specialdates;
+mod verify;
+
+pub use diagnostics::{take as take_diagnostics, Diagnostic, Location, Severity};
+pub use locale::Locale;
+pub use report::{ReportBuilder, Section};
+
+use categorize::Reclassification;
+use chrono::{Datelike, NaiveDate, TimeDelta};
+use clock::TimeZone;
+use config::Config;
+use daily::{AllowanceDay, Streaks};
+use digest::Since;
+use durables::DurableCost;
+use keywords::Keyword;
+use merchants::Merchant;
+use plotters::{
+    chart::ChartBuilder,
+    prelude::{BitMapBackend, IntoDrawingArea, IntoLinspace, Rectangle, Text},
+    series::LineSeries,
+    style::{full_palette::AMBER, Color, FontStyle, IntoFont, RED, WHITE},
+};
+use scale::AmountScale;
+use source::{
+    format_amount, CsvFileSource, JsonFileSource, ReadOptions, StdinSource, TransactionSource,
+};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+#[derive(Clone, Debug, Default, EnumIter, PartialEq, Hash, Eq)]
+enum Category {
+    Charity,
+    Grocery,
+    Education,
+    Entrateinment,
+    Healthcare,
+    Hobby,
+    Rent,
+    Restaurants,
+    Savings,
+    Shopping,
+    Taxes,
+    Transportation,
+    Travel,
+    Utilities,
+    Refunds, // negative amounts, when not netted against their category
+    Miscellaneous(String),
+    #[default]
+    Unknown,
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", {
+            match self {
+                Self::Charity => String::from("Charity"),
+                Self::Grocery => String::from("Grocery"),
+                Self::Education => String::from("Education"),
+                Self::Entrateinment => String::from("Entrateinment"),
+                Self::Healthcare => String::from("Healthcare"),
+                Self::Hobby => String::from("Hobby"),
+                Self::Rent => String::from("Rent"),
+                Self::Restaurants => String::from("Restaurants"),
+                Self::Savings => String::from("Savings"),
+                Self::Shopping => String::from("Shopping"),
+                Self::Taxes => String::from("Taxes"),
+                Self::Transportation => String::from("Transportation"),
+                Self::Travel => String::from("Travel"),
+                Self::Utilities => String::from("Utilities"),
+                Self::Refunds => String::from("Refunds"),
+                Self::Miscellaneous(a) => format!("Miscellaneous ({})", a),
+                Self::Unknown => String::from("Unknown"),
+            }
+        })
+    }
+}
+
+impl Category {
+    // The category as it is written in a file, i.e. without the
+    // `Miscellaneous (...)` wrapper for custom ones.
+    fn as_written(&self) -> String {
+        match self {
+            Self::Miscellaneous(s) => s.clone(),
+            c => c.to_string(),
+        }
+    }
+}
+
+impl From<&str> for Category {
+    fn from(s: &str) -> Self {
+        // Accept what `Display` writes, so categories survive a round trip
+        // through the config file.
+        if let Some(inner) = s
+            .strip_prefix("Miscellaneous (")
+            .and_then(|x| x.strip_suffix(')'))
+        {
+            return Self::Miscellaneous(String::from(inner));
+        }
+        for c in Category::iter() {
+            if format!("{}", c) == s {
+                return c;
+            }
+        }
+        Self::Miscellaneous(String::from(s))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+enum Status {
+    #[default]
+    Cleared,
+    Pending, // e.g. a card authorization that has not been booked yet
+}
+
+impl Status {
+    fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "" | "cleared" => Some(Self::Cleared),
+            "pending" => Some(Self::Pending),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Transaction {
+    value: i64, // units and cents
+    date: NaiveDate,
+    category: Category,
+    end_date: NaiveDate,
+    payment_method: String,
+    note: String,
+    id: String,     // explicit `Id` column or hash of the other fields
+    source: String, // label of the file (or account) it was read from
+    status: Status, // optional `Status` column; not part of the id, as it changes over time
+}
+
+impl Transaction {
+    // For programs building reports with `ReportBuilder`: an expense of
+    // `value` cents (negative for refunds) on `date`, valid for that day.
+    // Unknown category names become `Miscellaneous (...)`, as in data files.
+    pub fn new(
+        value: i64,
+        date: NaiveDate,
+        category: &str,
+        payment_method: &str,
+        note: &str,
+    ) -> Self {
+        let mut transaction = Self {
+            value,
+            date,
+            category: Category::from(category.trim()),
+            end_date: date,
+            payment_method: String::from(payment_method.trim()),
+            note: String::from(note.trim()),
+            ..Default::default()
+        };
+        transaction.id = transaction.compute_id(0);
+        transaction
+    }
+
+    // Hash of the fields. `occurrence` tells apart rows that are otherwise identical.
+    fn compute_id(&self, occurrence: u32) -> String {
+        let mut key = format!(
+            "{};{};{};{};{};{}",
+            self.value,
+            self.date.format("%Y-%m-%d"),
+            self.category,
+            self.end_date.format("%Y-%m-%d"),
+            self.payment_method,
+            self.note
+        );
+        if occurrence > 0 {
+            key.push_str(&format!(";#{}", occurrence));
+        }
+
+        format!("{:016x}", hash::fnv1a(key.as_bytes()))
+    }
+}
+
+// A period's transactions, by date: a range of the one sorted list that all
+// the statistics of a report share, rather than a copy for every period.
+#[derive(Debug, Default)]
+struct Transactions {
+    all: Arc<[Transaction]>,
+    range: Range<usize>,
+}
+
+impl Deref for Transactions {
+    type Target = [Transaction];
+
+    fn deref(&self) -> &[Transaction] {
+        &self.all[self.range.clone()]
+    }
+}
+
+#[derive(Debug, Default)]
+struct Stats {
+    per_day: f64,
+    days: i64, // that `per_day` averages over
+    total: i64,
+    by_category: Vec<(Category, i64)>,
+    by_payment_method: Vec<(String, i64)>,
+    by_note: Vec<(String, i64)>,
+    by_source: Vec<(String, i64)>,
+    average_transaction: f64,
+    transaction_count: u64,
+    no_spend_days: u32,
+    transactions: Transactions, // for drilling down from the totals
+}
+
+#[derive(Debug, Default)]
+struct TempStats {
+    per_day: f64,
+    days: i64,
+    total: i64,
+    by_category: HashMap<Category, i64>,
+    by_payment_method: HashMap<String, i64>,
+    by_note: HashMap<String, i64>,
+    by_source: HashMap<String, i64>,
+    average_transaction: f64,
+    transaction_count: u64,
+    no_spend_days: u32,
+    transactions: Range<usize>, // in `TempStatsCollection::transactions`
+}
+
+impl TempStats {
+    // `e` is the `i`-th of the sorted transactions; a period's transactions
+    // follow one another, so they are a range of them.
+    pub fn update(&mut self, i: usize, e: &Transaction) {
+        let value = e.value;
+        self.total += value;
+        if !self.by_category.contains_key(&e.category) {
+            self.by_category.insert(e.category.clone(), 0);
+        }
+        *(self.by_category.get_mut(&e.category).unwrap()) += value;
+
+        if !self.by_payment_method.contains_key(&e.payment_method) {
+            self.by_payment_method.insert(e.payment_method.clone(), 0);
+        }
+        *(self.by_payment_method.get_mut(&e.payment_method).unwrap()) += value;
+
+        if !self.by_note.contains_key(&e.note) {
+            self.by_note.insert(e.note.clone(), 0);
+        }
+        *(self.by_note.get_mut(&e.note).unwrap()) += value;
+
+        *self.by_source.entry(e.source.clone()).or_insert(0) += value;
+
+        self.transaction_count += 1;
+        if self.transactions.is_empty() {
+            self.transactions.start = i;
+        }
+        self.transactions.end = i + 1;
+    }
+
+    // `allocated` is what the period spent once every transaction is spread
+    // over its validity, with `--amortize`; otherwise the per-day average
+    // follows payment dates.
+    pub fn calc_averages(&mut self, days: i64, allocated: Option<i64>, config: &Config) {
+        self.days = days;
+        let spent = allocated.unwrap_or(self.total) as f64 / 100.0;
+        self.per_day = config.round(spent / days as f64);
+        self.average_transaction = config.round(self.get_total() / self.transaction_count as f64);
+    }
+
+    pub fn into_stats(self, all: &Arc<[Transaction]>) -> Stats {
+        let mut by_category = self.by_category.into_iter().collect::<Vec<_>>();
+        by_category.sort_by(|x, y| x.1.partial_cmp(&y.1).unwrap().reverse());
+        let mut by_payment_method = self.by_payment_method.into_iter().collect::<Vec<_>>();
+        by_payment_method.sort_by(|x, y| x.1.partial_cmp(&y.1).unwrap().reverse());
+        let mut by_note = self.by_note.into_iter().collect::<Vec<_>>();
+        by_note.sort_by(|x, y| x.1.partial_cmp(&y.1).unwrap().reverse());
+        let mut by_source = self.by_source.into_iter().collect::<Vec<_>>();
+        by_source.sort_by(|x, y| x.1.partial_cmp(&y.1).unwrap().reverse());
+        Stats {
+            per_day: self.per_day,
+            days: self.days,
+            total: self.total,
+            by_category,
+            by_payment_method,
+            by_note,
+            by_source,
+            average_transaction: self.average_transaction,
+            transaction_count: self.transaction_count,
+            no_spend_days: self.no_spend_days,
+            transactions: Transactions {
+                all: all.clone(),
+                range: self.transactions,
+            },
+        }
+    }
+
+    fn get_total(&self) -> f64 {
+        self.total as f64 / 100.0
+    }
+}
+
+impl Stats {
+    pub fn get_total(&self) -> f64 {
+        self.total as f64 / 100.0
+    }
+
+    // `by_category` as tables list it: biggest first, unless `category_order` says otherwise.
+    pub fn ordered_categories(&self, config: &Config) -> Vec<(Category, i64)> {
+        let mut categories = self.by_category.clone();
+        config.sort_categories(&mut categories);
+        categories
+    }
+
+    // With `refunds = "separate"`, refunds are a credit rather than a share of
+    // the spending: category percentages are of `spent()`, and the `Refunds`
+    // line comes after them with the net total.
+    pub fn refunds(&self) -> i64 {
+        self.by_category
+            .iter()
+            .filter(|(c, _)| *c == Category::Refunds)
+            .map(|(_, v)| v)
+            .sum()
+    }
+
+    pub fn spent(&self) -> i64 {
+        self.total - self.refunds()
+    }
+}
+
+// `by_payment_method` summed up by the groups in the config, largest first.
+fn by_payment_group(stats: &Stats, config: &Config) -> Vec<(String, i64)> {
+    let mut groups: Vec<(String, i64)> = vec![];
+    for (method, value) in stats.by_payment_method.iter() {
+        let group = config.payment_group(method);
+        match groups.iter_mut().find(|(g, _)| g == group) {
+            Some((_, v)) => *v += value,
+            None => groups.push((String::from(group), *value)),
+        }
+    }
+    groups.sort_by_key(|x| -x.1);
+    groups
+}
+
+// Percentages of `total` for each entry, adding up to exactly 100.
+fn shares<T>(values: &[(T, i64)], total: i64, config: &Config) -> Vec<f64> {
+    let values = values.iter().map(|x| x.1).collect::<Vec<_>>();
+    rounding::percentages(&values, total, config.precision)
+}
+
+// The categories' shares of what was spent, `Refunds` left out (0%).
+fn category_shares(categories: &[(Category, i64)], stats: &Stats, config: &Config) -> Vec<f64> {
+    let spending = categories
+        .iter()
+        .map(|(c, v)| if *c == Category::Refunds { 0 } else { *v })
+        .collect::<Vec<_>>();
+    rounding::percentages(&spending, stats.spent(), config.precision)
+}
+
+#[derive(Debug, Default)]
+pub struct StatsCollection {
+    yearly: Vec<(i32, Stats)>,                               // year
+    monthly: Vec<((i32, u32), Stats)>,                       // year, month
+    statement_periods: Vec<((NaiveDate, NaiveDate), Stats)>, // first and last day, with `statement_day`
+    last_365_days: Stats,
+    last_30_days: Stats,
+    no_spend_streaks: Streaks,
+    merchants: Vec<Merchant>,
+    keywords: Vec<Keyword>,
+    durables: Vec<DurableCost>,
+    allowance: Vec<AllowanceDay>, // this month, with `daily_allowance`
+}
+
+#[derive(Debug, Default)]
+struct TempStatsCollection {
+    transactions: Arc<[Transaction]>,                 // by date
+    yearly: HashMap<i32, TempStats>,                  // year
+    monthly: HashMap<(i32, u32), TempStats>,          // year, month
+    statement_periods: HashMap<NaiveDate, TempStats>, // first day
+    last_365_days: TempStats,
+    last_30_days: TempStats,
+    no_spend_streaks: Streaks,
+    merchants: Vec<Merchant>,
+    keywords: Vec<Keyword>,
+    durables: Vec<DurableCost>,
+    allowance: Vec<AllowanceDay>, // this month, with `daily_allowance`
+}
+
+impl TempStatsCollection {
+    pub fn into_stats_collection(self, statement_day: Option<u32>) -> StatsCollection {
+        let mut yearly = self
+            .yearly
+            .into_iter()
+            .map(|(a, b)| (a, b.into_stats(&self.transactions)))
+            .collect::<Vec<_>>();
+        yearly.sort_by_key(|x| x.0);
+        let mut monthly = self
+            .monthly
+            .into_iter()
+            .map(|(a, b)| (a, b.into_stats(&self.transactions)))
+            .collect::<Vec<_>>();
+        monthly.sort_by_key(|x| x.0 .0 * 12 + x.0 .1 as i32);
+        let mut statement_periods = self
+            .statement_periods
+            .into_iter()
+            .map(|(a, b)| (a, b.into_stats(&self.transactions)))
+            .collect::<Vec<_>>();
+        statement_periods.sort_by_key(|x| x.0);
+        let statement_periods = match statement_day {
+            Some(day) => statement_periods
+                .into_iter()
+                .map(|(start, stats)| ((start, period::statement_period_end(start, day)), stats))
+                .collect(),
+            None => vec![],
+        };
+        StatsCollection {
+            yearly,
+            monthly,
+            statement_periods,
+            last_30_days: self.last_30_days.into_stats(&self.transactions),
+            last_365_days: self.last_365_days.into_stats(&self.transactions),
+            no_spend_streaks: self.no_spend_streaks,
+            merchants: self.merchants,
+            keywords: self.keywords,
+            durables: self.durables,
+            allowance: self.allowance,
+        }
+    }
+}
+
+// struct DateRange(NaiveDate, NaiveDate);
+
+// impl Iterator for DateRange {
+//     type Item = NaiveDate;
+
+//     fn next(&mut self) -> Option<Self::Item> {
+//         if self.0 <= self.1 {
+//             let next = self.0 + Duration::days(1);
+//             Some(mem::replace(&mut self.0, next))
+//         } else {
+//             None
+//         }
+//     }
+// }
+
+fn category_share(stats: &Stats, category: &Category, config: &Config) -> f64 {
+    stats
+        .by_category
+        .iter()
+        .zip(category_shares(&stats.by_category, stats, config))
+        .find(|((c, _), _)| c == category)
+        .map(|(_, share)| share)
+        .unwrap_or_default()
+}
+
+// Fewer than `min_sample` transactions of `category` in the period: its
+// figures are still shown, but marked, and kept out of trends and alerts.
+fn is_thin(stats: &Stats, category: &Category, config: &Config) -> bool {
+    stats
+        .transactions
+        .iter()
+        .filter(|t| t.category == *category)
+        .count()
+        < config.min_sample
+}
+
+// The category's name, marked with `mark` if it is thin in `stats`.
+fn category_label(stats: &Stats, category: &Category, config: &Config, mark: &str) -> String {
+    if is_thin(stats, category, config) {
+        format!("{}{}", category, mark)
+    } else {
+        category.to_string()
+    }
+}
+
+// Half a point either way counts as steady, so rounding noise doesn't flip arrows.
+fn share_trend(now: f64, before: Option<f64>) -> Ordering {
+    match before {
+        Some(before) if now - before >= 0.5 => Ordering::Greater,
+        Some(before) if before - now >= 0.5 => Ordering::Less,
+        _ => Ordering::Equal,
+    }
+}
+
+fn moving_average(xs: Vec<f64>, window: isize) -> Vec<f64> {
+    let mut average = Vec::new();
+
+    for i in 0..xs.len() {
+        let mut a = 0.0;
+        let start = (i as isize - window + 1).max(0) as usize;
+        let n = (i - start + 1) as f64;
+        for x in xs.iter().take(i + 1).skip(start) {
+            a += x;
+        }
+        a /= n;
+        average.push(a);
+    }
+    assert!(average.len() == xs.len());
+    average
+}
+
+fn weighted_moving_average(xs: Vec<(f64, f64)>, window: isize) -> Vec<f64> {
+    let mut average = Vec::new();
+
+    for i in 0..xs.len() {
+        let mut a = 0.0;
+        let mut d = 0.0;
+        let start = (i as isize - window + 1).max(0) as usize;
+        for x in xs.iter().take(i + 1).skip(start) {
+            a += x.0 * x.1;
+            d += x.1;
+        }
+        a /= d;
+        average.push(a);
+    }
+    assert!(average.len() == xs.len());
+    average
+}
+
+fn daily_average_label(config: &Config) -> String {
+    let label = if config.amortize {
+        "Daily Average, amortized over validity"
+    } else {
+        "Daily Average"
+    };
+    config.scale.label(label)
+}
+
+fn days_in_month(d: NaiveDate) -> i64 {
+    let year = year_as_i32(d.year_ce());
+    let month = d.month0() + 1;
+    (NaiveDate::from_ymd_opt(year + if month == 12 { 1 } else { 0 }, (month % 12) + 1, 1).unwrap()
+        - NaiveDate::from_ymd_opt(year, month, 1).unwrap())
+    .num_days()
+}
+
+fn days_in_year(d: NaiveDate) -> i64 {
+    let year = year_as_i32(d.year_ce());
+    let month = d.month0() + 1;
+    (NaiveDate::from_ymd_opt(year + 1, month, 1).unwrap()
+        - NaiveDate::from_ymd_opt(year, month, 1).unwrap())
+    .num_days()
+}
+
+fn year_as_i32(year_ce: (bool, u32)) -> i32 {
+    if year_ce.0 {
+        year_ce.1 as i32
+    } else {
+        -(year_ce.1 as i32)
+    }
+}
+
+fn escape_string_for_tex(str: &str) -> String {
+    str.replace('&', "\\&")
+        .replace('$', "\\$")
+        .replace('%', "\\%")
+        .replace('#', "\\#")
+        .replace('_', "\\_")
+}
+
+// The start of a note longer than `note_length` characters, cut at a space
+// when there is one not too far back, so tables keep their layout.
+fn truncate_note<'a>(note: &'a str, config: &Config) -> Option<&'a str> {
+    let max = config.note_length;
+    if max == 0 || note.chars().count() <= max {
+        return None;
+    }
+    let end = note.char_indices().nth(max - 1).map(|(i, _)| i).unwrap();
+    let cut = &note[..end];
+    let cut = match cut.rfind(' ') {
+        Some(space) if cut[..space].chars().count() * 4 >= max * 3 => &cut[..space],
+        _ => cut,
+    };
+    Some(cut.trim_end())
+}
+
+fn note_for_terminal(note: &str, config: &Config) -> String {
+    match truncate_note(note, config) {
+        Some(short) => format!("{}…", short),
+        None => String::from(note),
+    }
+}
+
+fn note_label(note: &str) -> String {
+    format!("note:{:016x}", hash::fnv1a(note.as_bytes()))
+}
+
+// Which of the appendices that other sections link to are in the report, so
+// that no link points at a section left out.
+#[derive(Clone, Copy)]
+struct Links {
+    transactions: bool,
+    full_notes: bool,
+}
+
+impl Links {
+    fn new(sections: &[Section]) -> Self {
+        Self {
+            transactions: sections.contains(&Section::Transactions),
+            full_notes: sections.contains(&Section::FullNotes),
+        }
+    }
+}
+
+// Shortened notes link to their full text in the "Full notes" appendix.
+fn note_for_tex(note: &str, config: &Config, links: Links) -> String {
+    match truncate_note(note, config) {
+        Some(short) if links.full_notes => format!(
+            "\\hyperref[{}]{{{}\\ldots}}",
+            note_label(note),
+            escape_string_for_tex(short)
+        ),
+        Some(short) => format!("{}\\ldots", escape_string_for_tex(short)),
+        None => escape_string_for_tex(note),
+    }
+}
+
+fn print_usage() {
+    let program = env::args().next().unwrap();
+    println!(
+        "USAGE: {} [COMMAND] [OPTIONS] <path/to/file.csv | ->",
+        program
+    );
+    println!(
+        "  init [file.csv]       create the config file (and a starter data file) step by step"
+    );
+    println!("  budget suggest        propose monthly budgets and write them to the config file");
+    println!(
+        "  aliases suggest       find misspelled categories and alias them in the config file"
+    );
+    println!("  add @<shortcut> [amount]");
+    println!("  add <amount> <category> <payment method> [note]");
+    println!("                        append a transaction dated today to the file");
+    println!("  digest [--since last-run|<date>]");
+    println!("                        summarize what was added since the last digest");
+    println!("  schema                show the expected file format and the active settings");
+    println!("  export --daily <path> write per-day totals, overall and by category, to <path>");
+    println!(
+        "  export --pivot <path> write yearly spending by category and payment method to <path>"
+    );
+    println!("  card [--year <year>]  draw a \"year in review\" picture (this year by default)");
+    println!("  review                go through transactions queued for review");
+    println!("  batch <batch.toml>    write every report described in <batch.toml>");
+    println!("  history               show how the archived reports evolved");
+    println!(
+        "  verify <report>       tell whether a report (.tex or stats.json) is older than the data"
+    );
+    println!(
+        "  household             write the household report and one per person (one file each)"
+    );
+    println!("  explore               filter the transactions interactively and show their stats");
+    println!("  copy [--month <yyyy-mm>]");
+    println!("                        copy a month's categories to the clipboard (this month by default)");
+    println!("  self-update [--check] look for a newer release on GitHub and install it");
+    println!("OPTIONS:");
+    println!("  --config <path>       read settings from <path>");
+    println!("  --format text|json    how warnings and errors are printed");
+    println!("  --timezone <tz>       time zone deciding what today is");
+    println!("  --statement-day <day> also group by statement periods starting on <day>");
+    println!("  --period <period>     only transactions of e.g. `last month`, `ytd`, `2024-q2`");
+    println!("  --amortize            spread per-day averages from each date to its end date");
+    println!("  --skip-comments       ignore lines starting with `#`");
+    println!("  --include-pending     also count transactions with status `pending`");
+    println!("  --dry-run             show what would be written instead of writing it");
+    println!("  --read-only           never write any file, not even saved state");
+    println!("  --archive             also keep the report and its stats under `reports/YYYY-MM/`");
+}
+
+fn expect_value(
+    argv: &mut impl Iterator<Item = String>,
+    option: &str,
+) -> Result<String, Diagnostic> {
+    argv.next().ok_or_else(|| {
+        print_usage();
+        diagnostics::error(
+            "cli-missing-value",
+            None,
+            format!("Option `{}` expects a value", option),
+        )
+    })
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+enum Command {
+    #[default]
+    Report,
+    BudgetSuggest,
+    AliasesSuggest,
+    Add(Vec<String>), // `@shortcut [amount]` or `<amount> <category> <payment method> [note]`
+    Digest,
+    Schema,
+    Export,
+    Card,
+    Review,
+    Batch(PathBuf),  // the batch file
+    Verify(PathBuf), // the report, TeX or `stats.json`
+    History,
+    Household,
+    Explore,
+    Copy,
+    Init,
+    SelfUpdate,
+}
+
+#[derive(Default)]
+struct Args {
+    command: Command,
+    inputs: Vec<String>, // file paths, or `-` for stdin
+    read_options: ReadOptions,
+    config_path: Option<PathBuf>,
+    timezone: Option<TimeZone>,
+    statement_day: Option<u32>,
+    include_pending: bool,
+    since: Since,
+    daily_path: Option<PathBuf>, // `export --daily <path>`
+    pivot_path: Option<PathBuf>, // `export --pivot <path>`
+    year: Option<i32>,
+    month: Option<(i32, u32)>, // `copy --month <yyyy-mm>`
+    period: Option<String>,    // parsed once the time zone is known
+    archive: bool,
+    check: bool, // `self-update --check`
+    amortize: bool,
+}
+
+fn parse_args() -> Result<Args, Diagnostic> {
+    let mut args = Args::default();
+    let mut positional = vec![];
+
+    let mut argv = env::args().skip(1);
+    while let Some(arg) = argv.next() {
+        match arg.as_str() {
+            "--skip-comments" => args.read_options.skip_comments = true,
+            "--include-pending" => args.include_pending = true,
+            "--dry-run" => files::set_dry_run(true),
+            "--read-only" => files::set_read_only(true),
+            "--archive" => args.archive = true,
+            "--check" => args.check = true,
+            "--amortize" => args.amortize = true,
+            "--config" => args.config_path = Some(PathBuf::from(expect_value(&mut argv, &arg)?)),
+            "--daily" => args.daily_path = Some(PathBuf::from(expect_value(&mut argv, &arg)?)),
+            "--pivot" => args.pivot_path = Some(PathBuf::from(expect_value(&mut argv, &arg)?)),
+            "--period" => args.period = Some(expect_value(&mut argv, &arg)?),
+            "--statement-day" => {
+                let value = expect_value(&mut argv, &arg)?;
+                args.statement_day =
+                    Some(config::parse_statement_day(&value).ok_or_else(|| {
+                        diagnostics::error(
+                            "cli-invalid-value",
+                            None,
+                            format!("Invalid statement day `{}` (expected 1 to 31)", value),
+                        )
+                    })?);
+            }
+            "--month" => {
+                let value = expect_value(&mut argv, &arg)?;
+                args.month = Some(copy::parse_month(&value).ok_or_else(|| {
+                    diagnostics::error(
+                        "cli-invalid-value",
+                        None,
+                        format!("Invalid month `{}` (expected e.g. 2024-05)", value),
+                    )
+                })?);
+            }
+            "--year" => {
+                let value = expect_value(&mut argv, &arg)?;
+                args.year = Some(value.parse::<i32>().map_err(|_| {
+                    diagnostics::error(
+                        "cli-invalid-value",
+                        None,
+                        format!("Invalid year `{}`", value),
+                    )
+                })?);
+            }
+            "--since" => {
+                let value = expect_value(&mut argv, &arg)?;
+                args.since = Since::parse(&value).ok_or_else(|| {
+                    diagnostics::error(
+                        "cli-invalid-value",
+                        None,
+                        format!(
+                            "Invalid `--since` value `{}` (expected `last-run` or a date)",
+                            value
+                        ),
+                    )
+                })?;
+            }
+            "--timezone" => {
+                let value = expect_value(&mut argv, &arg)?;
+                args.timezone = Some(TimeZone::parse(&value).ok_or_else(|| {
+                    diagnostics::error(
+                        "cli-invalid-value",
+                        None,
+                        format!("Unknown time zone `{}`", value),
+                    )
+                })?);
+            }
+            "--format" => match expect_value(&mut argv, &arg)?.as_str() {
+                "text" => diagnostics::set_json_output(false),
+                "json" => diagnostics::set_json_output(true),
+                other => {
+                    return Err(diagnostics::error(
+                        "cli-invalid-value",
+                        None,
+                        format!("Unknown format `{}` (expected `text` or `json`)", other),
+                    ))
+                }
+            },
+            _ if arg.starts_with("--") => {
+                print_usage();
+                return Err(diagnostics::error(
+                    "cli-unknown-option",
+                    None,
+                    format!("Unknown option `{}`", arg),
+                ));
+            }
+            _ => positional.push(arg),
+        }
+    }
+
+    let mut positional = positional.into_iter().peekable();
+    if positional.peek().is_some_and(|x| x == "budget") {
+        positional.next();
+        match positional.next().as_deref() {
+            Some("suggest") => args.command = Command::BudgetSuggest,
+            other => {
+                print_usage();
+                return Err(diagnostics::error(
+                    "cli-unknown-command",
+                    None,
+                    format!("Unknown budget command `{}`", other.unwrap_or_default()),
+                ));
+            }
+        }
+    }
+
+    if positional.peek().is_some_and(|x| x == "aliases") {
+        positional.next();
+        match positional.next().as_deref() {
+            Some("suggest") => args.command = Command::AliasesSuggest,
+            other => {
+                print_usage();
+                return Err(diagnostics::error(
+                    "cli-unknown-command",
+                    None,
+                    format!("Unknown aliases command `{}`", other.unwrap_or_default()),
+                ));
+            }
+        }
+    }
+
+    if positional.peek().is_some_and(|x| x == "export") {
+        positional.next();
+        args.command = Command::Export;
+        if args.daily_path.is_none() && args.pivot_path.is_none() {
+            print_usage();
+            return Err(diagnostics::error(
+                "cli-missing-option",
+                None,
+                String::from("`export` needs `--daily <path>` or `--pivot <path>`"),
+            ));
+        }
+    }
+
+    if positional.peek().is_some_and(|x| x == "review") {
+        positional.next();
+        args.command = Command::Review;
+    }
+
+    if positional.peek().is_some_and(|x| x == "card") {
+        positional.next();
+        args.command = Command::Card;
+    }
+
+    if positional.peek().is_some_and(|x| x == "schema") {
+        positional.next();
+        args.command = Command::Schema;
+    }
+
+    if positional.peek().is_some_and(|x| x == "self-update") {
+        positional.next();
+        args.command = Command::SelfUpdate;
+    }
+
+    if positional.peek().is_some_and(|x| x == "init") {
+        positional.next();
+        args.command = Command::Init;
+    }
+
+    if positional.peek().is_some_and(|x| x == "copy") {
+        positional.next();
+        args.command = Command::Copy;
+    }
+
+    if positional.peek().is_some_and(|x| x == "household") {
+        positional.next();
+        args.command = Command::Household;
+    }
+
+    if positional.peek().is_some_and(|x| x == "explore") {
+        positional.next();
+        args.command = Command::Explore;
+    }
+
+    if positional.peek().is_some_and(|x| x == "history") {
+        positional.next();
+        args.command = Command::History;
+    }
+
+    if positional.peek().is_some_and(|x| x == "digest") {
+        positional.next();
+        args.command = Command::Digest;
+    }
+
+    if positional.peek().is_some_and(|x| x == "batch") {
+        positional.next();
+        let Some(path) = positional.next() else {
+            print_usage();
+            return Err(diagnostics::error(
+                "cli-missing-value",
+                None,
+                String::from("`batch` needs the path of a batch file"),
+            ));
+        };
+        args.command = Command::Batch(PathBuf::from(path));
+    }
+
+    if positional.peek().is_some_and(|x| x == "verify") {
+        positional.next();
+        let Some(path) = positional.next() else {
+            print_usage();
+            return Err(diagnostics::error(
+                "cli-missing-value",
+                None,
+                String::from("`verify` needs the path of a report"),
+            ));
+        };
+        args.command = Command::Verify(PathBuf::from(path));
+    }
+
+    if positional.peek().is_some_and(|x| x == "add") {
+        positional.next();
+        let (files, entry): (Vec<_>, Vec<_>) =
+            positional.partition(|arg| PathBuf::from(arg).is_file());
+        args.command = Command::Add(entry);
+        positional = files.into_iter().peekable();
+    }
+
+    args.inputs = positional.collect();
+    Ok(args)
+}
+
+// The options' CSV format only comes from the config, which is loaded after
+// the command line is parsed. A path that does not exist is an error rather
+// than silently left out of the report.
+fn open_sources(
+    inputs: &[String],
+    options: &ReadOptions,
+) -> Result<Vec<Box<dyn TransactionSource>>, Diagnostic> {
+    let mut sources: Vec<Box<dyn TransactionSource>> = vec![];
+    let mut stdin_used = false;
+    for arg in inputs {
+        if arg == "-" {
+            if !stdin_used {
+                sources.push(Box::new(StdinSource {
+                    options: options.clone(),
+                    sha256: OnceLock::new(),
+                }));
+                stdin_used = true;
+            }
+        } else {
+            let path = PathBuf::from(arg);
+            if let Err(e) = std::fs::metadata(&path) {
+                return Err(diagnostics::error(
+                    "io-read",
+                    Some(Location::file(arg)),
+                    format!("Could not read file: {}", e),
+                ));
+            }
+            let is_json = path
+                .extension()
+                .is_some_and(|x| x.eq_ignore_ascii_case("json"));
+            if is_json {
+                sources.push(Box::new(JsonFileSource {
+                    path,
+                    options: options.clone(),
+                    sha256: OnceLock::new(),
+                }));
+            } else {
+                sources.push(Box::new(CsvFileSource {
+                    path,
+                    options: options.clone(),
+                    sha256: OnceLock::new(),
+                }));
+            }
+        }
+    }
+    Ok(sources)
+}
+
+// What state kept per set of inputs (digest watermarks, review queues) is
+// keyed on: the canonical paths, sorted, so that `./a.csv b.csv` and
+// `b.csv /home/me/a.csv` share it.
+fn state_key(sources: &[Box<dyn TransactionSource>]) -> String {
+    let mut names = sources
+        .iter()
+        .map(|source| match source.path() {
+            Some(path) => std::fs::canonicalize(path)
+                .unwrap_or_else(|_| path.clone())
+                .display()
+                .to_string(),
+            None => source.name(),
+        })
+        .collect::<Vec<_>>();
+    names.sort();
+    names.dedup();
+    names.join("\n")
+}
+
+// Reads the sources on a few threads, each taking the next unread one. What
+// they report is held back and replayed file by file, and the transactions
+// are merged in the order of the command line, as if read one after another
+// (so the error of the first file that fails comes after its warnings).
+fn read_sources(sources: &[Box<dyn TransactionSource>]) -> Result<Vec<Transaction>, Diagnostic> {
+    let read = |source: &dyn TransactionSource| {
+        let label = source.label();
+        diagnostics::capture(|| {
+            let mut transactions = source.read_transactions()?;
+            for t in transactions.iter_mut() {
+                t.source = label.clone();
+            }
+            Ok(transactions)
+        })
+    };
+
+    let workers = thread::available_parallelism()
+        .map_or(1, |x| x.get())
+        .min(sources.len());
+    let results = if workers <= 1 {
+        sources.iter().map(|x| read(x.as_ref())).collect::<Vec<_>>()
+    } else {
+        let next = AtomicUsize::new(0);
+        let mut results = thread::scope(|scope| {
+            let handles = (0..workers)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut done = vec![];
+                        loop {
+                            let i = next.fetch_add(1, AtomicOrdering::Relaxed);
+                            let Some(source) = sources.get(i) else {
+                                return done;
+                            };
+                            done.push((i, read(source.as_ref())));
+                        }
+                    })
+                })
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .flat_map(|x| x.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+        results.sort_by_key(|(i, _)| *i);
+        results.into_iter().map(|(_, x)| x).collect()
+    };
+
+    let mut transactions = vec![];
+    for (read, diagnostics) in results {
+        diagnostics::replay(diagnostics);
+        transactions.extend(read?);
+    }
+    Ok(transactions)
+}
+
+impl StatsCollection {
+    // The statistics of a report, with the default settings.
+    pub fn new(transactions: &[Transaction]) -> Self {
+        get_stats(transactions, &Config::default())
+    }
+}
+
+fn get_stats(transactions: &[Transaction], config: &Config) -> StatsCollection {
+    let mut sorted = transactions.to_vec();
+    sorted.sort_by_key(|t| t.date);
+    let sorted: Arc<[Transaction]> = Arc::from(sorted);
+    let mut tsc = TempStatsCollection {
+        transactions: sorted.clone(),
+        ..Default::default()
+    };
+    let today = config.today();
+    let spending = daily::spending_days(transactions);
+    let allocation = config.amortize.then(|| daily::allocate(transactions));
+    let allocated =
+        |from: NaiveDate, days: i64| allocation.as_ref().map(|x| daily::allocated(x, from, days));
+
+    let mut start = today;
+    for (i, transaction) in sorted.iter().enumerate() {
+        let year = year_as_i32(transaction.date.year_ce());
+        let month = transaction.date.month0() + 1;
+        start = start.min(transaction.date);
+
+        // Yearly
+        tsc.yearly.entry(year).or_default();
+        tsc.yearly.get_mut(&year).unwrap().update(i, transaction);
+
+        // Monthly
+        let month_idx = (year, month);
+        tsc.monthly.entry(month_idx).or_default();
+        tsc.monthly
+            .get_mut(&month_idx)
+            .unwrap()
+            .update(i, transaction);
+
+        if let Some(day) = config.statement_day {
+            tsc.statement_periods
+                .entry(period::statement_period_start(transaction.date, day))
+                .or_default()
+                .update(i, transaction);
+        }
+
+        if (today - transaction.date).num_days() <= 30 {
+            tsc.last_30_days.update(i, transaction);
+        }
+
+        if (today - transaction.date).num_days() <= 365 {
+            tsc.last_365_days.update(i, transaction);
+        }
+    }
+
+    for (k, v) in tsc.yearly.iter_mut() {
+        let year_start = NaiveDate::from_ymd_opt(*k, 1, 1).unwrap();
+        let period_start = year_start.max(start);
+        let period_end = (NaiveDate::from_ymd_opt(*k + 1, 1, 1).unwrap() - TimeDelta::days(1))
+            .min(today + TimeDelta::days(1));
+        let days = days_in_year(year_start);
+        let days2 = (period_end - period_start).num_days();
+        // println!("{} {} {} {} {}", year_start, period_start, period_end, days, days2);
+        let days = days.min(days2);
+        v.calc_averages(days, allocated(period_start, days), config);
+        let year_end = NaiveDate::from_ymd_opt(*k, 12, 31).unwrap();
+        v.no_spend_days = daily::count_no_spend_days(&spending, period_start, year_end.min(today));
+    }
+
+    for (k, v) in tsc.monthly.iter_mut() {
+        let month_start = NaiveDate::from_ymd_opt(k.0, k.1, 1).unwrap();
+
+        let month_end =
+            NaiveDate::from_ymd_opt(k.0 + if k.1 == 12 { 1 } else { 0 }, (k.1 % 12) + 1, 1)
+                .unwrap()
+                - TimeDelta::days(1);
+        let period_start = month_start.max(start);
+        let period_end = (month_end + TimeDelta::days(1)).min(today + TimeDelta::days(1));
+        let days = days_in_month(month_start);
+        let days2 = (period_end - period_start).num_days();
+        // println!("{} {} {} {} {} {}", month_start, month_end, period_start, period_end, days, days2);
+        let days = days.min(days2);
+        v.calc_averages(days, allocated(period_start, days), config);
+        v.no_spend_days = daily::count_no_spend_days(&spending, period_start, month_end.min(today));
+    }
+
+    if let Some(day) = config.statement_day {
+        for (k, v) in tsc.statement_periods.iter_mut() {
+            let period_start = (*k).max(start);
+            let period_end = period::statement_period_end(*k, day).min(today);
+            let days = (period_end - period_start).num_days() + 1;
+            v.calc_averages(days, allocated(period_start, days), config);
+            v.no_spend_days = daily::count_no_spend_days(&spending, period_start, period_end);
+        }
+    }
+
+    tsc.last_30_days
+        .calc_averages(30, allocated(today - TimeDelta::days(29), 30), config);
+    tsc.last_365_days
+        .calc_averages(365, allocated(today - TimeDelta::days(364), 365), config);
+    tsc.last_30_days.no_spend_days =
+        daily::count_no_spend_days(&spending, (today - TimeDelta::days(29)).max(start), today);
+    tsc.last_365_days.no_spend_days =
+        daily::count_no_spend_days(&spending, (today - TimeDelta::days(364)).max(start), today);
+    tsc.no_spend_streaks = daily::no_spend_streaks(&spending, start, today);
+    tsc.merchants = merchants::merchants(transactions);
+    tsc.keywords = keywords::keywords(transactions);
+    tsc.durables = durables::durable_costs(transactions, config);
+    if let Some(allowance) = config.daily_allowance {
+        tsc.allowance =
+            daily::allowance_balance(transactions, allowance, &config.allowance_exclude, today);
+    }
+
+    tsc.into_stats_collection(config.statement_day)
+}
+
+// The `Refunds` line after the category shares, and what is left once they
+// are taken off.
+fn print_refunds(stats: &Stats, indent: &str, width: usize) {
+    let refunds = stats.refunds();
+    if refunds == 0 {
+        return;
+    }
+    println!(
+        "{}- {:<3$}: {:7.2}",
+        indent,
+        Category::Refunds.to_string(),
+        refunds as f64 / 100.0,
+        width
+    );
+    println!(
+        "{}- {:<3$}: {:7.2}",
+        indent,
+        "Net",
+        stats.total as f64 / 100.0,
+        width
+    );
+}
+
+fn print_metrics(stats: &Stats, indent: &str, config: &Config) {
+    if config.metrics.is_empty() {
+        return;
+    }
+    println!("{}- Metrics:", indent);
+    let max_len = config
+        .metrics
+        .iter()
+        .map(|x| x.0.len())
+        .max()
+        .unwrap_or_default();
+    for (name, expr) in config.metrics.iter() {
+        let value = metrics::eval(expr, stats)
+            .map(|x| format!("{:.*}", config.precision, config.round(x)))
+            .unwrap_or(String::from("n/a"));
+        println!("{}   - {:<3$}: {}", indent, name, value, max_len);
+    }
+}
+
+fn print_stats(stats: &StatsCollection, config: &Config) {
+    let today = config.today();
+
+    match &config.currency {
+        Some(currency) => {
+            println!("SPENDING REPORT ({})", currency);
+            println!(
+                "================{}",
+                "=".repeat(currency.chars().count() + 2)
+            );
+        }
+        None => {
+            println!("SPENDING REPORT");
+            println!("===============");
+        }
+    }
+
+    if config.amortize {
+        println!("  Per-day averages spread each transaction from its date to its end date.");
+        println!();
+    }
+    let all = stats.yearly.iter().flat_map(|(_, x)| x.transactions.iter());
+    if let Some(uncategorized) = categorize::uncategorized(all) {
+        println!(
+            "  !! {} transaction(s) without a category, {} in total, e.g.:",
+            uncategorized.count,
+            format_amount(uncategorized.total)
+        );
+        for t in uncategorized.samples.iter() {
+            println!(
+                "       {}  {:>10}  {}",
+                t.date.format("%d/%m/%Y"),
+                format_amount(t.value),
+                t.note
+            );
+        }
+        println!("     Give them a category, or add `[rules]` to the config.");
+        println!();
+    }
+
+    let mut this_year = None;
+    for (year, yearly) in stats.yearly.iter() {
+        if *year == year_as_i32(today.year_ce()) {
+            this_year = Some(yearly)
+        };
+        println!(
+            "  - {}: {:.2} ({:.*} per day)",
+            year,
+            yearly.get_total(),
+            config.precision,
+            yearly.per_day
+        );
+    }
+
+    if let Some(this_year) = this_year {
+        println!("    - Categories:");
+        let categories = this_year.ordered_categories(config);
+        let max_len = categories
+            .iter()
+            .map(|x| category_label(this_year, &x.0, config, "†").chars().count())
+            .max()
+            .unwrap_or_default();
+        for ((c, v), percentage) in categories
+            .iter()
+            .zip(category_shares(&categories, this_year, config))
+            .filter(|((c, _), _)| *c != Category::Refunds)
+        {
+            let percentage = format!("{:.*}", config.precision, percentage);
+            println!(
+                "       - {:<3$}: {:7.2} ({:>5}%)",
+                category_label(this_year, c, config, "†"),
+                *v as f64 / 100.0,
+                percentage,
+                max_len
+            );
+        }
+        print_refunds(this_year, "       ", max_len);
+
+        println!("    - Payment methods:");
+        let max_len = this_year
+            .by_payment_method
+            .iter()
+            .map(|x| x.0.len())
+            .max()
+            .unwrap_or_default();
+        for ((pm, v), percentage) in this_year.by_payment_method.iter().zip(shares(
+            &this_year.by_payment_method,
+            this_year.total,
+            config,
+        )) {
+            let percentage = format!("{:.*}", config.precision, percentage);
+            println!(
+                "       - {:<3$}: {:7.2} ({:>5}%)",
+                pm,
+                *v as f64 / 100.0,
+                percentage,
+                max_len
+            );
+        }
+
+        if !config.payment_groups.is_empty() {
+            println!("    - Payment method groups:");
+            let groups = by_payment_group(this_year, config);
+            let max_len = groups.iter().map(|x| x.0.len()).max().unwrap_or_default();
+            for ((group, v), percentage) in
+                groups.iter().zip(shares(&groups, this_year.total, config))
+            {
+                let percentage = format!("{:.*}", config.precision, percentage);
+                println!(
+                    "       - {:<3$}: {:7.2} ({:>5}%)",
+                    group,
+                    *v as f64 / 100.0,
+                    percentage,
+                    max_len
+                );
+            }
+        }
+
+        if this_year.by_source.len() > 1 {
+            println!("    - Sources:");
+            let max_len = this_year
+                .by_source
+                .iter()
+                .map(|x| x.0.len())
+                .max()
+                .unwrap_or_default();
+            for ((source, v), percentage) in this_year.by_source.iter().zip(shares(
+                &this_year.by_source,
+                this_year.total,
+                config,
+            )) {
+                let percentage = format!("{:.*}", config.precision, percentage);
+                println!(
+                    "       - {:<3$}: {:7.2} ({:>5}%)",
+                    source,
+                    *v as f64 / 100.0,
+                    percentage,
+                    max_len
+                );
+            }
+        }
+        print_metrics(this_year, "    ", config);
+        let round_ups = roundup::STEPS
+            .iter()
+            .zip(roundup::saved(this_year))
+            .map(|(step, saved)| format!("{:.2} (to {})", saved as f64 / 100.0, step))
+            .collect::<Vec<_>>();
+        println!("    - Round-ups would have saved: {}", round_ups.join(", "));
+    }
+
+    let mut this_month = None;
+    println!("    - Months:");
+    for ((y, m), monthly) in stats.monthly.iter() {
+        if *y != year_as_i32(today.year_ce()) {
+            continue;
+        }
+        if *m == today.month0() + 1 {
+            this_month = Some(monthly)
+        }
+        let month_name = NaiveDate::from_ymd_opt(*y, *m, 1).unwrap().format("%B");
+        if *m == today.month() {
+            // A per-day average of a month half gone says little, so show where it is heading.
+            let projected = monthly.get_total() / today.day() as f64 * days_in_month(today) as f64;
+            println!(
+                "      - {:9}: {:7.2} spent so far, {:.2} projected by month end (day {} of {}, {} no-spend days)",
+                month_name,
+                monthly.get_total(),
+                projected,
+                today.day(),
+                days_in_month(today),
+                monthly.no_spend_days
+            );
+            continue;
+        }
+        println!(
+            "      - {:9}: {:7.2} ({:5.*} per day, {} no-spend days)",
+            month_name,
+            monthly.get_total(),
+            config.precision,
+            monthly.per_day,
+            monthly.no_spend_days
+        );
+    }
+
+    let velocities = this_month
+        .map(|x| budget::velocity(x, today, config))
+        .unwrap_or_default();
+    if let Some(this_month) = this_month {
+        println!("        - Categories:");
+        let categories = this_month.ordered_categories(config);
+        let max_len = categories
+            .iter()
+            .map(|x| {
+                category_label(this_month, &x.0, config, "†")
+                    .chars()
+                    .count()
+            })
+            .max()
+            .unwrap_or_default();
+        for ((c, v), percentage) in categories
+            .iter()
+            .zip(category_shares(&categories, this_month, config))
+            .filter(|((c, _), _)| *c != Category::Refunds)
+        {
+            let percentage = format!("{:.*}", config.precision, percentage);
+            let budget = config
+                .budget_for(c)
+                .map(|b| format!(" of {:.2} budget", b as f64 / 100.0))
+                .unwrap_or_default();
+            println!(
+                "           - {:<3$}: {:7.2} ({:>5}%){4}",
+                category_label(this_month, c, config, "†"),
+                *v as f64 / 100.0,
+                percentage,
+                max_len,
+                budget
+            );
+        }
+        print_refunds(this_month, "           ", max_len);
+        print_metrics(this_month, "        ", config);
+
+        if !config.share_targets.is_empty() {
+            let previous = stats
+                .monthly
+                .iter()
+                .rev()
+                .find(|(k, _)| *k < (year_as_i32(today.year_ce()), today.month()))
+                .map(|(_, x)| x);
+            let max_len = config
+                .share_targets
+                .iter()
+                .map(|(c, _)| c.to_string().len())
+                .max()
+                .unwrap_or_default();
+            println!("        - Target shares:");
+            for (c, target) in config.share_targets.iter() {
+                let share = category_share(this_month, c, config);
+                let before = previous.map(|x| category_share(x, c, config));
+                let arrow = match share_trend(share, before) {
+                    _ if is_thin(this_month, c, config) => "†",
+                    Ordering::Greater => "↑",
+                    Ordering::Less => "↓",
+                    Ordering::Equal => "→",
+                };
+                println!(
+                    "           - {:<6$}: {:>6.*}% of at most {}% {}{}",
+                    c.to_string(),
+                    config.precision,
+                    share,
+                    target,
+                    arrow,
+                    if share > *target { " (over)" } else { "" },
+                    max_len
+                );
+            }
+        }
+
+        if !velocities.is_empty() {
+            let label = |v: &budget::Velocity| match &v.category {
+                Some(c) => category_label(this_month, c, config, "†"),
+                None => String::from("All budgets"),
+            };
+            let max_len = velocities
+                .iter()
+                .filter(|x| x.category.is_some())
+                .map(|x| label(x).chars().count())
+                .max()
+                .unwrap_or_default()
+                .max("All budgets".len());
+            println!(
+                "        - Budget pace ({:.0}% of the month gone):",
+                velocities[0].elapsed * 100.0
+            );
+            for v in velocities.iter() {
+                println!(
+                    "           - {:<4$}: {:>3.0}% used, {:.2}x pace{}",
+                    label(v),
+                    v.used * 100.0,
+                    v.pace(),
+                    if v.too_fast() { " (too fast)" } else { "" },
+                    max_len
+                );
+            }
+        }
+    }
+    println!();
+    println!(
+        "Spent last 365 days: {:.2} ({:.*} per day)",
+        stats.last_365_days.get_total(),
+        config.precision,
+        stats.last_365_days.per_day
+    );
+    println!(
+        "Spent last 30 days: {:.2} ({:.*} per day)",
+        stats.last_30_days.get_total(),
+        config.precision,
+        stats.last_30_days.per_day
+    );
+    if let Some(((first, last), period)) = stats
+        .statement_periods
+        .iter()
+        .find(|((first, last), _)| *first <= today && today <= *last)
+    {
+        println!(
+            "Spent this statement period ({} - {}): {:.2} ({:.*} per day)",
+            first.format("%d/%m"),
+            last.format("%d/%m"),
+            period.get_total(),
+            config.precision,
+            period.per_day
+        );
+    }
+    if let (Some(allowance), Some(last)) = (config.daily_allowance, stats.allowance.last()) {
+        let over = stats
+            .allowance
+            .iter()
+            .filter(|x| x.is_over(allowance))
+            .count();
+        println!(
+            "Daily allowance of {:.2}: {:+.2} left this month (over on {} of {} days)",
+            allowance as f64 / 100.0,
+            last.balance as f64 / 100.0,
+            over,
+            stats.allowance.len()
+        );
+    }
+    let too_fast = velocities
+        .iter()
+        .filter(|x| x.too_fast())
+        .map(|x| {
+            format!(
+                "{} ({:.2}x)",
+                x.category
+                    .as_ref()
+                    .map_or(String::from("all budgets"), |c| c.to_string()),
+                x.pace()
+            )
+        })
+        .collect::<Vec<_>>();
+    if !too_fast.is_empty() {
+        println!(
+            "Burning budget faster than {}x pace: {}",
+            budget::PACE_ALERT,
+            too_fast.join(", ")
+        );
+    }
+    if !config.note_groups.is_empty() {
+        println!();
+        println!("Note groups (last 12 complete months):");
+        let max_len = config
+            .note_groups
+            .iter()
+            .map(|x| x.0.len())
+            .max()
+            .unwrap_or_default();
+        for (name, patterns) in config.note_groups.iter() {
+            let kpi = notegroups::kpi(&notegroups::monthly(patterns, stats), today);
+            let trend = kpi
+                .trend()
+                .map(|x| format!(", {:+.0}% over the last 3 months", x))
+                .unwrap_or_default();
+            println!(
+                "  - {:<5$}: {:.2} per month, {} per occurrence ({:.1} per month){}",
+                name,
+                kpi.per_month,
+                kpi.per_occurrence
+                    .map(|x| format!("{:.2}", x))
+                    .unwrap_or(String::from("n/a")),
+                kpi.occurrences_per_month,
+                trend,
+                max_len
+            );
+        }
+    }
+    let periods = specialdates::compare(stats, config);
+    if let (Some(normal), true) = (periods.iter().find(|x| x.name.is_none()), periods.len() > 1) {
+        println!();
+        println!(
+            "Special dates (per day, against {:.*} on normal days):",
+            config.precision,
+            config.round(normal.per_day())
+        );
+        let named = periods.iter().filter_map(|x| Some((x.name.as_ref()?, x)));
+        let max_len = named.clone().map(|x| x.0.len()).max().unwrap_or_default();
+        for (name, period) in named {
+            let difference = if normal.total > 0 {
+                format!(
+                    " ({:+.0}%)",
+                    (period.per_day() / normal.per_day() - 1.0) * 100.0
+                )
+            } else {
+                String::new()
+            };
+            println!(
+                "  - {:<5$}: {:.*} over {} day(s){}",
+                name,
+                config.precision,
+                config.round(period.per_day()),
+                period.days,
+                difference,
+                max_len,
+            );
+        }
+    }
+    println!();
+    let streaks = &stats.no_spend_streaks;
+    println!(
+        "No-spend days in the last 30 days: {}",
+        stats.last_30_days.no_spend_days
+    );
+    println!("Current no-spend streak: {} days", streaks.current);
+    if let Some(end) = streaks.longest_end {
+        println!(
+            "Longest no-spend streak: {} days (until {})",
+            streaks.longest,
+            end.format("%d/%m/%Y")
+        );
+    }
+    let thin = [this_year, this_month]
+        .iter()
+        .flatten()
+        .any(|x| x.by_category.iter().any(|(c, _)| is_thin(x, c, config)));
+    if thin {
+        println!(
+            "† fewer than {} transactions in the period, too few to read much into.",
+            config.min_sample
+        );
+    }
+    println!();
+    println!("===============");
+}
+
+// The footer lines of every page, with the `footer` fields of the config:
+// enough to find the data and settings a printed page was made from. The
+// short fields share the first line, data files and filters get one each.
+fn tex_footer(
+    stats: &StatsCollection,
+    fingerprints: &[(String, String)],
+    filters: &[String],
+    config: &Config,
+) -> Vec<String> {
+    let mut short = vec![];
+    let mut lines = vec![];
+    for field in config.footer.iter() {
+        let text = match field.as_str() {
+            "version" => format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+            "generated" => {
+                let now = config.timezone.now();
+                format!(
+                    "generated {} {}",
+                    config.locale.date(now.date_naive()),
+                    now.format("%H:%M")
+                )
+            }
+            "as_of" => {
+                let last = stats
+                    .yearly
+                    .iter()
+                    .flat_map(|(_, x)| x.transactions.iter())
+                    .map(|t| t.date)
+                    .max();
+                match last {
+                    Some(last) => format!(
+                        "as of {} (last transaction {})",
+                        config.locale.date(config.today()),
+                        config.locale.date(last)
+                    ),
+                    None => format!("as of {}", config.locale.date(config.today())),
+                }
+            }
+            // The start of the hash is enough to tell files apart, the title
+            // page has it in full.
+            "data" => fingerprints
+                .iter()
+                .map(|(name, sha256)| {
+                    format!(
+                        "\\texttt{{{}}} ({})",
+                        escape_string_for_tex(name),
+                        &sha256[..12]
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(", "),
+            "filters" if filters.is_empty() => String::from("no filters"),
+            "filters" => escape_string_for_tex(&filters.join("; ")),
+            _ => unreachable!(), // checked when reading the config
+        };
+        if text.is_empty() {
+            continue; // e.g. no files when reading stdin
+        }
+        match field.as_str() {
+            "data" | "filters" => lines.push(text),
+            _ => short.push(text),
+        }
+    }
+    if !short.is_empty() {
+        lines.insert(0, short.join(" \\textperiodcentered{} "));
+    }
+    lines
+}
+
+fn write_tex_stats(
+    file_path: &Path,
+    stats: &StatsCollection,
+    source_name: &str,
+    fingerprints: &[(String, String)], // data file name and SHA-256
+    filters: &[String],                // what was left out, e.g. `--period`
+    config: &Config,
+    reclassified: &[Reclassification],
+) -> Result<(), Diagnostic> {
+    let buf = render_tex_stats(
+        stats,
+        source_name,
+        fingerprints,
+        filters,
+        config,
+        reclassified,
+        &Section::iter().collect::<Vec<_>>(),
+    );
+    files::write_atomically(file_path, buf.as_slice()).map_err(|e| {
+        diagnostics::error(
+            "io-write",
+            Some(Location::file(&file_path.display().to_string())),
+            format!("Could not write file: {}", e),
+        )
+    })
+}
+
+fn render_tex_stats(
+    stats: &StatsCollection,
+    source_name: &str,
+    fingerprints: &[(String, String)],
+    filters: &[String],
+    config: &Config,
+    reclassified: &[Reclassification],
+    sections: &[Section],
+) -> Vec<u8> {
+    let today_date_formatted = config.locale.long_date(config.today());
+
+    let mut buf = Vec::new();
+    write!(buf, "{}", verify::tex_header(fingerprints)).unwrap();
+    writeln!(buf, "\\documentclass[10pt, a4paper]{{article}}").unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "\\usepackage[english]{{babel}}").unwrap();
+    writeln!(buf, "\\usepackage{{csquotes}}").unwrap();
+    writeln!(buf, "\\usepackage{{fancyhdr}}").unwrap();
+    writeln!(buf, "\\usepackage[portrait]{{geometry}}").unwrap();
+    writeln!(buf, "\\usepackage{{hyperref}}").unwrap();
+    writeln!(buf, "\\usepackage{{longtable}}").unwrap();
+    writeln!(buf, "\\usepackage{{microtype}}").unwrap();
+    writeln!(buf, "\\usepackage{{pgfplots}}").unwrap();
+    writeln!(buf).unwrap();
+
+    writeln!(buf, "\\hypersetup{{").unwrap();
+    writeln!(buf, "    colorlinks=true,").unwrap();
+    writeln!(buf, "    linkcolor=black,").unwrap();
+    writeln!(buf, "    urlcolor=black,").unwrap();
+    writeln!(buf, "    bookmarks=true,").unwrap();
+    writeln!(
+        buf,
+        "    pdftitle={{Spending report from {} ({})}},",
+        source_name, today_date_formatted
+    )
+    .unwrap();
+    writeln!(buf, "}}").unwrap();
+    writeln!(buf).unwrap();
+    writeln!(
+        buf,
+        "\\title{{\\textbf{{Spending report from}} \\texttt{{{}}}}}",
+        source_name
+    )
+    .unwrap();
+    writeln!(
+        buf,
+        "\\author{{\\href{{https://www.github.com/MichaelObvious/battista}}{{{}}} {}}}",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION")
+    )
+    .unwrap();
+    writeln!(buf, "\\date{{{}}}", today_date_formatted).unwrap();
+    writeln!(buf).unwrap();
+    let footer = tex_footer(stats, fingerprints, filters, config);
+    if !footer.is_empty() {
+        // Redefining `plain` puts the footer on the title page too.
+        writeln!(buf, "\\fancypagestyle{{plain}}{{").unwrap();
+        writeln!(buf, "  \\fancyhf{{}}").unwrap();
+        writeln!(buf, "  \\renewcommand{{\\headrulewidth}}{{0pt}}").unwrap();
+        writeln!(buf, "  \\fancyfoot[L]{{\\tiny {}}}", footer.join("\\\\ ")).unwrap();
+        writeln!(buf, "  \\fancyfoot[R]{{\\thepage}}").unwrap();
+        writeln!(buf, "}}").unwrap();
+        writeln!(buf, "\\pagestyle{{plain}}").unwrap();
+        writeln!(buf).unwrap();
+    }
+    writeln!(buf, "\\makeindex").unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "\\begin{{document}}").unwrap();
+    writeln!(buf, "  \\maketitle").unwrap();
+    writeln!(buf).unwrap();
+    // So that a printed report says exactly which data it was made from.
+    writeln!(buf, "  \\begin{{center}}").unwrap();
+    writeln!(buf, "    \\footnotesize").unwrap();
+    for (name, sha256) in fingerprints {
+        writeln!(
+            buf,
+            "    \\texttt{{{}}}: SHA-256 \\texttt{{{}}}\\\\",
+            escape_string_for_tex(name),
+            sha256
+        )
+        .unwrap();
+    }
+    writeln!(buf, "  \\end{{center}}").unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "\\tableofcontents").unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "  \\vspace{{5ex}}").unwrap();
+    writeln!(buf).unwrap();
+    let links = Links::new(sections);
+    let mut appendix = false;
+    for section in Section::iter().filter(|x| sections.contains(x)) {
+        if section >= Section::Transactions && !appendix {
+            writeln!(buf, "  \\appendix").unwrap();
+            writeln!(buf).unwrap();
+            appendix = true;
+        }
+        match section {
+            Section::Overview => write_tex_overview(&mut buf, stats, config),
+            Section::Uncategorized => write_tex_uncategorized(&mut buf, stats, config, links),
+            Section::Profile => write_tex_profile(&mut buf, stats, config),
+            Section::CategoryTrends => write_tex_category_trends(&mut buf, stats, config),
+            Section::Yearly => write_tex_yearly(&mut buf, stats, config, links),
+            Section::Monthly => write_tex_monthly(&mut buf, stats, config, links),
+            Section::StatementPeriods => write_tex_statement_periods(&mut buf, stats, config),
+            Section::Allowance => write_tex_allowance(&mut buf, stats, config),
+            Section::Sources => write_tex_sources(&mut buf, stats, config),
+            Section::Metrics => write_tex_metrics(&mut buf, stats, config),
+            Section::ShareTargets => write_tex_share_targets(&mut buf, stats, config),
+            Section::NoteGroups => write_tex_note_groups(&mut buf, stats, config),
+            Section::SpecialDates => write_tex_special_dates(&mut buf, stats, config),
+            Section::Merchants => write_tex_merchants(&mut buf, stats, config, links),
+            Section::Keywords => write_tex_keywords(&mut buf, stats, config),
+            Section::RoundUps => write_tex_round_ups(&mut buf, stats, config),
+            Section::Durables => write_tex_durables(&mut buf, stats, config, links),
+            Section::Transactions => write_tex_transactions(&mut buf, stats, config, links),
+            Section::Reclassified => write_tex_reclassified(&mut buf, reclassified, config, links),
+            Section::FullNotes => write_tex_full_notes(&mut buf, stats, config),
+        }
+    }
+    writeln!(buf, "\\end{{document}}").unwrap();
+    buf
+}
+
+fn write_tex_overview(buf: &mut Vec<u8>, stats: &StatsCollection, config: &Config) {
+    writeln!(buf, "  \\section{{Overview}}").unwrap();
+    writeln!(buf).unwrap();
+    if let Some(currency) = &config.currency {
+        writeln!(
+            buf,
+            "  All amounts are in {}.",
+            escape_string_for_tex(currency)
+        )
+        .unwrap();
+        writeln!(buf).unwrap();
+    }
+    if config.amortize {
+        writeln!(
+            buf,
+            "  Per-day averages spread each transaction evenly from its date to its end date, so that long-lasting purchases don't show up as spikes; totals still follow the payment dates."
+        )
+        .unwrap();
+        writeln!(buf).unwrap();
+    }
+    if config.min_sample > 1 {
+        writeln!(
+            buf,
+            "  Categories marked with \\dag{{}} have fewer than {} transactions in the period, too few to read much into.",
+            config.min_sample
+        )
+        .unwrap();
+        writeln!(buf).unwrap();
+    }
+    writeln!(buf, "  \\begin{{tikzpicture}}").unwrap();
+    writeln!(buf, "    \\small").unwrap();
+    writeln!(buf, "    \\begin{{axis}}[").unwrap();
+    writeln!(
+        buf,
+        "      symbolic y coords={{{}}},",
+        stats
+            .monthly
+            .iter()
+            .map(|((y, m), _)| format!("{:02}/{}", m, y % 100))
+            .rev()
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+    .unwrap();
+    writeln!(buf, "      xbar,").unwrap();
+    writeln!(buf, "      ytick=data,").unwrap();
+    writeln!(buf, "      width=\\textwidth,").unwrap();
+    writeln!(buf, "      nodes near coords,").unwrap();
+    writeln!(
+        buf,
+        "      every node near coord/.append style={{anchor=west,font=\\tiny}},"
+    )
+    .unwrap();
+    writeln!(buf, "      xlabel={{{}}},", daily_average_label(config)).unwrap();
+    writeln!(buf, "      enlarge x limits={{value=0.2,upper}},").unwrap();
+    writeln!(buf, "      xmin=0").unwrap();
+    writeln!(buf, "    ]").unwrap();
+    writeln!(buf, "\\addplot[xbar, fill=black!20] coordinates {{").unwrap();
+    // let start_year = stats.monthly.first().unwrap().0 .0;
+    // let start_month = stats.monthly.first().unwrap().0.1;
+    for ((y, m), monthly) in stats.monthly.iter() {
+        // let month_name = NaiveDate::from_ymd_opt(*y, *m, 1).unwrap().format("%B");
+        writeln!(
+            buf,
+            "      ({},{:02}/{})",
+            config.scale.apply(monthly.per_day),
+            m,
+            y % 100
+        )
+        .unwrap();
+    }
+    writeln!(buf, "}};").unwrap();
+
+    writeln!(buf, "\\addplot[smooth, black!67,").unwrap();
+    // writeln!(
+    //     buf,
+    //     "      every node near coord/.append style={{anchor=east,font=\\tiny}},"
+    // )
+    // .unwrap();
+    writeln!(buf, "] coordinates {{").unwrap();
+    // Amortized months are weighed by the days they average over, like the
+    // days they spread over; the current month only counts its days so far.
+    let smoothed = if config.amortize {
+        let values = stats
+            .monthly
+            .iter()
+            .map(|(_, x)| (x.per_day, x.days as f64))
+            .collect();
+        weighted_moving_average(values, config.moving_average_window as isize)
+    } else {
+        let values = stats.monthly.iter().map(|x| x.1.per_day).collect();
+        moving_average(values, config.moving_average_window as isize)
+    };
+    for (value, (y, m)) in smoothed.into_iter().zip(stats.monthly.iter().map(|x| x.0)) {
+        // let idx = (y - start_year) * 12 + (m - start_month) as i32;
+        writeln!(
+            buf,
+            "      ({},{:02}/{})",
+            config.scale.apply(value),
+            m,
+            y % 100
+        )
+        .unwrap();
+    }
+    writeln!(buf, "}};").unwrap();
+    let months = stats.monthly.iter().map(|x| x.0).collect::<Vec<_>>();
+    let markers = specialdates::markers(&months, config);
+    if !markers.is_empty() {
+        writeln!(buf, "\\addplot[only marks, mark=triangle*, mark options={{rotate=-90}}, point meta=explicit symbolic, nodes near coords, every node near coord/.append style={{anchor=west,font=\\tiny\\itshape}}] coordinates {{").unwrap();
+        for ((y, m), names) in markers.iter() {
+            writeln!(
+                buf,
+                "      (0,{:02}/{}) [{}]",
+                m,
+                y % 100,
+                escape_string_for_tex(names)
+            )
+            .unwrap();
+        }
+        writeln!(buf, "}};").unwrap();
+    }
+    // writeln!(buf, "    \\centering").unwrap();
+    // writeln!(buf, "    \\includegraphics[width=\\textwidth]{{{}}}", image_path.display()).unwrap();
+    writeln!(buf, "  \\end{{axis}}").unwrap();
+    writeln!(buf, "  \\end{{tikzpicture}}").unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "  \\subsection{{Last 30 days}}").unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "  \\begin{{itemize}}").unwrap();
+    writeln!(
+        buf,
+        "    \\item {}{} spent ({}{} in average per day);",
+        config
+            .locale
+            .number(config.scale.apply(stats.last_30_days.get_total()), 2),
+        config.scale.suffix(),
+        config.locale.number(
+            config.scale.apply(stats.last_30_days.per_day),
+            config.precision
+        ),
+        config.scale.suffix()
+    )
+    .unwrap();
+    writeln!(
+        buf,
+        "    \\item {} transactions ({}{} in average per transaction).",
+        stats.last_30_days.transaction_count,
+        config.locale.number(
+            config.scale.apply(stats.last_30_days.average_transaction),
+            config.precision
+        ),
+        config.scale.suffix()
+    )
+    .unwrap();
+    writeln!(buf, "  \\end{{itemize}}").unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "  \\subsection{{Last 365 days}}").unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "  \\begin{{itemize}}").unwrap();
+    writeln!(
+        buf,
+        "    \\item {}{} spent ({}{} in average per day);",
+        config
+            .locale
+            .number(config.scale.apply(stats.last_365_days.get_total()), 2),
+        config.scale.suffix(),
+        config.locale.number(
+            config.scale.apply(stats.last_365_days.per_day),
+            config.precision
+        ),
+        config.scale.suffix()
+    )
+    .unwrap();
+    writeln!(
+        buf,
+        "    \\item {} transactions ({}{} in average per transaction).",
+        stats.last_365_days.transaction_count,
+        config.locale.number(
+            config.scale.apply(stats.last_365_days.average_transaction),
+            config.precision
+        ),
+        config.scale.suffix()
+    )
+    .unwrap();
+    writeln!(buf, "  \\end{{itemize}}").unwrap();
+    writeln!(buf).unwrap();
+}
+
+// Totals by year, then split by category, payment method and note; the payment
+// groups, pivot and money flow subsections go in between.
+fn write_tex_yearly(buf: &mut Vec<u8>, stats: &StatsCollection, config: &Config, links: Links) {
+    let spent = config.scale.label("Spent");
+    writeln!(buf, "  \\section{{Yearly spending}}").unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "  \\begin{{center}}").unwrap();
+    writeln!(buf, "    \\begin{{longtable}}{{l r r}}").unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    writeln!(
+        buf,
+        "      \\textbf{{Year}} & \\multicolumn{{1}}{{l}}{{\\textbf{{{}}}}} & \\multicolumn{{1}}{{l}}{{\\textbf{{{}}}}}\\\\",
+        spent,
+        config.scale.label("Daily Average")
+    )
+    .unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    for (year, yearly) in stats.yearly.iter() {
+        writeln!(
+            buf,
+            "      {} & {} & {}\\\\",
+            year,
+            config
+                .locale
+                .number(config.scale.apply(yearly.get_total()), 2),
+            config
+                .locale
+                .number(config.scale.apply(yearly.per_day), config.precision)
+        )
+        .unwrap();
+        writeln!(buf, "      \\hline").unwrap();
+    }
+    writeln!(buf, "    \\end{{longtable}}").unwrap();
+    writeln!(buf, "  \\end{{center}}").unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "  \\subsection{{By Category}}").unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "  \\begin{{center}}").unwrap();
+    writeln!(buf, "    \\begin{{longtable}}{{l r r}}").unwrap();
+    for (year, yearly) in stats.yearly.iter() {
+        writeln!(buf, "      \\hline").unwrap();
+        writeln!(
+            buf,
+            "      \\multicolumn{{3}}{{c}}{{\\textbf{{{}}}}}\\\\",
+            year
+        )
+        .unwrap();
+        writeln!(buf, "      \\hline").unwrap();
+        writeln!(buf, "      \\textbf{{Category}} & \\multicolumn{{1}}{{l}}{{\\textbf{{{}}}}} & \\multicolumn{{1}}{{l}}{{\\textbf{{Percentage}}}}\\\\", spent).unwrap();
+        writeln!(buf, "      \\hline").unwrap();
+        let categories = yearly.ordered_categories(config);
+        for ((cat, value), percentage) in categories
+            .iter()
+            .zip(category_shares(&categories, yearly, config))
+            .filter(|((c, _), _)| *c != Category::Refunds)
+        {
+            let cat = category_label(yearly, cat, config, "\\dag");
+            let percentage = config.locale.number(percentage, config.precision);
+            if percentage == config.locale.number(100.0, config.precision) {
+                writeln!(
+                    buf,
+                    "      {} & {} & {}\\% \\\\",
+                    cat,
+                    config
+                        .locale
+                        .number(config.scale.apply(*value as f64 / 100.0), 2),
+                    100
+                )
+                .unwrap();
+            } else {
+                writeln!(
+                    buf,
+                    "      {} & {} & {}\\% \\\\",
+                    cat,
+                    config
+                        .locale
+                        .number(config.scale.apply(*value as f64 / 100.0), 2),
+                    percentage
+                )
+                .unwrap();
+            }
+            writeln!(buf, "      \\hline").unwrap();
+        }
+        write_tex_refunds(buf, yearly, None, config);
+    }
+    writeln!(buf, "    \\end{{longtable}}").unwrap();
+    writeln!(buf, "  \\end{{center}}").unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "  \\subsection{{By Payment method}}").unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "  \\begin{{center}}").unwrap();
+    writeln!(buf, "    \\begin{{longtable}}{{l r r}}").unwrap();
+    for (year, yearly) in stats.yearly.iter() {
+        writeln!(buf, "      \\hline").unwrap();
+        writeln!(
+            buf,
+            "      \\multicolumn{{3}}{{c}}{{\\textbf{{{}}}}}\\\\",
+            year
+        )
+        .unwrap();
+        writeln!(buf, "      \\hline").unwrap();
+        writeln!(buf, "      \\textbf{{Payment method}} & \\multicolumn{{1}}{{l}}{{\\textbf{{{}}}}} & \\multicolumn{{1}}{{l}}{{\\textbf{{Percentage}}}}\\\\", spent).unwrap();
+        writeln!(buf, "      \\hline").unwrap();
+        for ((pm, value), percentage) in yearly.by_payment_method.iter().zip(shares(
+            &yearly.by_payment_method,
+            yearly.total,
+            config,
+        )) {
+            let percentage = config.locale.number(percentage, config.precision);
+            if percentage == config.locale.number(100.0, config.precision) {
+                writeln!(
+                    buf,
+                    "      {} & {} & {}\\% \\\\",
+                    pm,
+                    config
+                        .locale
+                        .number(config.scale.apply(*value as f64 / 100.0), 2),
+                    100
+                )
+                .unwrap();
+            } else {
+                writeln!(
+                    buf,
+                    "      {} & {} & {}\\% \\\\",
+                    pm,
+                    config
+                        .locale
+                        .number(config.scale.apply(*value as f64 / 100.0), 2),
+                    percentage
+                )
+                .unwrap();
+            }
+            writeln!(buf, "      \\hline").unwrap();
+        }
+    }
+    writeln!(buf, "    \\end{{longtable}}").unwrap();
+    writeln!(buf, "  \\end{{center}}").unwrap();
+    writeln!(buf).unwrap();
+    write_tex_payment_groups(buf, stats, config);
+    write_tex_pivot(buf, stats, config);
+    write_tex_money_flow(buf, stats, config, links);
+    writeln!(buf, "  \\subsection{{By Note}}").unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "  \\begin{{center}}").unwrap();
+    writeln!(buf, "    \\begin{{longtable}}{{l r r}}").unwrap();
+    for (year, yearly) in stats.yearly.iter() {
+        writeln!(buf, "      \\hline").unwrap();
+        writeln!(
+            buf,
+            "      \\multicolumn{{3}}{{c}}{{\\textbf{{{}}}}}\\\\",
+            year
+        )
+        .unwrap();
+        writeln!(buf, "      \\hline").unwrap();
+        writeln!(buf, "      \\textbf{{Note}} & \\multicolumn{{1}}{{l}}{{\\textbf{{{}}}}} & \\multicolumn{{1}}{{l}}{{\\textbf{{Percentage}}}}\\\\", spent).unwrap();
+        writeln!(buf, "      \\hline").unwrap();
+        for ((note, value), percentage) in
+            yearly
+                .by_note
+                .iter()
+                .zip(shares(&yearly.by_note, yearly.total, config))
+        {
+            let note = note_for_tex(note, config, links);
+            let percentage = config.locale.number(percentage, config.precision);
+            if percentage == config.locale.number(100.0, config.precision) {
+                writeln!(
+                    buf,
+                    "      \\textquote{{{}}} & {} & {}\\% \\\\",
+                    note,
+                    config
+                        .locale
+                        .number(config.scale.apply(*value as f64 / 100.0), 2),
+                    100
+                )
+                .unwrap();
+            } else {
+                writeln!(
+                    buf,
+                    "      \\textquote{{{}}} & {} & {}\\% \\\\",
+                    note,
+                    config
+                        .locale
+                        .number(config.scale.apply(*value as f64 / 100.0), 2),
+                    percentage
+                )
+                .unwrap();
+            }
+            writeln!(buf, "      \\hline").unwrap();
+        }
+    }
+    writeln!(buf, "    \\end{{longtable}}").unwrap();
+    writeln!(buf, "  \\end{{center}}").unwrap();
+    writeln!(buf).unwrap();
+}
+
+fn write_tex_monthly(buf: &mut Vec<u8>, stats: &StatsCollection, config: &Config, links: Links) {
+    let spent = config.scale.label("Spent");
+    writeln!(buf, "  \\section{{Monthly spending}}").unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "  \\begin{{center}}").unwrap();
+    writeln!(buf, "    \\begin{{longtable}}{{l r r}}").unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    writeln!(
+        buf,
+        "      \\textbf{{Month}} & \\multicolumn{{1}}{{l}}{{\\textbf{{{}}}}} & \\multicolumn{{1}}{{l}}{{\\textbf{{{}}}}}\\\\",
+        spent,
+        config.scale.label("Daily average")
+    )
+    .unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    for ((y, m), monthly) in stats.monthly.iter() {
+        let month_name = config.locale.month(*m);
+        let month = format!("{} {}", month_name, y);
+        writeln!(
+            buf,
+            "      {} & {} & {}\\\\",
+            link_to(
+                links.transactions,
+                &transactions_label(*y, *m, None),
+                &month
+            ),
+            config
+                .locale
+                .number(config.scale.apply(monthly.get_total()), 2),
+            config
+                .locale
+                .number(config.scale.apply(monthly.per_day), config.precision)
+        )
+        .unwrap();
+        writeln!(buf, "      \\hline").unwrap();
+    }
+    writeln!(buf, "    \\end{{longtable}}").unwrap();
+    writeln!(buf, "  \\end{{center}}").unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "  \\subsection{{By Category}}").unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "  \\begin{{center}}").unwrap();
+    writeln!(buf, "    \\begin{{longtable}}{{l r r}}").unwrap();
+    for ((y, m), monthly) in stats.monthly.iter() {
+        let month_name = config.locale.month(*m);
+        writeln!(buf, "      \\hline").unwrap();
+        writeln!(
+            buf,
+            "      \\multicolumn{{3}}{{c}}{{\\textbf{{{} {}}}}}\\\\",
+            month_name, y
+        )
+        .unwrap();
+        writeln!(buf, "      \\hline").unwrap();
+        writeln!(buf, "      \\textbf{{Category}} & \\multicolumn{{1}}{{l}}{{\\textbf{{{}}}}} & \\multicolumn{{1}}{{l}}{{\\textbf{{Percentage}}}}\\\\", spent).unwrap();
+        writeln!(buf, "      \\hline").unwrap();
+        let categories = monthly.ordered_categories(config);
+        for (i, ((cat, value), percentage)) in categories
+            .iter()
+            .zip(category_shares(&categories, monthly, config))
+            .enumerate()
+            .filter(|(_, ((c, _), _))| *c != Category::Refunds)
+        {
+            let cat = link_to(
+                links.transactions,
+                &transactions_label(*y, *m, Some(i)),
+                &category_label(monthly, cat, config, "\\dag"),
+            );
+            let percentage = config.locale.number(percentage, config.precision);
+            if percentage == config.locale.number(100.0, config.precision) {
+                writeln!(
+                    buf,
+                    "      {} & {} & {}\\% \\\\",
+                    cat,
+                    config
+                        .locale
+                        .number(config.scale.apply(*value as f64 / 100.0), 2),
+                    100
+                )
+                .unwrap();
+            } else {
+                writeln!(
+                    buf,
+                    "      {} & {} & {}\\% \\\\",
+                    cat,
+                    config
+                        .locale
+                        .number(config.scale.apply(*value as f64 / 100.0), 2),
+                    percentage
+                )
+                .unwrap();
+            }
+            writeln!(buf, "      \\hline").unwrap();
+        }
+        let listing = categories
+            .iter()
+            .position(|(c, _)| *c == Category::Refunds)
+            .filter(|_| links.transactions)
+            .map(|i| transactions_label(*y, *m, Some(i)));
+        write_tex_refunds(buf, monthly, listing, config);
+    }
+    writeln!(buf, "    \\end{{longtable}}").unwrap();
+    writeln!(buf, "  \\end{{center}}").unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "  \\subsection{{By Payment method}}").unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "  \\begin{{center}}").unwrap();
+    writeln!(buf, "    \\begin{{longtable}}{{l r r}}").unwrap();
+    for ((y, m), monthly) in stats.monthly.iter() {
+        let month_name = config.locale.month(*m);
+        writeln!(buf, "      \\hline").unwrap();
+        writeln!(
+            buf,
+            "      \\multicolumn{{3}}{{c}}{{\\textbf{{{} {}}}}}\\\\",
+            month_name, y
+        )
+        .unwrap();
+        writeln!(buf, "      \\hline").unwrap();
+        writeln!(buf, "      \\textbf{{Payment method}} & \\multicolumn{{1}}{{l}}{{\\textbf{{{}}}}} & \\multicolumn{{1}}{{l}}{{\\textbf{{Percentage}}}}\\\\", spent).unwrap();
+        writeln!(buf, "      \\hline").unwrap();
+        for ((pm, value), percentage) in monthly.by_payment_method.iter().zip(shares(
+            &monthly.by_payment_method,
+            monthly.total,
+            config,
+        )) {
+            let percentage = config.locale.number(percentage, config.precision);
+            if percentage == config.locale.number(100.0, config.precision) {
+                writeln!(
+                    buf,
+                    "      {} & {} & {}\\% \\\\",
+                    pm,
+                    config
+                        .locale
+                        .number(config.scale.apply(*value as f64 / 100.0), 2),
+                    100
+                )
+                .unwrap();
+            } else {
+                writeln!(
+                    buf,
+                    "      {} & {} & {}\\% \\\\",
+                    pm,
+                    config
+                        .locale
+                        .number(config.scale.apply(*value as f64 / 100.0), 2),
+                    percentage
+                )
+                .unwrap();
+            }
+            writeln!(buf, "      \\hline").unwrap();
+        }
+    }
+    writeln!(buf, "    \\end{{longtable}}").unwrap();
+    writeln!(buf, "  \\end{{center}}").unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "  \\subsection{{By Note}}").unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "  \\begin{{center}}").unwrap();
+    writeln!(buf, "    \\begin{{longtable}}{{l r r}}").unwrap();
+    for ((y, m), monthly) in stats.monthly.iter() {
+        let month_name = config.locale.month(*m);
+        writeln!(buf, "      \\hline").unwrap();
+        writeln!(
+            buf,
+            "      \\multicolumn{{3}}{{c}}{{\\textbf{{{} {}}}}}\\\\",
+            month_name, y
+        )
+        .unwrap();
+        writeln!(buf, "      \\hline").unwrap();
+        writeln!(buf, "      \\textbf{{Note}} & \\multicolumn{{1}}{{l}}{{\\textbf{{{}}}}} & \\multicolumn{{1}}{{l}}{{\\textbf{{Percentage}}}}\\\\", spent).unwrap();
+        writeln!(buf, "      \\hline").unwrap();
+        for ((note, value), percentage) in
+            monthly
+                .by_note
+                .iter()
+                .zip(shares(&monthly.by_note, monthly.total, config))
+        {
+            let note = note_for_tex(note, config, links);
+            let percentage = config.locale.number(percentage, config.precision);
+            if percentage == config.locale.number(100.0, config.precision) {
+                writeln!(
+                    buf,
+                    "       \\textquote{{{}}} & {} & {}\\% \\\\",
+                    note,
+                    config
+                        .locale
+                        .number(config.scale.apply(*value as f64 / 100.0), 2),
+                    100
+                )
+                .unwrap();
+            } else {
+                writeln!(
+                    buf,
+                    "       \\textquote{{{}}} & {} & {}\\% \\\\",
+                    note,
+                    config
+                        .locale
+                        .number(config.scale.apply(*value as f64 / 100.0), 2),
+                    percentage
+                )
+                .unwrap();
+            }
+            writeln!(buf, "      \\hline").unwrap();
+        }
+    }
+    writeln!(buf, "    \\end{{longtable}}").unwrap();
+    writeln!(buf, "  \\end{{center}}").unwrap();
+    writeln!(buf).unwrap();
+}
+
+// Which transactions aliases and rules moved to another category, and why.
+fn write_tex_reclassified(
+    buf: &mut Vec<u8>,
+    reclassified: &[Reclassification],
+    config: &Config,
+    links: Links,
+) {
+    if reclassified.is_empty() {
+        return;
+    }
+    writeln!(buf, "  \\section{{Auto-reclassified transactions}}").unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "  \\begin{{center}}").unwrap();
+    writeln!(buf, "    \\begin{{longtable}}{{l l l l l l}}").unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    writeln!(buf, "      \\textbf{{Id}} & \\textbf{{Date}} & \\textbf{{Note}} & \\textbf{{Written}} & \\textbf{{Category}} & \\textbf{{By}}\\\\").unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    for r in reclassified.iter() {
+        writeln!(
+            buf,
+            "      \\texttt{{{}}} & {} & \\textquote{{{}}} & {} & {} & {}\\\\",
+            &r.transaction_id[..r.transaction_id.len().min(8)],
+            config.locale.date(r.date),
+            note_for_tex(&r.note, config, links),
+            escape_string_for_tex(&r.written),
+            escape_string_for_tex(&r.category.to_string()),
+            escape_string_for_tex(&r.reason).replace('`', "")
+        )
+        .unwrap();
+        writeln!(buf, "      \\hline").unwrap();
+    }
+    writeln!(buf, "    \\end{{longtable}}").unwrap();
+    writeln!(buf, "  \\end{{center}}").unwrap();
+    writeln!(buf).unwrap();
+}
+
+fn write_tex_uncategorized(
+    buf: &mut Vec<u8>,
+    stats: &StatsCollection,
+    config: &Config,
+    links: Links,
+) {
+    let all = stats.yearly.iter().flat_map(|(_, x)| x.transactions.iter());
+    let Some(uncategorized) = categorize::uncategorized(all) else {
+        return;
+    };
+
+    writeln!(buf, "  \\subsection{{Uncategorized}}").unwrap();
+    writeln!(buf).unwrap();
+    writeln!(
+        buf,
+        "  \\textbf{{{} transaction(s), {} in total, have no category.}} The biggest of them:",
+        uncategorized.count,
+        config.locale.number(uncategorized.total as f64 / 100.0, 2)
+    )
+    .unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "  \\begin{{center}}").unwrap();
+    writeln!(buf, "    \\begin{{tabular}}{{l r l}}").unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    writeln!(
+        buf,
+        "      \\textbf{{Date}} & \\textbf{{Amount}} & \\textbf{{Note}}\\\\"
+    )
+    .unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    for t in uncategorized.samples.iter() {
+        writeln!(
+            buf,
+            "      {} & {} & \\textquote{{{}}}\\\\",
+            config.locale.date(t.date),
+            config.locale.number(t.value as f64 / 100.0, 2),
+            note_for_tex(&t.note, config, links)
+        )
+        .unwrap();
+        writeln!(buf, "      \\hline").unwrap();
+    }
+    writeln!(buf, "    \\end{{tabular}}").unwrap();
+    writeln!(buf, "  \\end{{center}}").unwrap();
+    writeln!(buf).unwrap();
+}
+
+fn write_tex_full_notes(buf: &mut Vec<u8>, stats: &StatsCollection, config: &Config) {
+    let mut notes = stats
+        .monthly
+        .iter()
+        .flat_map(|(_, x)| x.transactions.iter().map(|t| t.note.as_str()))
+        .chain(stats.durables.iter().map(|x| x.name.as_str()))
+        .filter(|x| truncate_note(x, config).is_some())
+        .collect::<Vec<_>>();
+    if notes.is_empty() {
+        return;
+    }
+    notes.sort();
+    notes.dedup();
+
+    writeln!(buf, "  \\section{{Full notes}}").unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "  \\begin{{itemize}}").unwrap();
+    for note in notes {
+        writeln!(
+            buf,
+            "    \\item \\phantomsection\\label{{{}}}\\textquote{{{}}}",
+            note_label(note),
+            escape_string_for_tex(note)
+        )
+        .unwrap();
+    }
+    writeln!(buf, "  \\end{{itemize}}").unwrap();
+    writeln!(buf).unwrap();
+}
+
+// Target of the links from the monthly tables to the transactions behind
+// them: a month, or the `category`-th category of its `by_category`.
+fn transactions_label(year: i32, month: u32, category: Option<usize>) -> String {
+    match category {
+        Some(i) => format!("tx:{}-{:02}:{}", year, month, i),
+        None => format!("tx:{}-{:02}", year, month),
+    }
+}
+
+// `text`, as a link to `label` if its section is in the report.
+fn link_to(linked: bool, label: &str, text: &str) -> String {
+    if linked {
+        format!("\\hyperref[{}]{{{}}}", label, text)
+    } else {
+        String::from(text)
+    }
+}
+
+// The `Refunds` row under a category table, linked to its transactions if
+// `listing` is given, and the net total after it.
+fn write_tex_refunds(buf: &mut Vec<u8>, stats: &Stats, listing: Option<String>, config: &Config) {
+    let refunds = stats.refunds();
+    if refunds == 0 {
+        return;
+    }
+    let label = match listing {
+        Some(label) => format!("\\hyperref[{}]{{{}}}", label, Category::Refunds),
+        None => Category::Refunds.to_string(),
+    };
+    writeln!(
+        buf,
+        "      {} & {} & \\\\",
+        label,
+        config
+            .locale
+            .number(config.scale.apply(refunds as f64 / 100.0), 2)
+    )
+    .unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    writeln!(
+        buf,
+        "      \\textbf{{Net}} & {} & \\\\",
+        config
+            .locale
+            .number(config.scale.apply(stats.total as f64 / 100.0), 2)
+    )
+    .unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+}
+
+fn write_tex_transactions(
+    buf: &mut Vec<u8>,
+    stats: &StatsCollection,
+    config: &Config,
+    links: Links,
+) {
+    writeln!(buf, "  \\section{{Transactions}}").unwrap();
+    writeln!(buf).unwrap();
+    for ((y, m), monthly) in stats.monthly.iter() {
+        let month_name = config.locale.month(*m);
+        writeln!(buf, "  \\subsection{{{} {}}}", month_name, y).unwrap();
+        writeln!(buf, "  \\label{{{}}}", transactions_label(*y, *m, None)).unwrap();
+        writeln!(buf).unwrap();
+        writeln!(buf, "  \\begin{{center}}").unwrap();
+        writeln!(buf, "    \\begin{{longtable}}{{l r l l}}").unwrap();
+        for (i, (category, value)) in monthly.ordered_categories(config).iter().enumerate() {
+            writeln!(buf, "      \\hline").unwrap();
+            writeln!(buf, "      \\multicolumn{{4}}{{l}}{{\\phantomsection\\label{{{}}}\\textbf{{{}}} ({})}}\\\\", transactions_label(*y, *m, Some(i)), category, config.locale.number(*value as f64 / 100.0, 2))
+            .unwrap();
+            writeln!(buf, "      \\hline").unwrap();
+            for t in monthly
+                .transactions
+                .iter()
+                .filter(|t| t.category == *category)
+            {
+                writeln!(
+                    buf,
+                    "      {} & {} & {} & \\textquote{{{}}}\\\\",
+                    config.locale.date(t.date),
+                    config.locale.number(t.value as f64 / 100.0, 2),
+                    escape_string_for_tex(&t.payment_method),
+                    note_for_tex(&t.note, config, links)
+                )
+                .unwrap();
+            }
+        }
+        writeln!(buf, "      \\hline").unwrap();
+        writeln!(buf, "    \\end{{longtable}}").unwrap();
+        writeln!(buf, "  \\end{{center}}").unwrap();
+        writeln!(buf).unwrap();
+    }
+}
+
+fn write_tex_payment_groups(buf: &mut Vec<u8>, stats: &StatsCollection, config: &Config) {
+    if config.payment_groups.is_empty() {
+        return;
+    }
+
+    writeln!(buf, "  \\subsection{{By Payment method group}}").unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "  \\begin{{center}}").unwrap();
+    writeln!(buf, "    \\begin{{longtable}}{{l r r}}").unwrap();
+    for (year, yearly) in stats.yearly.iter() {
+        writeln!(buf, "      \\hline").unwrap();
+        writeln!(
+            buf,
+            "      \\multicolumn{{3}}{{c}}{{\\textbf{{{}}}}}\\\\",
+            year
+        )
+        .unwrap();
+        writeln!(buf, "      \\hline").unwrap();
+        writeln!(buf, "      \\textbf{{Group}} & \\multicolumn{{1}}{{l}}{{\\textbf{{Spent}}}} & \\multicolumn{{1}}{{l}}{{\\textbf{{Percentage}}}}\\\\").unwrap();
+        writeln!(buf, "      \\hline").unwrap();
+        let groups = by_payment_group(yearly, config);
+        for ((group, value), percentage) in groups.iter().zip(shares(&groups, yearly.total, config))
+        {
+            writeln!(
+                buf,
+                "      {} & {} & {}\\% \\\\",
+                escape_string_for_tex(group),
+                config.locale.number(*value as f64 / 100.0, 2),
+                config.locale.number(percentage, config.precision)
+            )
+            .unwrap();
+            writeln!(buf, "      \\hline").unwrap();
+        }
+    }
+    writeln!(buf, "    \\end{{longtable}}").unwrap();
+    writeln!(buf, "  \\end{{center}}").unwrap();
+    writeln!(buf).unwrap();
+}
+
+fn write_tex_pivot(buf: &mut Vec<u8>, stats: &StatsCollection, config: &Config) {
+    writeln!(buf, "  \\subsection{{By Category and Payment method}}").unwrap();
+    writeln!(buf).unwrap();
+    for (year, yearly) in stats.yearly.iter() {
+        let pivot = pivot::pivot(yearly, config);
+        let columns = pivot.methods.len() + 2;
+        writeln!(buf, "  \\begin{{center}}").unwrap();
+        writeln!(buf, "    \\small").unwrap();
+        writeln!(
+            buf,
+            "    \\begin{{longtable}}{{l {}r}}",
+            "r ".repeat(pivot.methods.len())
+        )
+        .unwrap();
+        writeln!(buf, "      \\hline").unwrap();
+        writeln!(
+            buf,
+            "      \\multicolumn{{{}}}{{c}}{{\\textbf{{{}}}}}\\\\",
+            columns,
+            config.scale.label(&year.to_string())
+        )
+        .unwrap();
+        writeln!(buf, "      \\hline").unwrap();
+        write!(buf, "      \\textbf{{Category}}").unwrap();
+        for method in pivot.methods.iter().map(|x| escape_string_for_tex(x)) {
+            write!(buf, " & \\multicolumn{{1}}{{l}}{{\\textbf{{{}}}}}", method).unwrap();
+        }
+        writeln!(buf, " & \\multicolumn{{1}}{{l}}{{\\textbf{{Total}}}}\\\\").unwrap();
+        writeln!(buf, "      \\hline").unwrap();
+        for (category, values) in pivot.rows.iter() {
+            write!(
+                buf,
+                "      {}",
+                escape_string_for_tex(&category.to_string())
+            )
+            .unwrap();
+            for value in values {
+                // Blanks keep the combinations that never happen out of the way.
+                if *value == 0 {
+                    write!(buf, " &").unwrap();
+                } else {
+                    write!(
+                        buf,
+                        " & {}",
+                        config
+                            .locale
+                            .number(config.scale.apply(*value as f64 / 100.0), 2)
+                    )
+                    .unwrap();
+                }
+            }
+            writeln!(
+                buf,
+                " & {} \\\\",
+                config.locale.number(
+                    config
+                        .scale
+                        .apply(values.iter().sum::<i64>() as f64 / 100.0),
+                    2
+                )
+            )
+            .unwrap();
+            writeln!(buf, "      \\hline").unwrap();
+        }
+        write!(buf, "      \\textbf{{Total}}").unwrap();
+        for i in 0..pivot.methods.len() {
+            write!(
+                buf,
+                " & {}",
+                config
+                    .locale
+                    .number(config.scale.apply(pivot.method_total(i) as f64 / 100.0), 2)
+            )
+            .unwrap();
+        }
+        writeln!(
+            buf,
+            " & {} \\\\",
+            config
+                .locale
+                .number(config.scale.apply(yearly.get_total()), 2)
+        )
+        .unwrap();
+        writeln!(buf, "      \\hline").unwrap();
+        writeln!(buf, "    \\end{{longtable}}").unwrap();
+        writeln!(buf, "  \\end{{center}}").unwrap();
+        writeln!(buf).unwrap();
+    }
+}
+
+fn write_tex_money_flow(buf: &mut Vec<u8>, stats: &StatsCollection, config: &Config, links: Links) {
+    writeln!(buf, "  \\subsection{{Money flow}}").unwrap();
+    writeln!(buf).unwrap();
+    for (year, yearly) in stats.yearly.iter() {
+        writeln!(buf, "  \\subsubsection*{{{}}}", year).unwrap();
+        writeln!(buf).unwrap();
+        sankey::write_tikz(buf, yearly, config, links);
+        writeln!(buf).unwrap();
+    }
+}
+
+// Monthly spending of the biggest categories, each with its own moving
+// average, so a drift within one category isn't hidden by the others.
+fn write_tex_category_trends(buf: &mut Vec<u8>, stats: &StatsCollection, config: &Config) {
+    if stats.monthly.len() < 2 {
+        return;
+    }
+    let mut totals: Vec<(Category, i64)> = vec![];
+    for (c, v) in stats.yearly.iter().flat_map(|(_, x)| x.by_category.iter()) {
+        match totals.iter_mut().find(|(x, _)| x == c) {
+            Some((_, total)) => *total += v,
+            None => totals.push((c.clone(), *v)),
+        }
+    }
+    totals.sort_by_key(|x| -x.1);
+    totals.truncate(8);
+    config.sort_categories(&mut totals);
+    let months = stats
+        .monthly
+        .iter()
+        .map(|((y, m), _)| format!("{:02}/{}", m, y % 100))
+        .collect::<Vec<_>>();
+
+    writeln!(buf, "  \\section{{Category trends}}").unwrap();
+    writeln!(buf).unwrap();
+    writeln!(
+        buf,
+        "  Monthly spending of the biggest categories, with their {} month moving average.",
+        config.moving_average_window
+    )
+    .unwrap();
+    writeln!(buf).unwrap();
+    for (category, _) in totals.iter() {
+        // Months without spending in the category count as zero.
+        let values = stats
+            .monthly
+            .iter()
+            .map(|(_, x)| {
+                x.by_category
+                    .iter()
+                    .find(|(c, _)| c == category)
+                    .map_or(0.0, |(_, v)| config.scale.apply(*v as f64 / 100.0))
+            })
+            .collect::<Vec<_>>();
+        writeln!(
+            buf,
+            "  \\subsection*{{{}}}",
+            escape_string_for_tex(&category.to_string())
+        )
+        .unwrap();
+        writeln!(buf, "  \\begin{{tikzpicture}}").unwrap();
+        writeln!(buf, "    \\small").unwrap();
+        writeln!(buf, "    \\begin{{axis}}[").unwrap();
+        writeln!(buf, "      symbolic x coords={{{}}},", months.join(", ")).unwrap();
+        writeln!(buf, "      xtick=data,").unwrap();
+        writeln!(buf, "      x tick label style={{rotate=90,font=\\tiny}},").unwrap();
+        writeln!(buf, "      width=\\textwidth,").unwrap();
+        writeln!(buf, "      height=5cm,").unwrap();
+        if config.scale != AmountScale::Units {
+            writeln!(buf, "      ylabel={{{}}},", config.scale.label("Spent")).unwrap();
+        }
+        writeln!(buf, "      ymin=0").unwrap();
+        writeln!(buf, "    ]").unwrap();
+        writeln!(buf, "\\addplot[ybar, fill=black!20] coordinates {{").unwrap();
+        for (value, month) in values.iter().zip(months.iter()) {
+            writeln!(buf, "      ({},{:.2})", month, value).unwrap();
+        }
+        writeln!(buf, "}};").unwrap();
+        writeln!(buf, "\\addplot[smooth, black!67] coordinates {{").unwrap();
+        for (value, month) in moving_average(values, config.moving_average_window as isize)
+            .iter()
+            .zip(months.iter())
+        {
+            writeln!(buf, "      ({},{:.2})", month, value).unwrap();
+        }
+        writeln!(buf, "}};").unwrap();
+        writeln!(buf, "  \\end{{axis}}").unwrap();
+        writeln!(buf, "  \\end{{tikzpicture}}").unwrap();
+        writeln!(buf).unwrap();
+    }
+}
+
+fn write_tex_profile(buf: &mut Vec<u8>, stats: &StatsCollection, config: &Config) {
+    let Some(profile) = &config.profile else {
+        return;
+    };
+    if stats.last_365_days.total == 0 {
+        return;
+    }
+
+    writeln!(
+        buf,
+        "  \\subsection{{Reference profile ({})}}",
+        escape_string_for_tex(&profile.name)
+    )
+    .unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "  Spending of the last 365 days.").unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "  \\begin{{center}}").unwrap();
+    writeln!(buf, "    \\begin{{longtable}}{{l r r r}}").unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    writeln!(buf, "      \\textbf{{Bucket}} & \\multicolumn{{1}}{{l}}{{\\textbf{{Target}}}} & \\multicolumn{{1}}{{l}}{{\\textbf{{Actual}}}} & \\multicolumn{{1}}{{l}}{{\\textbf{{Deviation}}}}\\\\").unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    for deviation in profiles::compare(profile, &stats.last_365_days, config.precision) {
+        writeln!(
+            buf,
+            "      {} & {}\\% & {}\\% & {} pp\\\\",
+            escape_string_for_tex(&deviation.bucket),
+            config.locale.number(deviation.target, config.precision),
+            config.locale.number(deviation.actual, config.precision),
+            config.locale.signed(deviation.points(), config.precision)
+        )
+        .unwrap();
+        writeln!(buf, "      \\hline").unwrap();
+    }
+    writeln!(buf, "    \\end{{longtable}}").unwrap();
+    writeln!(buf, "  \\end{{center}}").unwrap();
+    writeln!(buf).unwrap();
+}
+
+fn write_tex_allowance(buf: &mut Vec<u8>, stats: &StatsCollection, config: &Config) {
+    let (Some(allowance), Some(first)) = (config.daily_allowance, stats.allowance.first()) else {
+        return;
+    };
+
+    writeln!(
+        buf,
+        "  \\section{{Daily allowance ({})}}",
+        config.locale.month_year(first.date)
+    )
+    .unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "  {} per day; the balance is what is left of the allowance accumulated since the first of the month.", config.locale.number(allowance as f64 / 100.0, 2))
+    .unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "  \\begin{{tikzpicture}}").unwrap();
+    writeln!(buf, "    \\small").unwrap();
+    writeln!(buf, "    \\begin{{axis}}[").unwrap();
+    writeln!(buf, "      width=\\textwidth,").unwrap();
+    writeln!(buf, "      height=0.4\\textwidth,").unwrap();
+    writeln!(buf, "      xlabel={{Day}},").unwrap();
+    writeln!(buf, "      ylabel={{Balance}},").unwrap();
+    writeln!(buf, "      xmin=1,").unwrap();
+    writeln!(buf, "      xmax={},", days_in_month(first.date)).unwrap();
+    writeln!(buf, "      extra y ticks={{0}},").unwrap();
+    writeln!(buf, "      extra y tick style={{grid=major}}").unwrap();
+    writeln!(buf, "    ]").unwrap();
+    writeln!(buf, "\\addplot[const plot, black, thick] coordinates {{").unwrap();
+    for day in stats.allowance.iter() {
+        writeln!(
+            buf,
+            "      ({},{:.2})",
+            day.date.day(),
+            day.balance as f64 / 100.0
+        )
+        .unwrap();
+    }
+    writeln!(buf, "}};").unwrap();
+    writeln!(buf, "  \\end{{axis}}").unwrap();
+    writeln!(buf, "  \\end{{tikzpicture}}").unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "  \\begin{{center}}").unwrap();
+    writeln!(buf, "    \\begin{{longtable}}{{l r l r}}").unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    writeln!(buf, "      \\textbf{{Day}} & \\multicolumn{{1}}{{l}}{{\\textbf{{Spent}}}} & & \\multicolumn{{1}}{{l}}{{\\textbf{{Balance}}}}\\\\").unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    for day in stats.allowance.iter() {
+        writeln!(
+            buf,
+            "      {} & {} & {} & {}\\\\",
+            config.locale.date(day.date),
+            config.locale.number(day.spent as f64 / 100.0, 2),
+            if day.is_over(allowance) {
+                "over"
+            } else {
+                "under"
+            },
+            config.locale.signed(day.balance as f64 / 100.0, 2)
+        )
+        .unwrap();
+        writeln!(buf, "      \\hline").unwrap();
+    }
+    writeln!(buf, "    \\end{{longtable}}").unwrap();
+    writeln!(buf, "  \\end{{center}}").unwrap();
+    writeln!(buf).unwrap();
+}
+
+fn write_tex_statement_periods(buf: &mut Vec<u8>, stats: &StatsCollection, config: &Config) {
+    if stats.statement_periods.is_empty() {
+        return;
+    }
+    let label = |first: &NaiveDate| config.locale.short_date(*first);
+
+    writeln!(buf, "  \\section{{Statement periods}}").unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "  \\begin{{tikzpicture}}").unwrap();
+    writeln!(buf, "    \\small").unwrap();
+    writeln!(buf, "    \\begin{{axis}}[").unwrap();
+    writeln!(
+        buf,
+        "      symbolic y coords={{{}}},",
+        stats
+            .statement_periods
+            .iter()
+            .map(|((first, _), _)| label(first))
+            .rev()
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+    .unwrap();
+    writeln!(buf, "      xbar,").unwrap();
+    writeln!(buf, "      ytick=data,").unwrap();
+    writeln!(buf, "      width=\\textwidth,").unwrap();
+    writeln!(buf, "      nodes near coords,").unwrap();
+    writeln!(
+        buf,
+        "      every node near coord/.append style={{anchor=west,font=\\tiny}},"
+    )
+    .unwrap();
+    writeln!(buf, "      xlabel={{{}}},", daily_average_label(config)).unwrap();
+    writeln!(buf, "      enlarge x limits={{value=0.2,upper}},").unwrap();
+    writeln!(buf, "      xmin=0").unwrap();
+    writeln!(buf, "    ]").unwrap();
+    writeln!(buf, "\\addplot[xbar, fill=black!20] coordinates {{").unwrap();
+    for ((first, _), period) in stats.statement_periods.iter() {
+        writeln!(
+            buf,
+            "      ({},{})",
+            config.scale.apply(period.per_day),
+            label(first)
+        )
+        .unwrap();
+    }
+    writeln!(buf, "}};").unwrap();
+    writeln!(buf, "  \\end{{axis}}").unwrap();
+    writeln!(buf, "  \\end{{tikzpicture}}").unwrap();
+    writeln!(buf).unwrap();
+
+    writeln!(buf, "  \\begin{{center}}").unwrap();
+    writeln!(buf, "    \\begin{{longtable}}{{l r r}}").unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    writeln!(
+        buf,
+        "      \\textbf{{Period}} & \\multicolumn{{1}}{{l}}{{\\textbf{{{}}}}} & \\multicolumn{{1}}{{l}}{{\\textbf{{{}}}}}\\\\",
+        config.scale.label("Spent"),
+        config.scale.label("Daily average")
+    )
+    .unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    for ((first, last), period) in stats.statement_periods.iter() {
+        writeln!(
+            buf,
+            "      {} -- {} & {} & {}\\\\",
+            config.locale.date(*first),
+            config.locale.date(*last),
+            config
+                .locale
+                .number(config.scale.apply(period.get_total()), 2),
+            config
+                .locale
+                .number(config.scale.apply(period.per_day), config.precision)
+        )
+        .unwrap();
+        writeln!(buf, "      \\hline").unwrap();
+    }
+    writeln!(buf, "    \\end{{longtable}}").unwrap();
+    writeln!(buf, "  \\end{{center}}").unwrap();
+    writeln!(buf).unwrap();
+
+    writeln!(buf, "  \\subsection{{By Category}}").unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "  \\begin{{center}}").unwrap();
+    writeln!(buf, "    \\begin{{longtable}}{{l r r}}").unwrap();
+    for ((first, last), period) in stats.statement_periods.iter() {
+        writeln!(buf, "      \\hline").unwrap();
+        writeln!(
+            buf,
+            "      \\multicolumn{{3}}{{c}}{{\\textbf{{{} -- {}}}}}\\\\",
+            config.locale.date(*first),
+            config.locale.date(*last)
+        )
+        .unwrap();
+        writeln!(buf, "      \\hline").unwrap();
+        writeln!(buf, "      \\textbf{{Category}} & \\multicolumn{{1}}{{l}}{{\\textbf{{{}}}}} & \\multicolumn{{1}}{{l}}{{\\textbf{{Percentage}}}}\\\\", config.scale.label("Spent")).unwrap();
+        writeln!(buf, "      \\hline").unwrap();
+        let categories = period.ordered_categories(config);
+        for ((cat, value), percentage) in categories
+            .iter()
+            .zip(category_shares(&categories, period, config))
+            .filter(|((c, _), _)| *c != Category::Refunds)
+        {
+            let mark = if is_thin(period, cat, config) {
+                "\\dag"
+            } else {
+                ""
+            };
+            writeln!(
+                buf,
+                "      {}{} & {} & {}\\% \\\\",
+                escape_string_for_tex(&cat.to_string()),
+                mark,
+                config
+                    .locale
+                    .number(config.scale.apply(*value as f64 / 100.0), 2),
+                config.locale.number(percentage, config.precision)
+            )
+            .unwrap();
+            writeln!(buf, "      \\hline").unwrap();
+        }
+        write_tex_refunds(buf, period, None, config);
+    }
+    writeln!(buf, "    \\end{{longtable}}").unwrap();
+    writeln!(buf, "  \\end{{center}}").unwrap();
+    writeln!(buf).unwrap();
+}
+
+// Compares the input files (e.g. one per card) when more than one was given.
+fn write_tex_sources(buf: &mut Vec<u8>, stats: &StatsCollection, config: &Config) {
+    let mut sources = stats
+        .yearly
+        .iter()
+        .flat_map(|(_, x)| x.by_source.iter().map(|(s, _)| s.clone()))
+        .collect::<Vec<_>>();
+    sources.sort();
+    sources.dedup();
+    if sources.len() < 2 {
+        return;
+    }
+
+    writeln!(buf, "  \\section{{Sources}}").unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "  \\begin{{center}}").unwrap();
+    writeln!(buf, "    \\begin{{longtable}}{{l r r}}").unwrap();
+    for (year, yearly) in stats.yearly.iter() {
+        writeln!(buf, "      \\hline").unwrap();
+        writeln!(
+            buf,
+            "      \\multicolumn{{3}}{{c}}{{\\textbf{{{}}}}}\\\\",
+            year
+        )
+        .unwrap();
+        writeln!(buf, "      \\hline").unwrap();
+        writeln!(buf, "      \\textbf{{Source}} & \\multicolumn{{1}}{{l}}{{\\textbf{{Spent}}}} & \\multicolumn{{1}}{{l}}{{\\textbf{{Percentage}}}}\\\\").unwrap();
+        writeln!(buf, "      \\hline").unwrap();
+        for ((source, value), percentage) in
+            yearly
+                .by_source
+                .iter()
+                .zip(shares(&yearly.by_source, yearly.total, config))
+        {
+            writeln!(
+                buf,
+                "      \\texttt{{{}}} & {} & {}\\% \\\\",
+                escape_string_for_tex(source),
+                config.locale.number(*value as f64 / 100.0, 2),
+                config.locale.number(percentage, config.precision)
+            )
+            .unwrap();
+            writeln!(buf, "      \\hline").unwrap();
+        }
+    }
+    writeln!(buf, "    \\end{{longtable}}").unwrap();
+    writeln!(buf, "  \\end{{center}}").unwrap();
+    writeln!(buf).unwrap();
+
+    writeln!(buf, "  \\subsection{{Monthly comparison}}").unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "  \\begin{{center}}").unwrap();
+    writeln!(
+        buf,
+        "    \\begin{{longtable}}{{l {}}}",
+        vec!["r"; sources.len()].join(" ")
+    )
+    .unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    writeln!(
+        buf,
+        "      \\textbf{{Month}} & {}\\\\",
+        sources
+            .iter()
+            .map(|x| format!("\\texttt{{{}}}", escape_string_for_tex(x)))
+            .collect::<Vec<_>>()
+            .join(" & ")
+    )
+    .unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    for ((y, m), monthly) in stats.monthly.iter() {
+        let month_name = config.locale.month(*m);
+        let values = sources
+            .iter()
+            .map(|source| {
+                let value = monthly
+                    .by_source
+                    .iter()
+                    .find(|(s, _)| s == source)
+                    .map(|(_, v)| *v)
+                    .unwrap_or(0);
+                config.locale.number(value as f64 / 100.0, 2)
+            })
+            .collect::<Vec<_>>();
+        writeln!(
+            buf,
+            "      {} {} & {}\\\\",
+            month_name,
+            y,
+            values.join(" & ")
+        )
+        .unwrap();
+        writeln!(buf, "      \\hline").unwrap();
+    }
+    writeln!(buf, "    \\end{{longtable}}").unwrap();
+    writeln!(buf, "  \\end{{center}}").unwrap();
+    writeln!(buf).unwrap();
+}
+
+fn write_tex_metrics(buf: &mut Vec<u8>, stats: &StatsCollection, config: &Config) {
+    if config.metrics.is_empty() {
+        return;
+    }
+
+    let format_metrics = |stats: &Stats| {
+        config
+            .metrics
+            .iter()
+            .map(|(_, expr)| {
+                metrics::eval(expr, stats)
+                    .map(|x| config.locale.number(config.round(x), config.precision))
+                    .unwrap_or(String::from("--"))
+            })
+            .collect::<Vec<_>>()
+            .join(" & ")
+    };
+    let header = config
+        .metrics
+        .iter()
+        .map(|(name, _)| {
+            format!(
+                "\\multicolumn{{1}}{{l}}{{\\textbf{{{}}}}}",
+                escape_string_for_tex(name)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" & ");
+    let columns = vec!["r"; config.metrics.len()].join(" ");
+
+    writeln!(buf, "  \\section{{Metrics}}").unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "  \\begin{{center}}").unwrap();
+    writeln!(buf, "    \\begin{{longtable}}{{l {}}}", columns).unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    writeln!(buf, "      \\textbf{{Year}} & {}\\\\", header).unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    for (year, yearly) in stats.yearly.iter() {
+        writeln!(buf, "      {} & {}\\\\", year, format_metrics(yearly)).unwrap();
+        writeln!(buf, "      \\hline").unwrap();
+    }
+    for (name, period) in [
+        ("Last 365 days", &stats.last_365_days),
+        ("Last 30 days", &stats.last_30_days),
+    ] {
+        writeln!(buf, "      {} & {}\\\\", name, format_metrics(period)).unwrap();
+        writeln!(buf, "      \\hline").unwrap();
+    }
+    writeln!(buf, "    \\end{{longtable}}").unwrap();
+    writeln!(buf, "  \\end{{center}}").unwrap();
+    writeln!(buf).unwrap();
+
+    writeln!(buf, "  \\subsection{{Monthly}}").unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "  \\begin{{center}}").unwrap();
+    writeln!(buf, "    \\begin{{longtable}}{{l {}}}", columns).unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    writeln!(buf, "      \\textbf{{Month}} & {}\\\\", header).unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    for ((y, m), monthly) in stats.monthly.iter() {
+        let month_name = config.locale.month(*m);
+        writeln!(
+            buf,
+            "      {} {} & {}\\\\",
+            month_name,
+            y,
+            format_metrics(monthly)
+        )
+        .unwrap();
+        writeln!(buf, "      \\hline").unwrap();
+    }
+    writeln!(buf, "    \\end{{longtable}}").unwrap();
+    writeln!(buf, "  \\end{{center}}").unwrap();
+    writeln!(buf).unwrap();
+}
+
+fn write_tex_share_targets(buf: &mut Vec<u8>, stats: &StatsCollection, config: &Config) {
+    if config.share_targets.is_empty() {
+        return;
+    }
+
+    let header = config
+        .share_targets
+        .iter()
+        .map(|(c, target)| {
+            format!(
+                "\\multicolumn{{1}}{{l}}{{\\textbf{{{} ($\\leq$ {}\\%)}}}}",
+                escape_string_for_tex(&c.to_string()),
+                target
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" & ");
+    let columns = vec!["r"; config.share_targets.len()].join(" ");
+
+    writeln!(buf, "  \\section{{Target shares}}").unwrap();
+    writeln!(buf).unwrap();
+    writeln!(
+        buf,
+        "  Share of each month's spending, with its trend from the month before. Shares over their target are in bold; \\dag{{}} stands for the trend of a month with fewer than {} transactions in the category.",
+        config.min_sample
+    )
+    .unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "  \\begin{{center}}").unwrap();
+    writeln!(buf, "    \\begin{{longtable}}{{l {}}}", columns).unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    writeln!(buf, "      \\textbf{{Month}} & {}\\\\", header).unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    let mut previous: Option<&Stats> = None;
+    for ((y, m), monthly) in stats.monthly.iter() {
+        let month_name = config.locale.month(*m);
+        let cells = config
+            .share_targets
+            .iter()
+            .map(|(c, target)| {
+                let share = category_share(monthly, c, config);
+                let before = previous.map(|x| category_share(x, c, config));
+                let arrow = match share_trend(share, before) {
+                    _ if is_thin(monthly, c, config) => "\\dag",
+                    Ordering::Greater => "$\\uparrow$",
+                    Ordering::Less => "$\\downarrow$",
+                    Ordering::Equal => "$\\rightarrow$",
+                };
+                let text = format!("{}\\%", config.locale.number(share, config.precision));
+                if share > *target {
+                    format!("\\textbf{{{}}} {}", text, arrow)
+                } else {
+                    format!("{} {}", text, arrow)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" & ");
+        writeln!(buf, "      {} {} & {}\\\\", month_name, y, cells).unwrap();
+        writeln!(buf, "      \\hline").unwrap();
+        previous = Some(monthly);
+    }
+    writeln!(buf, "    \\end{{longtable}}").unwrap();
+    writeln!(buf, "  \\end{{center}}").unwrap();
+    writeln!(buf).unwrap();
+}
+
+fn write_tex_note_groups(buf: &mut Vec<u8>, stats: &StatsCollection, config: &Config) {
+    if config.note_groups.is_empty() {
+        return;
+    }
+    let groups = config
+        .note_groups
+        .iter()
+        .map(|(name, patterns)| (name, notegroups::monthly(patterns, stats)))
+        .collect::<Vec<_>>();
+
+    writeln!(buf, "  \\section{{Note groups}}").unwrap();
+    writeln!(buf).unwrap();
+    writeln!(
+        buf,
+        "  Averages of the last 12 complete months; the trend compares the last 3 of them with the 3 before."
+    )
+    .unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "  \\begin{{center}}").unwrap();
+    writeln!(buf, "    \\begin{{longtable}}{{l r r r r}}").unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    writeln!(buf, "      \\textbf{{Group}} & \\multicolumn{{1}}{{l}}{{\\textbf{{Per month}}}} & \\multicolumn{{1}}{{l}}{{\\textbf{{Per occurrence}}}} & \\multicolumn{{1}}{{l}}{{\\textbf{{Occurrences per month}}}} & \\multicolumn{{1}}{{l}}{{\\textbf{{Trend}}}}\\\\").unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    for (name, months) in groups.iter() {
+        let kpi = notegroups::kpi(months, config.today());
+        writeln!(
+            buf,
+            "      {} & {} & {} & {} & {}\\\\",
+            escape_string_for_tex(name),
+            config.locale.number(kpi.per_month, 2),
+            kpi.per_occurrence
+                .map(|x| config.locale.number(x, 2))
+                .unwrap_or(String::from("--")),
+            config.locale.number(kpi.occurrences_per_month, 1),
+            kpi.trend()
+                .map(|x| format!("{}\\%", config.locale.signed(x, 0)))
+                .unwrap_or(String::from("--"))
+        )
+        .unwrap();
+        writeln!(buf, "      \\hline").unwrap();
+    }
+    writeln!(buf, "    \\end{{longtable}}").unwrap();
+    writeln!(buf, "  \\end{{center}}").unwrap();
+    writeln!(buf).unwrap();
+
+    let header = groups
+        .iter()
+        .map(|(name, _)| {
+            format!(
+                "\\multicolumn{{1}}{{l}}{{\\textbf{{{}}}}}",
+                escape_string_for_tex(name)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" & ");
+    writeln!(buf, "  \\subsection{{Monthly}}").unwrap();
+    writeln!(buf).unwrap();
+    writeln!(
+        buf,
+        "  Total, with the number of occurrences in parentheses."
+    )
+    .unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "  \\begin{{center}}").unwrap();
+    writeln!(
+        buf,
+        "    \\begin{{longtable}}{{l {}}}",
+        vec!["r"; groups.len()].join(" ")
+    )
+    .unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    writeln!(buf, "      \\textbf{{Month}} & {}\\\\", header).unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    for (i, ((y, m), _)) in stats.monthly.iter().enumerate() {
+        let month_name = config.locale.month(*m);
+        let cells = groups
+            .iter()
+            .map(|(_, months)| {
+                let month = &months[i].1;
+                format!(
+                    "{} ({})",
+                    config.locale.number(month.total as f64 / 100.0, 2),
+                    month.count
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" & ");
+        writeln!(buf, "      {} {} & {}\\\\", month_name, y, cells).unwrap();
+        writeln!(buf, "      \\hline").unwrap();
+    }
+    writeln!(buf, "    \\end{{longtable}}").unwrap();
+    writeln!(buf, "  \\end{{center}}").unwrap();
+    writeln!(buf).unwrap();
+}
+
+fn write_tex_special_dates(buf: &mut Vec<u8>, stats: &StatsCollection, config: &Config) {
+    let periods = specialdates::compare(stats, config);
+    let Some(normal) = periods.iter().find(|x| x.name.is_none()) else {
+        return;
+    };
+    if periods.len() < 2 {
+        return;
+    }
+
+    writeln!(buf, "  \\section{{Special dates}}").unwrap();
+    writeln!(buf).unwrap();
+    writeln!(
+        buf,
+        "  Spending on the days of each entry of \\texttt{{[dates]}}, against the days in none of them. Ranges are marked in the overview chart."
+    )
+    .unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "  \\begin{{center}}").unwrap();
+    writeln!(buf, "    \\begin{{longtable}}{{l r r r r}}").unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    writeln!(buf, "      \\textbf{{Dates}} & \\multicolumn{{1}}{{l}}{{\\textbf{{Days}}}} & \\multicolumn{{1}}{{l}}{{\\textbf{{Spent}}}} & \\multicolumn{{1}}{{l}}{{\\textbf{{Per day}}}} & \\multicolumn{{1}}{{l}}{{\\textbf{{Against normal days}}}}\\\\").unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    for period in periods.iter() {
+        let difference = match &period.name {
+            Some(_) if normal.total > 0 => format!(
+                "{}\\%",
+                config
+                    .locale
+                    .signed((period.per_day() / normal.per_day() - 1.0) * 100.0, 0)
+            ),
+            _ => String::from("--"),
+        };
+        writeln!(
+            buf,
+            "      {} & {} & {} & {} & {}\\\\",
+            period.name.as_deref().map_or(
+                String::from("\\textit{{Normal days}}"),
+                escape_string_for_tex
+            ),
+            period.days,
+            config.locale.number(period.total as f64 / 100.0, 2),
+            config
+                .locale
+                .number(config.round(period.per_day()), config.precision),
+            difference
+        )
+        .unwrap();
+        writeln!(buf, "      \\hline").unwrap();
+    }
+    writeln!(buf, "    \\end{{longtable}}").unwrap();
+    writeln!(buf, "  \\end{{center}}").unwrap();
+    writeln!(buf).unwrap();
+}
+
+fn write_tex_merchants(buf: &mut Vec<u8>, stats: &StatsCollection, config: &Config, links: Links) {
+    if stats.merchants.is_empty() {
+        return;
+    }
+
+    writeln!(buf, "  \\section{{Merchants}}").unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "  \\begin{{center}}").unwrap();
+    writeln!(buf, "    \\begin{{longtable}}{{l r r r l l}}").unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    writeln!(buf, "      \\textbf{{Note}} & \\multicolumn{{1}}{{l}}{{\\textbf{{Visits}}}} & \\multicolumn{{1}}{{l}}{{\\textbf{{Average ticket}}}} & \\multicolumn{{1}}{{l}}{{\\textbf{{Spent}}}} & \\textbf{{First seen}} & \\textbf{{Last seen}}\\\\").unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    for merchant in stats.merchants.iter() {
+        writeln!(
+            buf,
+            "      \\textquote{{{}}} & {} & {} & {} & {} & {}\\\\",
+            note_for_tex(&merchant.name, config, links),
+            merchant.visits,
+            config
+                .locale
+                .number(config.round(merchant.average_ticket()), config.precision),
+            config.locale.number(merchant.total as f64 / 100.0, 2),
+            config.locale.date(merchant.first_seen),
+            config.locale.date(merchant.last_seen)
+        )
+        .unwrap();
+        writeln!(buf, "      \\hline").unwrap();
+    }
+    writeln!(buf, "    \\end{{longtable}}").unwrap();
+    writeln!(buf, "  \\end{{center}}").unwrap();
+    writeln!(buf).unwrap();
+}
+
+fn write_tex_keywords(buf: &mut Vec<u8>, stats: &StatsCollection, config: &Config) {
+    if stats.keywords.is_empty() {
+        return;
+    }
+    let keywords = &stats.keywords[..stats.keywords.len().min(30)];
+    let total = stats.yearly.iter().map(|(_, s)| s.total).sum::<i64>();
+
+    writeln!(buf, "  \\section{{Note keywords}}").unwrap();
+    writeln!(buf).unwrap();
+    // A word cloud, in alphabetical order so the sizes stand out: from 9pt to
+    // 30pt, by the square root of the spending so one word doesn't crush the rest.
+    let max = keywords[0].total as f64;
+    let mut cloud = keywords.iter().collect::<Vec<_>>();
+    cloud.sort_by(|x, y| x.word.cmp(&y.word));
+    writeln!(buf, "  \\begin{{center}}").unwrap();
+    for k in cloud {
+        let size = 9.0 + 21.0 * (k.total as f64 / max).sqrt();
+        writeln!(
+            buf,
+            "    {{\\fontsize{{{:.1}pt}}{{{:.1}pt}}\\selectfont {}}}\\quad",
+            size,
+            size * 1.2,
+            escape_string_for_tex(&k.word)
+        )
+        .unwrap();
+    }
+    writeln!(buf, "  \\end{{center}}").unwrap();
+    writeln!(buf).unwrap();
+
+    writeln!(buf, "  \\begin{{center}}").unwrap();
+    writeln!(buf, "    \\begin{{longtable}}{{l r r r l}}").unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    writeln!(buf, "      \\textbf{{Keyword}} & \\multicolumn{{1}}{{l}}{{\\textbf{{Transactions}}}} & \\multicolumn{{1}}{{l}}{{\\textbf{{Spent}}}} & \\multicolumn{{1}}{{l}}{{\\textbf{{Of all}}}} & \\textbf{{Mostly in}}\\\\").unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    for k in keywords {
+        let categories = k
+            .categories
+            .iter()
+            .take(3)
+            .map(|(c, _)| escape_string_for_tex(&c.to_string()))
+            .collect::<Vec<_>>();
+        writeln!(
+            buf,
+            "      {} & {} & {} & {}\\% & {}\\\\",
+            escape_string_for_tex(&k.word),
+            k.count,
+            config.locale.number(k.total as f64 / 100.0, 2),
+            config.locale.number(
+                config.round(k.total as f64 / total as f64 * 100.0),
+                config.precision
+            ),
+            categories.join(", ")
+        )
+        .unwrap();
+        writeln!(buf, "      \\hline").unwrap();
+    }
+    writeln!(buf, "    \\end{{longtable}}").unwrap();
+    writeln!(buf, "  \\end{{center}}").unwrap();
+    writeln!(buf).unwrap();
+}
+
+fn write_tex_round_ups(buf: &mut Vec<u8>, stats: &StatsCollection, config: &Config) {
+    let header = roundup::STEPS
+        .iter()
+        .map(|step| format!("\\multicolumn{{1}}{{l}}{{\\textbf{{To {}}}}}", step))
+        .collect::<Vec<_>>()
+        .join(" & ");
+    let columns = "r ".repeat(roundup::STEPS.len());
+    let cells = |saved: Vec<i64>| {
+        saved
+            .iter()
+            .map(|x| config.locale.number(*x as f64 / 100.0, 2))
+            .collect::<Vec<_>>()
+            .join(" & ")
+    };
+
+    writeln!(buf, "  \\section{{Round-up savings}}").unwrap();
+    writeln!(buf).unwrap();
+    writeln!(
+        buf,
+        "  What would have been put aside by rounding every payment up to the next whole unit, or the next 5, and saving the difference. Refunds and savings are left out."
+    )
+    .unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "  \\begin{{center}}").unwrap();
+    writeln!(buf, "    \\begin{{longtable}}{{l {}}}", columns.trim_end()).unwrap();
+    for (year, yearly) in stats.yearly.iter() {
+        writeln!(buf, "      \\hline").unwrap();
+        writeln!(buf, "      \\textbf{{{}}} & {}\\\\", year, header).unwrap();
+        writeln!(buf, "      \\hline").unwrap();
+        writeln!(buf, "      \\hline").unwrap();
+        for ((_, m), monthly) in stats.monthly.iter().filter(|((y, _), _)| y == year) {
+            let month_name = config.locale.month(*m);
+            writeln!(
+                buf,
+                "      {} & {}\\\\",
+                month_name,
+                cells(roundup::saved(monthly))
+            )
+            .unwrap();
+            writeln!(buf, "      \\hline").unwrap();
+        }
+        writeln!(
+            buf,
+            "      \\textbf{{Total}} & {}\\\\",
+            cells(roundup::saved(yearly))
+        )
+        .unwrap();
+        writeln!(buf, "      \\hline").unwrap();
+    }
+    writeln!(buf, "    \\end{{longtable}}").unwrap();
+    writeln!(buf, "  \\end{{center}}").unwrap();
+    writeln!(buf).unwrap();
+}
+
+fn write_tex_durables(buf: &mut Vec<u8>, stats: &StatsCollection, config: &Config, links: Links) {
+    if stats.durables.is_empty() {
+        return;
+    }
+
+    writeln!(buf, "  \\section{{Durable purchases}}").unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "  \\begin{{center}}").unwrap();
+    writeln!(buf, "    \\begin{{longtable}}{{l l r r r r r}}").unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    writeln!(buf, "      \\textbf{{Note}} & \\textbf{{Bought}} & \\multicolumn{{1}}{{l}}{{\\textbf{{Cost}}}} & \\multicolumn{{1}}{{l}}{{\\textbf{{Months}}}} & \\multicolumn{{1}}{{l}}{{\\textbf{{Per month}}}} & \\multicolumn{{1}}{{l}}{{\\textbf{{Per use}}}} & \\multicolumn{{1}}{{l}}{{\\textbf{{Months left}}}}\\\\").unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    writeln!(buf, "      \\hline").unwrap();
+    for durable in stats.durables.iter() {
+        writeln!(
+            buf,
+            "      \\textquote{{{}}} & {} & {} & {} & {} & {} & {}\\\\",
+            note_for_tex(&durable.name, config, links),
+            config.locale.date(durable.bought),
+            config.locale.number(durable.price as f64 / 100.0, 2),
+            durable.months_owned,
+            config
+                .locale
+                .number(config.round(durable.per_month()), config.precision),
+            durable
+                .per_use()
+                .map(|x| config.locale.number(config.round(x), config.precision))
+                .unwrap_or(String::from("--")),
+            durable
+                .lifetime_left
+                .map(|x| x.to_string())
+                .unwrap_or(String::from("--"))
+        )
+        .unwrap();
+        writeln!(buf, "      \\hline").unwrap();
+    }
+    writeln!(buf, "    \\end{{longtable}}").unwrap();
+    writeln!(buf, "  \\end{{center}}").unwrap();
+    writeln!(buf).unwrap();
+}
+
+fn plot_monthly_usage(
+    filepath: &Path,
+    transactions: &[Transaction],
+    stats: &StatsCollection,
+    config: &Config,
+) -> Result<(), Diagnostic> {
+    // The backend writes the image itself, so there is nothing to describe but the path.
+    if files::is_dry_run() {
+        println!("Would write a chart to `{}`.", filepath.display());
+        return Ok(());
+    }
+    let max_value: f64 = stats
+        .monthly
+        .iter()
+        .map(|(_, b)| b.per_day)
+        .max_by(|a, b| a.partial_cmp(b).unwrap())
+        .unwrap();
+    let magic_factor = 1.1;
+    let first = transactions.first().unwrap();
+    let last = transactions.last().unwrap();
+    let start_year = first.date.year_ce().1 as i32 * if first.date.year_ce().0 { 1 } else { -1 };
+    let start_month = first.date.month0();
+    let end_year = last.date.year_ce().1 as i32 * if last.date.year_ce().0 { 1 } else { -1 };
+    let end_month = last.date.month0();
+
+    let num_months = end_year * 12 - start_year * 12 + end_month as i32 - start_month as i32;
+
+    let monthly_values = stats
+        .monthly
+        .iter()
+        .map(|x| x.1.per_day)
+        .collect::<Vec<_>>();
+
+    let month_labels = (0..=num_months).map(|x| {
+        let n = x + start_month as i32;
+        let year = start_year + n / 12;
+        let month = n % 12 + 1;
+        format!("{:02}/{}", month, year)
+    });
+
+    let month_value_labels = (0..=num_months).map(|x| format!("{:.2}", monthly_values[x as usize]));
+
+    let temp_path = files::temp_path_for(filepath);
+    let root = BitMapBackend::new(&temp_path, (960 * 2, 720 * 2)).into_drawing_area();
+    root.fill(&WHITE).unwrap();
+
+    // Create a chart builder
+    let mut chart = ChartBuilder::on(&root)
+        .caption("Monthly Spending", ("serif", 64).into_font())
+        .x_label_area_size(100)
+        .y_label_area_size(100)
+        .right_y_label_area_size(100)
+        .margin(50)
+        .build_cartesian_2d(
+            (0.0..((num_months + 1) as f32)).step(1.0),
+            0.0..max_value * magic_factor,
+        )
+        .unwrap();
+
+    // Configure the axes
+    chart
+        .configure_mesh()
+        .x_desc("Months")
+        .y_desc("Daily average")
+        .axis_desc_style(("serif", 32).into_font())
+        .x_label_style(("serif", 24).into_font())
+        .y_label_style(("serif", 24).into_font())
+        .x_labels((num_months + 1) as usize)
+        .y_labels(10)
+        .x_label_formatter(&|_| String::default())
+        .draw()
+        .unwrap();
+
+    chart
+        .draw_series(monthly_values.iter().enumerate().map(|(month, &v)| {
+            Rectangle::new(
+                [(month as f32, 0.0), ((month + 1) as f32, v)],
+                RED.mix((v / max_value).sqrt()).filled(),
+            )
+        }))
+        .unwrap();
+
+    let font = ("serif", 28.0).into_font();
+    let pixels_per_unit_x =
+        chart.plotting_area().get_x_axis_pixel_range().len() as f32 / num_months as f32;
+    let pixels_per_unit_y =
+        chart.plotting_area().get_y_axis_pixel_range().len() as f64 / (max_value * magic_factor);
+
+    for (i, label) in month_labels.into_iter().enumerate() {
+        let offset_x = (font.box_size(&label).unwrap().0 as f32) / pixels_per_unit_x;
+        // let offset_y = (font.box_size(&label).unwrap().1 as f64) / pixels_per_unit_y;
+        chart
+            .draw_series(std::iter::once(Text::new(
+                label,
+                (i as f32 + 0.5 - offset_x * 0.5, -20.0), // Positioning the label
+                font.clone(),
+            )))
+            .unwrap();
+    }
+
+    for (i, label) in month_value_labels.into_iter().enumerate() {
+        let offset_x = (font.box_size(&label).unwrap().0 as f32) / pixels_per_unit_x;
+        let offset_y = (font.box_size(&label).unwrap().1 as f64) / pixels_per_unit_y;
+        chart
+            .draw_series(std::iter::once(Text::new(
+                label,
+                (
+                    i as f32 + 0.5 - offset_x * 0.5,
+                    (monthly_values[i] + offset_y) / 2.0,
+                ), // Positioning the label
+                font.clone(),
+            )))
+            .unwrap();
+    }
+
+    let mut pts = if true {
+        let values = stats
+            .monthly
+            .iter()
+            .map(|(a, b)| {
+                (
+                    b.per_day,
+                    days_in_month(NaiveDate::from_ymd_opt(a.0, a.1, 1).unwrap()) as f64,
+                )
+            })
+            .collect();
+        weighted_moving_average(values, config.moving_average_window as isize)
+    } else {
+        let values = stats.monthly.iter().map(|x| x.1.per_day).collect();
+        moving_average(values, config.moving_average_window as isize)
+    }
+    .iter()
+    .enumerate()
+    .map(|(i, v)| (i as f32 + 0.5, *v))
+    .collect::<Vec<_>>();
+
+    pts.insert(0, (0.0, pts.first().unwrap().1));
+    pts.push(((num_months + 1) as f32, pts.last().unwrap().1));
+
+    chart
+        .draw_series(LineSeries::new(pts.clone(), AMBER.stroke_width(10)))
+        .unwrap();
+
+    {
+        let value = pts.last().unwrap().1;
+        let label = format!("Average: {:.2}", value);
+        let offset_x = (font.box_size(&label).unwrap().0 as f32) / pixels_per_unit_x;
+        let offset_y = (font.box_size(&label).unwrap().1 as f64) / pixels_per_unit_y;
+        chart
+            .draw_series(std::iter::once(Text::new(
+                label,
+                (
+                    (num_months as f32 + 1.0) - offset_x - 20.0 / pixels_per_unit_x,
+                    value + offset_y * 1.5,
+                ), // Positioning the label
+                font.clone().style(FontStyle::Bold),
+            )))
+            .unwrap();
+    }
+
+    root.present().unwrap();
+    files::commit_temp_file(&temp_path, filepath).map_err(|e| {
+        diagnostics::error(
+            "io-write",
+            Some(Location::file(&filepath.display().to_string())),
+            format!("Could not write file: {}", e),
+        )
+    })
+}
+
+// The command line program, see `main.rs`. Errors come back up to here,
+// where they are printed like any other diagnostic before exiting.
+pub fn run() {
+    diagnostics::set_print(true);
+    if let Err(diagnostic) = run_command() {
+        diagnostics::emit(diagnostic);
+        exit(1);
+    }
+}
+
+fn run_command() -> Result<(), Diagnostic> {
+    let args = parse_args()?;
+    if args.command == Command::Init {
+        // Neither file has to exist (or make sense) yet, so nothing is loaded.
+        let config_path = args
+            .config_path
+            .clone()
+            .unwrap_or_else(config::default_config_path);
+        return init::run(&config_path, args.inputs.first().map(Path::new));
+    }
+    if args.command == Command::SelfUpdate {
+        return selfupdate::run(args.check);
+    }
+    if let Command::Verify(report) = &args.command {
+        // Only the files' bytes matter, not how they are read.
+        if args.inputs.is_empty() {
+            print_usage();
+            return Err(diagnostics::error(
+                "no-input",
+                None,
+                String::from("No file provided"),
+            ));
+        }
+        return verify::run(report, &args.inputs);
+    }
+    let mut config = Config::load(args.config_path.as_deref())?;
+    let read_options = ReadOptions {
+        format: config.csv.clone(),
+        ..args.read_options.clone()
+    };
+    let sources = open_sources(&args.inputs, &read_options)?;
+    if let Some(timezone) = args.timezone {
+        config.timezone = timezone;
+        config.set_by_flag("timezone");
+    }
+    if args.statement_day.is_some() {
+        config.statement_day = args.statement_day;
+        config.set_by_flag("statement_day");
+    }
+    if args.amortize {
+        config.amortize = true;
+        config.set_by_flag("amortize");
+    }
+    let period = args
+        .period
+        .as_ref()
+        .map(|value| {
+            period::parse(value, config.today()).ok_or_else(|| {
+                diagnostics::error(
+                    "cli-invalid-value",
+                    None,
+                    format!(
+                        "Invalid period `{}` (expected e.g. `last month`, `ytd`, `2024-q2`, `last 90 days`)",
+                        value
+                    ),
+                )
+            })
+        })
+        .transpose()?;
+
+    if args.command == Command::Schema {
+        schema::run(&sources, &config);
+        return Ok(());
+    }
+
+    if sources.is_empty() {
+        print_usage();
+        return Err(diagnostics::error(
+            "no-input",
+            None,
+            String::from("No file provided"),
+        ));
+    }
+
+    // Outputs go next to the (first) data file, or in the working directory when reading stdin.
+    let out_path = sources[0]
+        .path()
+        .cloned()
+        .unwrap_or_else(|| PathBuf::from(env!("CARGO_PKG_NAME")));
+
+    if args.command == Command::History {
+        archive::history(&out_path);
+        return Ok(());
+    }
+
+    if let Command::Add(entry) = &args.command {
+        let Some(path) = sources[0].path() else {
+            return Err(diagnostics::error(
+                "add-no-file",
+                None,
+                String::from("`add` needs a file to write to"),
+            ));
+        };
+        if path
+            .extension()
+            .is_some_and(|x| x.eq_ignore_ascii_case("json"))
+        {
+            return Err(diagnostics::error(
+                "add-no-file",
+                Some(Location::file(&path.display().to_string())),
+                String::from("`add` only writes to CSV files"),
+            ));
+        }
+        return add::run(entry, path, &config);
+    }
+
+    // Held for the whole run, so nobody rewrites the files under our feet.
+    let _locks = sources
+        .iter()
+        .filter_map(|source| source.path())
+        .map(|path| {
+            files::lock_data_file(path, false).map_err(|e| {
+                diagnostics::error(
+                    "io-lock",
+                    Some(Location::file(&path.display().to_string())),
+                    format!("Could not lock file: {}", e),
+                )
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut transactions = read_sources(&sources)?;
+    transactions.sort_by_key(|t| t.date);
+    // The digest's watermark records everything read, whatever `--period`
+    // and the filters below leave out of this run.
+    let read_ids = if args.command == Command::Digest {
+        transactions.iter().map(|t| t.id.clone()).collect()
+    } else {
+        vec![]
+    };
+    // For the report footers.
+    let mut filters = vec![];
+    if let Some((from, to)) = period {
+        filters.push(format!(
+            "period {} to {}",
+            from.format("%d/%m/%Y"),
+            to.format("%d/%m/%Y")
+        ));
+        transactions.retain(|t| from <= t.date && t.date <= to);
+        diagnostics::info(
+            "period",
+            None,
+            format!(
+                "Only transactions from {} to {}.",
+                from.format("%d/%m/%Y"),
+                to.format("%d/%m/%Y")
+            ),
+        );
+    }
+    if !args.include_pending {
+        let count = transactions.len();
+        transactions.retain(|t| t.status != Status::Pending);
+        let pending = count - transactions.len();
+        if pending > 0 {
+            filters.push(format!("{} pending left out", pending));
+            diagnostics::info(
+                "pending-excluded",
+                None,
+                format!(
+                    "{} pending transaction(s) left out (use `--include-pending` to count them).",
+                    pending
+                ),
+            );
+        }
+    }
+    let reclassified = categorize::apply(&mut transactions, &config);
+    if !config.net_refunds {
+        for t in transactions.iter_mut().filter(|t| t.value < 0) {
+            t.category = Category::Refunds;
+        }
+    }
+
+    if transactions.is_empty() && args.command != Command::Digest {
+        diagnostics::info(
+            "no-transactions",
+            None,
+            String::from("Provided file has no transactions. Exiting..."),
+        );
+        return Ok(());
+    }
+
+    if args.command == Command::AliasesSuggest {
+        return categorize::run_suggest(&transactions, &config);
+    }
+
+    if args.command == Command::BudgetSuggest {
+        return budget::run_suggest(&transactions, &config);
+    }
+
+    let source_name = sources
+        .iter()
+        .map(|x| x.name())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let fingerprints = sources
+        .iter()
+        .filter_map(|x| Some((x.name(), x.sha256()?)))
+        .collect::<Vec<_>>();
+
+    if args.command == Command::Export {
+        let write = |path: &Path, csv: String, what: &str| {
+            if let Err(e) = files::write_atomically(path, csv.as_bytes()) {
+                return Err(diagnostics::error(
+                    "io-write",
+                    Some(Location::file(&path.display().to_string())),
+                    format!("Could not write file: {}", e),
+                ));
+            }
+            if !files::is_dry_run() {
+                println!("{} saved in `{}`.", what, path.display());
+            }
+            Ok(())
+        };
+        if let Some(path) = &args.daily_path {
+            let from = transactions.iter().map(|t| t.date).min().unwrap();
+            let csv = daily::daily_csv(
+                &transactions,
+                from,
+                config.today(),
+                config.amortize,
+                &config.csv,
+            );
+            write(path, csv, "Daily series")?;
+        }
+        if let Some(path) = &args.pivot_path {
+            let stats = get_stats(&transactions, &config);
+            let csv = pivot::pivot_csv(&stats.yearly, &config);
+            write(path, csv, "Category by payment method table")?;
+        }
+        return Ok(());
+    }
+
+    if args.command == Command::Explore {
+        filter::run(&transactions, &config);
+        return Ok(());
+    }
+
+    if args.command == Command::Review {
+        return review::run(&transactions, &state_key(&sources), &config);
+    }
+
+    if args.command == Command::Household {
+        return household::run(
+            &out_path,
+            &transactions,
+            &reclassified,
+            &fingerprints,
+            &filters,
+            &config,
+        );
+    }
+
+    if let Command::Batch(path) = &args.command {
+        return batch::run(
+            path,
+            &transactions,
+            &reclassified,
+            &source_name,
+            &fingerprints,
+            &filters,
+            &config,
+        );
+    }
+
+    if args.command == Command::Digest {
+        return digest::run(
+            &transactions,
+            &read_ids,
+            &state_key(&sources),
+            &args.since,
+            &config,
+        );
+    }
+
+    let stats = get_stats(&transactions, &config);
+
+    if args.command == Command::Copy {
+        let today = config.today();
+        let month = args
+            .month
+            .unwrap_or((year_as_i32(today.year_ce()), today.month()));
+        return copy::run(&stats, month, &config);
+    }
+
+    if args.command == Command::Card {
+        let year = args
+            .year
+            .unwrap_or_else(|| year_as_i32(config.today().year_ce()));
+        let mut card_path = out_path.clone();
+        card_path.set_file_name(format!(
+            "{}-{}.png",
+            out_path.file_stem().unwrap_or_default().to_string_lossy(),
+            year
+        ));
+        return card::write(&card_path, year, &stats);
+    }
+
+    print_stats(&stats, &config);
+    for (name, sha256) in fingerprints.iter() {
+        println!("Data: `{}` (SHA-256 {})", name, sha256);
+    }
+    let to_review = review::waiting(&transactions, &state_key(&sources), &config);
+    if to_review > 0 {
+        println!(
+            "{} transaction(s) waiting for review, see `{} review`.",
+            to_review,
+            env!("CARGO_PKG_NAME")
+        );
+    }
+    let misspelled = categorize::suggest_aliases(&transactions).len();
+    if misspelled > 0 {
+        println!(
+            "{} category name(s) look misspelled, see `{} aliases suggest`.",
+            misspelled,
+            env!("CARGO_PKG_NAME")
+        );
+    }
+    if !reclassified.is_empty() {
+        println!(
+            "{} transaction(s) were reclassified by aliases or rules, see the report appendix.",
+            reclassified.len()
+        );
+    }
+
+    if false {
+        let mut out_graph_path = out_path.clone();
+        out_graph_path.set_extension("png");
+        plot_monthly_usage(&out_graph_path, &transactions, &stats, &config)?;
+        if !files::is_dry_run() {
+            println!(
+                "Monthly usage chart saved in `{}`.",
+                out_graph_path.display()
+            );
+        }
+    }
+
+    if files::is_read_only() {
+        diagnostics::info(
+            "read-only",
+            None,
+            String::from("Read-only mode: the report was not written."),
+        );
+    } else {
+        let mut out_tex_path = out_path.clone();
+        out_tex_path.set_extension("tex");
+        write_tex_stats(
+            &out_tex_path,
+            &stats,
+            &source_name,
+            &fingerprints,
+            &filters,
+            &config,
+            &reclassified,
+        )?;
+        if !files::is_dry_run() {
+            println!("Detailed report saved in `{}`.", out_tex_path.display());
+        }
+        if args.archive {
+            let dir = archive::month_dir(&out_path, &config);
+            if let Err(e) = files::create_dir_all(&dir) {
+                return Err(diagnostics::error(
+                    "io-write",
+                    Some(Location::file(&dir.display().to_string())),
+                    format!("Could not create directory: {}", e),
+                ));
+            }
+            write_tex_stats(
+                &dir.join("report.tex"),
+                &stats,
+                &source_name,
+                &fingerprints,
+                &filters,
+                &config,
+                &reclassified,
+            )?;
+            archive::store(&dir, &stats, &source_name, &fingerprints, &config)?;
+            if !files::is_dry_run() {
+                println!("Snapshot archived in `{}`.", dir.display());
+            }
+        }
+    }
+
+    let warnings = diagnostics::take()
+        .into_iter()
+        .filter(|x| x.severity == Severity::Warning)
+        .count();
+    if warnings > 0 {
+        diagnostics::info(
+            "warnings-summary",
+            None,
+            format!("Finished with {} warning(s), see above.", warnings),
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,142 @@
+use chrono::{Datelike, NaiveDate};
+
+// How reports write numbers and dates. Only the formatting changes: the text
+// of the report stays English.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Locale {
+    #[default]
+    English,
+    German,
+    French,
+    Italian,
+}
+
+const MONTHS: [[&str; 12]; 4] = [
+    [
+        "January",
+        "February",
+        "March",
+        "April",
+        "May",
+        "June",
+        "July",
+        "August",
+        "September",
+        "October",
+        "November",
+        "December",
+    ],
+    [
+        "Januar",
+        "Februar",
+        "März",
+        "April",
+        "Mai",
+        "Juni",
+        "Juli",
+        "August",
+        "September",
+        "Oktober",
+        "November",
+        "Dezember",
+    ],
+    [
+        "janvier",
+        "février",
+        "mars",
+        "avril",
+        "mai",
+        "juin",
+        "juillet",
+        "août",
+        "septembre",
+        "octobre",
+        "novembre",
+        "décembre",
+    ],
+    [
+        "gennaio",
+        "febbraio",
+        "marzo",
+        "aprile",
+        "maggio",
+        "giugno",
+        "luglio",
+        "agosto",
+        "settembre",
+        "ottobre",
+        "novembre",
+        "dicembre",
+    ],
+];
+
+impl Locale {
+    fn decimal(self) -> char {
+        match self {
+            Self::English => '.',
+            _ => ',',
+        }
+    }
+
+    // `x` with `decimals` digits after the decimal separator.
+    pub fn number(self, x: f64, decimals: usize) -> String {
+        format!("{:.*}", decimals, x).replace('.', &self.decimal().to_string())
+    }
+
+    // Like `number`, with a `+` in front of positive numbers.
+    pub fn signed(self, x: f64, decimals: usize) -> String {
+        format!("{:+.*}", decimals, x).replace('.', &self.decimal().to_string())
+    }
+
+    pub fn month(self, month: u32) -> &'static str {
+        MONTHS[self as usize][month as usize - 1]
+    }
+
+    pub fn month_year(self, date: NaiveDate) -> String {
+        format!("{} {}", self.month(date.month()), date.year())
+    }
+
+    pub fn date(self, date: NaiveDate) -> String {
+        match self {
+            Self::German => date.format("%d.%m.%Y").to_string(),
+            _ => date.format("%d/%m/%Y").to_string(),
+        }
+    }
+
+    // Two digits for the year, for tight chart labels.
+    pub fn short_date(self, date: NaiveDate) -> String {
+        match self {
+            Self::German => date.format("%d.%m.%y").to_string(),
+            _ => date.format("%d/%m/%y").to_string(),
+        }
+    }
+
+    // With the month's name, as under the report's title.
+    pub fn long_date(self, date: NaiveDate) -> String {
+        let month = self.month(date.month());
+        match self {
+            Self::English => format!("{} {:02}, {}", month, date.day(), date.year()),
+            Self::German => format!("{}. {} {}", date.day(), month, date.year()),
+            _ => format!("{} {} {}", date.day(), month, date.year()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_numbers_and_dates() {
+        let date = NaiveDate::from_ymd_opt(2026, 3, 5).unwrap();
+        assert_eq!(Locale::English.number(1234.5, 2), "1234.50");
+        assert_eq!(Locale::German.number(-0.125, 1), "-0,1");
+        assert_eq!(Locale::French.signed(3.0, 2), "+3,00");
+        assert_eq!(Locale::English.date(date), "05/03/2026");
+        assert_eq!(Locale::German.date(date), "05.03.2026");
+        assert_eq!(Locale::Italian.short_date(date), "05/03/26");
+        assert_eq!(Locale::English.long_date(date), "March 05, 2026");
+        assert_eq!(Locale::German.long_date(date), "5. März 2026");
+        assert_eq!(Locale::French.month_year(date), "mars 2026");
+    }
+}
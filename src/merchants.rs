@@ -0,0 +1,52 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+
+use crate::Transaction;
+
+// Where money goes most often, as opposed to where most of it goes. The note
+// stands in for the merchant.
+#[derive(Debug)]
+pub struct Merchant {
+    pub name: String,
+    pub visits: u64,
+    pub total: i64,
+    pub first_seen: NaiveDate,
+    pub last_seen: NaiveDate,
+}
+
+impl Merchant {
+    pub fn average_ticket(&self) -> f64 {
+        self.total as f64 / 100.0 / self.visits as f64
+    }
+}
+
+// Most visited first. Refunds and transactions without a note are left out.
+pub fn merchants(transactions: &[Transaction]) -> Vec<Merchant> {
+    let mut merchants: HashMap<&str, Merchant> = HashMap::new();
+    for t in transactions
+        .iter()
+        .filter(|t| t.value > 0 && !t.note.is_empty())
+    {
+        let merchant = merchants.entry(&t.note).or_insert_with(|| Merchant {
+            name: t.note.clone(),
+            visits: 0,
+            total: 0,
+            first_seen: t.date,
+            last_seen: t.date,
+        });
+        merchant.visits += 1;
+        merchant.total += t.value;
+        merchant.first_seen = merchant.first_seen.min(t.date);
+        merchant.last_seen = merchant.last_seen.max(t.date);
+    }
+
+    let mut merchants = merchants.into_values().collect::<Vec<_>>();
+    merchants.sort_by(|x, y| {
+        y.visits
+            .cmp(&x.visits)
+            .then(y.total.cmp(&x.total))
+            .then(x.name.cmp(&y.name))
+    });
+    merchants
+}
@@ -0,0 +1,207 @@
+use std::fmt;
+
+use crate::{Category, Stats};
+
+// A user defined metric, e.g. `Restaurants / (Restaurants + Grocery)`.
+// Names stand for what was spent in that category (written as in the data
+// file, quoted with `'...'` when they contain spaces or symbols) and `Total`
+// for all spending in the period.
+#[derive(Clone, Debug)]
+pub enum Expr {
+    Number(f64),
+    Category(Category),
+    Total,
+    Neg(Box<Expr>),
+    Binary(Box<Expr>, char, Box<Expr>),
+}
+
+impl Expr {
+    fn precedence(&self) -> u8 {
+        match self {
+            Self::Binary(_, '+' | '-', _) => 1,
+            Self::Binary(..) => 2,
+            _ => 3,
+        }
+    }
+}
+
+// Parenthesized only where needed, so it reads like what was written.
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Number(n) => write!(f, "{}", n),
+            Self::Category(Category::Miscellaneous(name)) => write!(f, "'{}'", name),
+            Self::Category(c) => write!(f, "{}", c),
+            Self::Total => write!(f, "Total"),
+            Self::Neg(x) if x.precedence() < 3 => write!(f, "-({})", x),
+            Self::Neg(x) => write!(f, "-{}", x),
+            Self::Binary(lhs, op, rhs) => {
+                let p = self.precedence();
+                if lhs.precedence() < p {
+                    write!(f, "({})", lhs)?;
+                } else {
+                    write!(f, "{}", lhs)?;
+                }
+                write!(f, " {} ", op)?;
+                // `a - (b - c)` and `a / (b * c)` need the parentheses.
+                if rhs.precedence() < p || (rhs.precedence() == p && matches!(op, '-' | '/')) {
+                    write!(f, "({})", rhs)
+                } else {
+                    write!(f, "{}", rhs)
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Name(String),
+    Op(char),
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut chars = s.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            ' ' | '\t' => {
+                chars.next();
+            }
+            '+' | '-' | '*' | '/' | '(' | ')' => {
+                tokens.push(Token::Op(c));
+                chars.next();
+            }
+            '\'' => {
+                chars.next();
+                let name = chars
+                    .by_ref()
+                    .take_while(|c| *c != '\'')
+                    .collect::<String>();
+                tokens.push(Token::Name(name));
+            }
+            _ if c.is_ascii_digit() || c == '.' => {
+                let mut number = String::new();
+                while let Some(&c) = chars.peek().filter(|c| c.is_ascii_digit() || **c == '.') {
+                    number.push(c);
+                    chars.next();
+                }
+                let n = number
+                    .parse::<f64>()
+                    .map_err(|_| format!("invalid number `{}`", number))?;
+                tokens.push(Token::Number(n));
+            }
+            _ if c.is_alphabetic() || c == '_' => {
+                let mut name = String::new();
+                while let Some(&c) = chars.peek().filter(|c| c.is_alphanumeric() || **c == '_') {
+                    name.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Name(name));
+            }
+            _ => return Err(format!("unexpected `{}`", c)),
+        }
+    }
+    Ok(tokens)
+}
+
+// Recursive descent over `sum := product (('+' | '-') product)*`,
+// `product := unary (('*' | '/') unary)*`, `unary := '-' unary | atom`.
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek_op(&self) -> Option<char> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Op(c)) => Some(*c),
+            _ => None,
+        }
+    }
+
+    fn sum(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.product()?;
+        while let Some(op @ ('+' | '-')) = self.peek_op() {
+            self.pos += 1;
+            lhs = Expr::Binary(Box::new(lhs), op, Box::new(self.product()?));
+        }
+        Ok(lhs)
+    }
+
+    fn product(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.unary()?;
+        while let Some(op @ ('*' | '/')) = self.peek_op() {
+            self.pos += 1;
+            lhs = Expr::Binary(Box::new(lhs), op, Box::new(self.unary()?));
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.peek_op() == Some('-') {
+            self.pos += 1;
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        self.atom()
+    }
+
+    fn atom(&mut self) -> Result<Expr, String> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        match token {
+            Some(Token::Number(n)) => Ok(Expr::Number(n)),
+            Some(Token::Name(name)) if name == "Total" => Ok(Expr::Total),
+            Some(Token::Name(name)) => Ok(Expr::Category(Category::from(name.as_str()))),
+            Some(Token::Op('(')) => {
+                let inner = self.sum()?;
+                if self.peek_op() != Some(')') {
+                    return Err(String::from("missing `)`"));
+                }
+                self.pos += 1;
+                Ok(inner)
+            }
+            Some(Token::Op(c)) => Err(format!("unexpected `{}`", c)),
+            None => Err(String::from("unexpected end of expression")),
+        }
+    }
+}
+
+pub fn parse(s: &str) -> Result<Expr, String> {
+    let mut parser = Parser {
+        tokens: tokenize(s)?,
+        pos: 0,
+    };
+    let expr = parser.sum()?;
+    if parser.pos < parser.tokens.len() {
+        return Err(String::from("unexpected input after the expression"));
+    }
+    Ok(expr)
+}
+
+// `None` when the metric is undefined for the period (division by zero).
+pub fn eval(expr: &Expr, stats: &Stats) -> Option<f64> {
+    match expr {
+        Expr::Number(n) => Some(*n),
+        Expr::Category(category) => Some(
+            stats
+                .by_category
+                .iter()
+                .find(|(c, _)| c == category)
+                .map_or(0.0, |(_, v)| *v as f64 / 100.0),
+        ),
+        Expr::Total => Some(stats.get_total()),
+        Expr::Neg(x) => eval(x, stats).map(|x| -x),
+        Expr::Binary(lhs, op, rhs) => {
+            let (lhs, rhs) = (eval(lhs, stats)?, eval(rhs, stats)?);
+            match op {
+                '+' => Some(lhs + rhs),
+                '-' => Some(lhs - rhs),
+                '*' => Some(lhs * rhs),
+                _ if rhs == 0.0 => None,
+                _ => Some(lhs / rhs),
+            }
+        }
+    }
+}
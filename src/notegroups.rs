@@ -0,0 +1,132 @@
+use chrono::{Datelike, NaiveDate};
+
+use crate::{StatsCollection, Transaction};
+
+// Case-insensitive. A pattern without `*` matches anywhere in the note, like
+// `[rules]`; with `*` it has to match the whole note, `*` standing for any text.
+pub fn matches(pattern: &str, note: &str) -> bool {
+    let pattern = pattern.to_lowercase();
+    let note = note.to_lowercase();
+    if !pattern.contains('*') {
+        return note.contains(&pattern);
+    }
+
+    let parts = pattern.split('*').collect::<Vec<_>>();
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    let Some(mut rest) = note.strip_prefix(first).and_then(|x| x.strip_suffix(last)) else {
+        return false;
+    };
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+pub fn in_group(patterns: &[String], t: &Transaction) -> bool {
+    patterns.iter().any(|p| matches(p, &t.note))
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Month {
+    pub total: i64,
+    pub count: usize,
+}
+
+// The group's spending in every month of `stats.monthly`, in the same order.
+pub fn monthly(patterns: &[String], stats: &StatsCollection) -> Vec<((i32, u32), Month)> {
+    stats
+        .monthly
+        .iter()
+        .map(|(k, monthly)| {
+            let mut month = Month::default();
+            for t in monthly
+                .transactions
+                .iter()
+                .filter(|t| in_group(patterns, t))
+            {
+                month.total += t.value;
+                month.count += 1;
+            }
+            (*k, month)
+        })
+        .collect()
+}
+
+pub struct Kpi {
+    pub per_month: f64,              // average over the last 12 complete months
+    pub per_occurrence: Option<f64>, // over the same months
+    pub occurrences_per_month: f64,
+    pub last_3_months: f64, // average per month
+    pub previous_3_months: f64,
+}
+
+impl Kpi {
+    // Change of the last three complete months against the three before, in percent.
+    pub fn trend(&self) -> Option<f64> {
+        (self.previous_3_months != 0.0)
+            .then(|| (self.last_3_months / self.previous_3_months - 1.0) * 100.0)
+    }
+}
+
+pub fn kpi(months: &[((i32, u32), Month)], today: NaiveDate) -> Kpi {
+    // Months with no data at all are missing from `months`; they count as zero.
+    let complete = (1..=12)
+        .map(|back| {
+            let first = today.with_day(1).unwrap() - chrono::Months::new(back);
+            months
+                .iter()
+                .find(|((y, m), _)| *y == first.year() && *m == first.month())
+                .map(|(_, x)| x.clone())
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>(); // most recent first
+    let total = |xs: &[Month]| xs.iter().map(|x| x.total).sum::<i64>() as f64 / 100.0;
+    let count = complete.iter().map(|x| x.count).sum::<usize>();
+    Kpi {
+        per_month: total(&complete) / 12.0,
+        per_occurrence: (count > 0).then(|| total(&complete) / count as f64),
+        occurrences_per_month: count as f64 / 12.0,
+        last_3_months: total(&complete[..3]) / 3.0,
+        previous_3_months: total(&complete[3..6]) / 3.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn without_star_matches_anywhere() {
+        assert!(matches("lunch", "Lunch at Coop"));
+        assert!(matches("coop", "Lunch at COOP"));
+        assert!(!matches("dinner", "Lunch at Coop"));
+    }
+
+    #[test]
+    fn with_star_matches_the_whole_note() {
+        assert!(matches("lunch*", "Lunch at Coop"));
+        assert!(matches("*coop", "Lunch at Coop"));
+        assert!(matches("lunch*coop", "Lunch at Coop"));
+        assert!(matches("l*at*p", "Lunch at Coop"));
+        assert!(matches("*", ""));
+        assert!(!matches("*lunch", "Lunch at Coop"));
+        assert!(!matches("coop*", "Lunch at Coop"));
+        assert!(!matches("l*x*p", "Lunch at Coop"));
+        // The start and the end can't share characters.
+        assert!(!matches("ab*ba", "aba"));
+    }
+
+    #[test]
+    fn non_ascii_notes() {
+        assert!(!matches("*é", "5€"));
+        assert!(matches("*€", "5€"));
+        assert!(matches("café*", "Café crème"));
+        assert!(matches("*crème", "Café crème"));
+        assert!(matches("é*è", "écrè"));
+        assert!(!matches("é*è", "è"));
+        assert!(matches("CAFÉ", "un café"));
+    }
+}
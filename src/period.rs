@@ -0,0 +1,242 @@
+use chrono::{Datelike, Months, NaiveDate, TimeDelta};
+
+// The `day` of the month of `date`, or its last day if the month is shorter.
+fn clamped_day(year: i32, month: u32, day: u32) -> NaiveDate {
+    (1..=day)
+        .rev()
+        .find_map(|d| NaiveDate::from_ymd_opt(year, month, d))
+        .unwrap()
+}
+
+// First day of the credit card statement period containing `date`, for
+// periods running from `day` of one month to the day before `day` of the next.
+pub fn statement_period_start(date: NaiveDate, day: u32) -> NaiveDate {
+    let this_month = clamped_day(date.year(), date.month(), day);
+    if date >= this_month {
+        this_month
+    } else {
+        let previous = date.with_day(1).unwrap() - Months::new(1);
+        clamped_day(previous.year(), previous.month(), day)
+    }
+}
+
+// Last day of the statement period starting on `start`.
+pub fn statement_period_end(start: NaiveDate, day: u32) -> NaiveDate {
+    let next = start.with_day(1).unwrap() + Months::new(1);
+    clamped_day(next.year(), next.month(), day) - TimeDelta::days(1)
+}
+
+fn month_end(year: i32, month: u32) -> NaiveDate {
+    clamped_day(year, month, 31)
+}
+
+// First and last day of a period written the way people say it, relative to
+// `today`: `today`, `yesterday`, `this week|month|quarter|year`, `last
+// week|month|quarter|year`, `ytd`, `mtd`, `last 90 days` (also weeks, months),
+// `2024`, `2024-05`, `2024-q2`, `q2` (of this year), or two dates as
+// `1/1/2024..31/3/2024`. Periods may run past today.
+pub fn parse(s: &str, today: NaiveDate) -> Option<(NaiveDate, NaiveDate)> {
+    let s = s
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+    let quarter = |year: i32, q: u32| {
+        (1..=4).contains(&q).then(|| {
+            let first = NaiveDate::from_ymd_opt(year, q * 3 - 2, 1).unwrap();
+            (first, month_end(year, q * 3))
+        })
+    };
+    let this_quarter = (today.month() - 1) / 3 + 1;
+    let this_month = today.with_day(1).unwrap();
+    let monday = today - TimeDelta::days(today.weekday().num_days_from_monday() as i64);
+
+    match s.as_str() {
+        "today" => return Some((today, today)),
+        "yesterday" => {
+            let day = today - TimeDelta::days(1);
+            return Some((day, day));
+        }
+        "this week" => return Some((monday, monday + TimeDelta::days(6))),
+        "last week" => return Some((monday - TimeDelta::days(7), monday - TimeDelta::days(1))),
+        "this month" => return Some((this_month, month_end(today.year(), today.month()))),
+        "last month" => {
+            let first = this_month - Months::new(1);
+            return Some((first, this_month - TimeDelta::days(1)));
+        }
+        "this quarter" => return quarter(today.year(), this_quarter),
+        "last quarter" => {
+            return match this_quarter {
+                1 => quarter(today.year() - 1, 4),
+                q => quarter(today.year(), q - 1),
+            }
+        }
+        "this year" => {
+            let first = NaiveDate::from_ymd_opt(today.year(), 1, 1)?;
+            return Some((first, month_end(today.year(), 12)));
+        }
+        "last year" => {
+            let first = NaiveDate::from_ymd_opt(today.year() - 1, 1, 1)?;
+            return Some((first, month_end(today.year() - 1, 12)));
+        }
+        "ytd" => return Some((NaiveDate::from_ymd_opt(today.year(), 1, 1)?, today)),
+        "mtd" => return Some((this_month, today)),
+        _ => {}
+    }
+
+    if let Some(rest) = s.strip_prefix("last ") {
+        let (n, unit) = rest.split_once(' ')?;
+        let n = n.parse::<u32>().ok().filter(|x| *x > 0)?;
+        // `n` days up to and including today.
+        let first = match unit.trim_end_matches('s') {
+            "day" => today.checked_sub_signed(TimeDelta::days(n as i64 - 1))?,
+            "week" => today.checked_sub_signed(TimeDelta::days(7 * n as i64 - 1))?,
+            "month" => today.checked_sub_months(Months::new(n))? + TimeDelta::days(1),
+            _ => return None,
+        };
+        return Some((first, today));
+    }
+
+    if let Some((from, to)) = s.split_once("..") {
+        let date = |x: &str| NaiveDate::parse_from_str(x.trim(), "%d/%m/%Y").ok();
+        let (from, to) = (date(from)?, date(to)?);
+        return (from <= to).then_some((from, to));
+    }
+
+    if let Some(q) = s.strip_prefix('q') {
+        return quarter(today.year(), q.parse().ok()?);
+    }
+    let (year, rest) = s.split_once('-').unwrap_or((&s, ""));
+    let year = year
+        .parse::<i32>()
+        .ok()
+        .filter(|x| (1000..=9999).contains(x))?;
+    if rest.is_empty() {
+        return Some((NaiveDate::from_ymd_opt(year, 1, 1)?, month_end(year, 12)));
+    }
+    if let Some(q) = rest.strip_prefix('q') {
+        return quarter(year, q.parse().ok()?);
+    }
+    let month = rest.parse::<u32>().ok().filter(|x| (1..=12).contains(x))?;
+    Some((
+        NaiveDate::from_ymd_opt(year, month, 1)?,
+        month_end(year, month),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn period(s: &str) -> Option<(NaiveDate, NaiveDate)> {
+        // A Wednesday in the fourth quarter.
+        parse(s, date(2026, 10, 14))
+    }
+
+    #[test]
+    fn named_periods() {
+        let today = date(2026, 10, 14);
+        assert_eq!(period("today"), Some((today, today)));
+        let yesterday = date(2026, 10, 13);
+        assert_eq!(period("Yesterday"), Some((yesterday, yesterday)));
+        assert_eq!(
+            period("this week"),
+            Some((date(2026, 10, 12), date(2026, 10, 18)))
+        );
+        assert_eq!(
+            period("last  week"),
+            Some((date(2026, 10, 5), date(2026, 10, 11)))
+        );
+        assert_eq!(
+            period("this month"),
+            Some((date(2026, 10, 1), date(2026, 10, 31)))
+        );
+        assert_eq!(
+            period("last month"),
+            Some((date(2026, 9, 1), date(2026, 9, 30)))
+        );
+        assert_eq!(
+            period("this quarter"),
+            Some((date(2026, 10, 1), date(2026, 12, 31)))
+        );
+        assert_eq!(
+            period("last quarter"),
+            Some((date(2026, 7, 1), date(2026, 9, 30)))
+        );
+        assert_eq!(
+            period("this year"),
+            Some((date(2026, 1, 1), date(2026, 12, 31)))
+        );
+        assert_eq!(
+            period("last year"),
+            Some((date(2025, 1, 1), date(2025, 12, 31)))
+        );
+        assert_eq!(period("ytd"), Some((date(2026, 1, 1), today)));
+        assert_eq!(period("mtd"), Some((date(2026, 10, 1), today)));
+    }
+
+    #[test]
+    fn last_quarter_of_the_previous_year() {
+        assert_eq!(
+            parse("last quarter", date(2026, 2, 1)),
+            Some((date(2025, 10, 1), date(2025, 12, 31)))
+        );
+    }
+
+    #[test]
+    fn last_n_units() {
+        let today = date(2026, 10, 14);
+        assert_eq!(period("last 1 day"), Some((today, today)));
+        assert_eq!(period("last 90 days"), Some((date(2026, 7, 17), today)));
+        assert_eq!(period("last 2 weeks"), Some((date(2026, 10, 1), today)));
+        assert_eq!(period("last 3 months"), Some((date(2026, 7, 15), today)));
+        assert_eq!(period("last 0 days"), None);
+        assert_eq!(period("last 3 fortnights"), None);
+        assert_eq!(period("last -3 days"), None);
+    }
+
+    #[test]
+    fn last_n_units_out_of_range() {
+        assert_eq!(period("last 99999999 days"), None);
+        assert_eq!(period("last 99999999 weeks"), None);
+        assert_eq!(period("last 4000000000 months"), None);
+    }
+
+    #[test]
+    fn years_months_and_quarters() {
+        assert_eq!(period("2024"), Some((date(2024, 1, 1), date(2024, 12, 31))));
+        assert_eq!(
+            period("2024-02"),
+            Some((date(2024, 2, 1), date(2024, 2, 29)))
+        );
+        assert_eq!(
+            period("2024-Q2"),
+            Some((date(2024, 4, 1), date(2024, 6, 30)))
+        );
+        assert_eq!(period("q1"), Some((date(2026, 1, 1), date(2026, 3, 31))));
+        assert_eq!(period("q0"), None);
+        assert_eq!(period("q5"), None);
+        assert_eq!(period("2024-q0"), None);
+        assert_eq!(period("2024-q5"), None);
+        assert_eq!(period("2024-13"), None);
+        assert_eq!(period("24"), None);
+    }
+
+    #[test]
+    fn date_ranges() {
+        assert_eq!(
+            period("1/1/2024..31/3/2024"),
+            Some((date(2024, 1, 1), date(2024, 3, 31)))
+        );
+        assert_eq!(
+            period("5/5/2024 .. 5/5/2024"),
+            Some((date(2024, 5, 5), date(2024, 5, 5)))
+        );
+        assert_eq!(period("31/3/2024..1/1/2024"), None);
+        assert_eq!(period("1/1/2024..31/2/2024"), None);
+    }
+}
@@ -0,0 +1,75 @@
+use crate::{config::Config, Category, Stats};
+
+// Spending by category (rows) and payment method (columns), both in the order
+// of their totals in `stats` (categories in `category_order` if there is one).
+pub struct Pivot {
+    pub methods: Vec<String>,
+    pub rows: Vec<(Category, Vec<i64>)>,
+}
+
+impl Pivot {
+    pub fn method_total(&self, i: usize) -> i64 {
+        self.rows.iter().map(|(_, values)| values[i]).sum()
+    }
+}
+
+pub fn pivot(stats: &Stats, config: &Config) -> Pivot {
+    let methods = stats
+        .by_payment_method
+        .iter()
+        .map(|(m, _)| m.clone())
+        .collect::<Vec<_>>();
+    let mut rows = stats
+        .by_category
+        .iter()
+        .map(|(c, _)| (c.clone(), vec![0; methods.len()]))
+        .collect::<Vec<_>>();
+    config.sort_categories(&mut rows);
+    for t in stats.transactions.iter() {
+        let (Some(row), Some(column)) = (
+            rows.iter().position(|(c, _)| *c == t.category),
+            methods.iter().position(|m| *m == t.payment_method),
+        ) else {
+            continue;
+        };
+        rows[row].1[column] += t.value;
+    }
+    Pivot { methods, rows }
+}
+
+// One block of rows per year, with a `Year` column so it can be filtered.
+pub fn pivot_csv(yearly: &[(i32, Stats)], config: &Config) -> String {
+    let format = &config.csv;
+    let delimiter = format.delimiter.to_string();
+    let mut methods = yearly
+        .iter()
+        .flat_map(|(_, x)| x.by_payment_method.iter().map(|(m, _)| m.clone()))
+        .collect::<Vec<_>>();
+    methods.sort();
+    methods.dedup();
+
+    let mut header = vec![String::from("Year"), String::from("Category")];
+    header.extend(methods.iter().cloned());
+    header.push(String::from("Total"));
+    let mut out = header.join(&delimiter);
+    out.push('\n');
+    for (year, stats) in yearly {
+        let pivot = pivot(stats, config);
+        for (category, values) in pivot.rows.iter() {
+            let mut line = vec![year.to_string(), category.to_string()];
+            line.extend(methods.iter().map(|m| {
+                let value = pivot
+                    .methods
+                    .iter()
+                    .position(|x| x == m)
+                    .map(|i| values[i])
+                    .unwrap_or_default();
+                format.format_amount(value)
+            }));
+            line.push(format.format_amount(values.iter().sum()));
+            out.push_str(&line.join(&delimiter));
+            out.push('\n');
+        }
+    }
+    out
+}
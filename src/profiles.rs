@@ -0,0 +1,93 @@
+use crate::{rounding, Category, Stats};
+
+// A reference way of splitting spending into buckets, each with a target
+// share in percent, e.g. the 50/30/20 rule.
+#[derive(Clone, Debug)]
+pub struct Profile {
+    pub name: String,
+    pub buckets: Vec<(String, f64, Vec<Category>)>, // name, target share, categories
+}
+
+pub fn builtin(name: &str) -> Option<Profile> {
+    use Category::*;
+    let buckets = match name {
+        "50/30/20" => vec![
+            (
+                "Needs",
+                50.0,
+                vec![
+                    Grocery,
+                    Rent,
+                    Utilities,
+                    Healthcare,
+                    Transportation,
+                    Taxes,
+                    Education,
+                ],
+            ),
+            (
+                "Wants",
+                30.0,
+                vec![Restaurants, Entrateinment, Hobby, Shopping, Travel, Charity],
+            ),
+            ("Savings", 20.0, vec![Savings]),
+        ],
+        _ => return None,
+    };
+    Some(Profile {
+        name: String::from(name),
+        buckets: buckets
+            .into_iter()
+            .map(|(bucket, share, categories)| (String::from(bucket), share, categories))
+            .collect(),
+    })
+}
+
+// One row of the comparison.
+#[derive(Debug)]
+pub struct Deviation {
+    pub bucket: String,
+    pub target: f64,
+    pub actual: f64,
+}
+
+impl Deviation {
+    // In percentage points, positive when more went into the bucket than the
+    // profile suggests.
+    pub fn points(&self) -> f64 {
+        self.actual - self.target
+    }
+}
+
+// Categories outside every bucket end up in an `Other` row without a target.
+pub fn compare(profile: &Profile, stats: &Stats, precision: usize) -> Vec<Deviation> {
+    let mut values = vec![0; profile.buckets.len() + 1];
+    for (category, value) in stats.by_category.iter() {
+        let idx = profile
+            .buckets
+            .iter()
+            .position(|(_, _, categories)| categories.contains(category))
+            .unwrap_or(profile.buckets.len());
+        values[idx] += value;
+    }
+    let actual = rounding::percentages(&values, stats.total, precision);
+
+    let mut deviations = profile
+        .buckets
+        .iter()
+        .zip(actual.iter())
+        .map(|((bucket, target, _), actual)| Deviation {
+            bucket: bucket.clone(),
+            target: *target,
+            actual: *actual,
+        })
+        .collect::<Vec<_>>();
+    if values[profile.buckets.len()] != 0 {
+        deviations.push(Deviation {
+            bucket: String::from("Other"),
+            target: 0.0,
+            actual: actual[profile.buckets.len()],
+        });
+    }
+    deviations
+}
@@ -0,0 +1,130 @@
+use std::path::Path;
+
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+use crate::{
+    config::Config,
+    diagnostics::{self, Diagnostic, Location},
+    escape_string_for_tex, files, html, render_tex_stats, Locale, StatsCollection,
+};
+
+// The parts of the TeX report, in the order they are written. Sections
+// without anything to show (no uncategorized transactions, no merchants, ...)
+// are left out even when selected.
+#[derive(Clone, Copy, Debug, EnumIter, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Section {
+    Overview,
+    Uncategorized,
+    Profile,
+    CategoryTrends,
+    Yearly,
+    Monthly,
+    StatementPeriods,
+    Allowance,
+    Sources,
+    Metrics,
+    ShareTargets,
+    NoteGroups,
+    SpecialDates,
+    Merchants,
+    Keywords,
+    RoundUps,
+    Durables,
+    // The appendix.
+    Transactions,
+    Reclassified,
+    FullNotes,
+}
+
+// Renders the TeX report of the command line tool for programs that gather
+// transactions themselves:
+//
+//     let stats = StatsCollection::new(&transactions);
+//     let tex = ReportBuilder::new(&stats).title("Joint account").render_tex()?;
+//
+// Settings are the defaults of a missing config file, except for the ones
+// set here.
+pub struct ReportBuilder<'a> {
+    stats: &'a StatsCollection,
+    title: String,
+    config: Config,
+    sections: Vec<Section>,
+}
+
+impl<'a> ReportBuilder<'a> {
+    pub fn new(stats: &'a StatsCollection) -> Self {
+        Self {
+            stats,
+            title: String::from(env!("CARGO_PKG_NAME")),
+            config: Config::default(),
+            sections: Section::iter().collect(),
+        }
+    }
+
+    // Shown after "Spending report from", like the data file name.
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = String::from(title);
+        self
+    }
+
+    // Only shown, amounts are never converted.
+    pub fn currency(mut self, currency: &str) -> Self {
+        self.config.currency = Some(String::from(currency));
+        self
+    }
+
+    // Numbers and dates as written in `locale` (English by default).
+    pub fn locale(mut self, locale: Locale) -> Self {
+        self.config.locale = locale;
+        self
+    }
+
+    // Only these sections, still in the report's order (all by default).
+    // Without `Section::Transactions` or `Section::FullNotes`, nothing links
+    // to them.
+    pub fn sections(mut self, sections: &[Section]) -> Self {
+        self.sections = sections.to_vec();
+        self
+    }
+
+    // Without data files, the footer and `verify` have no hashes to show.
+    pub fn render_tex(&self) -> Result<String, Diagnostic> {
+        let tex = render_tex_stats(
+            self.stats,
+            &escape_string_for_tex(&self.title),
+            &[],
+            &[],
+            &self.config,
+            &[],
+            &self.sections,
+        );
+        String::from_utf8(tex).map_err(|e| {
+            diagnostics::error(
+                "tex-encoding",
+                None,
+                format!("The report is not valid UTF-8: {}", e),
+            )
+        })
+    }
+
+    // The same report as a standalone HTML page, with the sections that are
+    // tables and lists: `Overview` (without its chart), `Uncategorized`,
+    // `Yearly`, `Monthly`, `Transactions` and `FullNotes`. The rest are only
+    // in the TeX report.
+    pub fn render_html(&self) -> String {
+        html::render_html_stats(self.stats, &self.title, &self.config, &self.sections)
+    }
+
+    // `render_tex` into `path`, replacing it in one go.
+    pub fn write_tex(&self, path: &Path) -> Result<(), Diagnostic> {
+        let tex = self.render_tex()?;
+        files::write_atomically(path, tex.as_bytes()).map_err(|e| {
+            diagnostics::error(
+                "io-write",
+                Some(Location::file(&path.display().to_string())),
+                format!("Could not write file: {}", e),
+            )
+        })
+    }
+}
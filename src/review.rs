@@ -0,0 +1,218 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    io::{self, BufRead, Write},
+    path::PathBuf,
+};
+
+use crate::{
+    categorize,
+    config::Config,
+    diagnostics::{self, Diagnostic, Location},
+    files, hash, Category, Transaction,
+};
+
+// How many times the category's median amount makes a transaction stand out.
+const ANOMALY_FACTOR: f64 = 3.0;
+// Below this many transactions a category has no meaningful median.
+const ANOMALY_MIN_SAMPLES: usize = 5;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum State {
+    Pending,
+    Reviewed,
+}
+
+// The queue is stored as `id;pending|reviewed;reason` lines. Reviewed entries
+// stay, so that they are not queued again on the next run.
+struct Queue {
+    path: PathBuf,
+    entries: Vec<(String, State, String)>,
+}
+
+// One file per set of inputs, named after a hash of their canonical paths.
+fn queue_path(state_key: &str) -> PathBuf {
+    files::state_dir().join(format!(
+        "review-{:016x}.txt",
+        hash::fnv1a(state_key.as_bytes())
+    ))
+}
+
+impl Queue {
+    fn load(state_key: &str) -> Self {
+        let path = queue_path(state_key);
+        let entries = fs::read_to_string(&path)
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.splitn(3, ';');
+                let id = parts.next()?;
+                let state = match parts.next()? {
+                    "pending" => State::Pending,
+                    "reviewed" => State::Reviewed,
+                    _ => return None,
+                };
+                Some((String::from(id), state, String::from(parts.next()?)))
+            })
+            .collect();
+        Self { path, entries }
+    }
+
+    fn save(&self) -> Result<(), Diagnostic> {
+        let mut content = String::new();
+        for (id, state, reason) in self.entries.iter() {
+            let state = match state {
+                State::Pending => "pending",
+                State::Reviewed => "reviewed",
+            };
+            content.push_str(&format!("{};{};{}\n", id, state, reason));
+        }
+        let result = files::create_dir_all(&files::state_dir())
+            .and_then(|_| files::write_atomically(&self.path, content.as_bytes()));
+        if let Err(e) = result {
+            return Err(diagnostics::error(
+                "io-write",
+                Some(Location::file(&self.path.display().to_string())),
+                format!("Could not save review queue: {}", e),
+            ));
+        }
+        Ok(())
+    }
+
+    fn pending(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|x| x.1 == State::Pending)
+            .count()
+    }
+}
+
+fn median_by_category(transactions: &[Transaction]) -> HashMap<&Category, i64> {
+    let mut values: HashMap<&Category, Vec<i64>> = HashMap::new();
+    for t in transactions.iter().filter(|t| t.value > 0) {
+        values.entry(&t.category).or_default().push(t.value);
+    }
+    values
+        .into_iter()
+        .filter(|(_, xs)| xs.len() >= ANOMALY_MIN_SAMPLES)
+        .map(|(c, mut xs)| {
+            xs.sort();
+            (c, xs[xs.len() / 2])
+        })
+        .collect()
+}
+
+// Why a transaction deserves a second look, if it does.
+fn reason(t: &Transaction, threshold: i64, medians: &HashMap<&Category, i64>) -> Option<String> {
+    if categorize::is_uncategorized(&t.category) {
+        return Some(String::from("uncategorized"));
+    }
+    if t.value >= threshold {
+        return Some(format!("at least {:.2}", threshold as f64 / 100.0));
+    }
+    let median = *medians.get(&t.category)?;
+    if median > 0 && t.value as f64 > ANOMALY_FACTOR * median as f64 {
+        return Some(format!(
+            "{:.1}x the usual {} amount",
+            t.value as f64 / median as f64,
+            t.category
+        ));
+    }
+    None
+}
+
+// The saved queue with the newly flagged transactions added, in memory only.
+// Nothing is flagged unless `review_threshold` is configured.
+fn queue(transactions: &[Transaction], state_key: &str, config: &Config) -> Queue {
+    let mut queue = Queue::load(state_key);
+    let Some(threshold) = config.review_threshold else {
+        return queue;
+    };
+    let medians = median_by_category(transactions);
+    let queued = queue
+        .entries
+        .iter()
+        .map(|(id, _, _)| id.as_str())
+        .collect::<HashSet<_>>();
+    let flagged = transactions
+        .iter()
+        .filter(|t| !queued.contains(t.id.as_str()))
+        .filter_map(|t| {
+            Some((
+                t.id.clone(),
+                State::Pending,
+                reason(t, threshold, &medians)?,
+            ))
+        })
+        .collect::<Vec<_>>();
+    queue.entries.extend(flagged);
+    queue
+}
+
+// How many transactions `review` would go through. Only `review` itself
+// writes the queue, so a report leaves the review state alone.
+pub fn waiting(transactions: &[Transaction], state_key: &str, config: &Config) -> usize {
+    let ids = transactions
+        .iter()
+        .map(|t| t.id.as_str())
+        .collect::<HashSet<_>>();
+    queue(transactions, state_key, config)
+        .entries
+        .iter()
+        .filter(|(id, state, _)| *state == State::Pending && ids.contains(id.as_str()))
+        .count()
+}
+
+// Walks the pending entries: each one can be marked as reviewed, skipped for
+// now, or the walk stopped. Transactions no longer in the data are dropped.
+pub fn run(
+    transactions: &[Transaction],
+    state_key: &str,
+    config: &Config,
+) -> Result<(), Diagnostic> {
+    let by_id = transactions
+        .iter()
+        .map(|t| (t.id.as_str(), t))
+        .collect::<HashMap<_, _>>();
+    let mut queue = queue(transactions, state_key, config);
+    queue
+        .entries
+        .retain(|(id, _, _)| by_id.contains_key(id.as_str()));
+
+    let pending = queue.pending();
+    if pending == 0 {
+        println!("Nothing to review.");
+        return queue.save();
+    }
+
+    let mut lines = io::stdin().lock().lines();
+    let mut seen = 0;
+    for (id, state, reason) in queue.entries.iter_mut() {
+        if *state != State::Pending {
+            continue;
+        }
+        seen += 1;
+        let t = by_id[id.as_str()];
+        println!(
+            "[{}/{}] {} {:.2} {} ({}, {}): {}",
+            seen,
+            pending,
+            t.date.format("%d/%m/%Y"),
+            t.value as f64 / 100.0,
+            t.category,
+            t.payment_method,
+            t.note,
+            reason
+        );
+        print!("  [r]eviewed, [s]kip, [q]uit? ");
+        io::stdout().flush().unwrap();
+        match lines.next().and_then(|x| x.ok()).as_deref().map(str::trim) {
+            Some("r") => *state = State::Reviewed,
+            Some("s") | Some("") => {}
+            _ => break,
+        }
+    }
+    queue.save()?;
+    println!("{} transaction(s) left to review.", queue.pending());
+    Ok(())
+}
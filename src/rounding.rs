@@ -0,0 +1,72 @@
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum RoundingMode {
+    #[default]
+    HalfUp, // ties away from zero
+    Bankers, // ties to even
+}
+
+impl std::fmt::Display for RoundingMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::HalfUp => write!(f, "half-up"),
+            Self::Bankers => write!(f, "bankers"),
+        }
+    }
+}
+
+impl RoundingMode {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "half-up" => Some(Self::HalfUp),
+            "bankers" => Some(Self::Bankers),
+            _ => None,
+        }
+    }
+
+    pub fn round(&self, x: f64, precision: usize) -> f64 {
+        let scale = 10f64.powi(precision as i32);
+        // Products like 2.675 * 100 land just below the tie; snap away the
+        // float noise so the configured mode actually decides.
+        let scaled = (x * scale * 1e6).round() / 1e6;
+        let rounded = match self {
+            Self::HalfUp => scaled.round(),
+            Self::Bankers => scaled.round_ties_even(),
+        };
+        rounded / scale
+    }
+}
+
+// Shares of `total` in percent, rounded to `precision` decimals such that they
+// add up to exactly 100 (largest remainder method). Rounding each share on
+// its own can easily sum to 99.99 or 100.01.
+pub fn percentages(values: &[i64], total: i64, precision: usize) -> Vec<f64> {
+    if total == 0 {
+        return vec![0.0; values.len()];
+    }
+
+    let units = 100 * 10i64.pow(precision as u32);
+    let quotas = values
+        .iter()
+        .map(|v| *v as f64 / total as f64 * units as f64)
+        .collect::<Vec<_>>();
+    let mut allocated = quotas.iter().map(|q| q.floor() as i64).collect::<Vec<_>>();
+
+    let mut by_remainder = (0..values.len()).collect::<Vec<_>>();
+    by_remainder.sort_by(|a, b| {
+        let ra = quotas[*a] - quotas[*a].floor();
+        let rb = quotas[*b] - quotas[*b].floor();
+        rb.partial_cmp(&ra).unwrap()
+    });
+
+    let missing = units - allocated.iter().sum::<i64>();
+    if !by_remainder.is_empty() {
+        for i in 0..missing.max(0) as usize {
+            allocated[by_remainder[i % by_remainder.len()]] += 1;
+        }
+    }
+
+    allocated
+        .into_iter()
+        .map(|x| x as f64 / 10f64.powi(precision as i32))
+        .collect()
+}
@@ -0,0 +1,23 @@
+use crate::{Category, Stats, Transaction};
+
+// Whole units every payment is rounded up to, like the round-up savings of
+// banking apps: to the next franc, and to the next 5 francs.
+pub const STEPS: [i64; 2] = [1, 5];
+
+// What rounding `t` up to the next multiple of `step` units would put aside.
+// Refunds and what already goes to savings put nothing aside.
+fn round_up(t: &Transaction, step: i64) -> i64 {
+    if t.value <= 0 || t.category == Category::Savings {
+        return 0;
+    }
+    let step = step * 100;
+    (step - t.value % step) % step
+}
+
+// Put aside over the period of `stats`, for each of `STEPS`.
+pub fn saved(stats: &Stats) -> Vec<i64> {
+    STEPS
+        .iter()
+        .map(|step| stats.transactions.iter().map(|t| round_up(t, *step)).sum())
+        .collect()
+}
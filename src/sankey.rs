@@ -0,0 +1,199 @@
+use std::{collections::HashMap, io::Write};
+
+use crate::{config::Config, escape_string_for_tex, locale::Locale, note_for_tex, Links, Stats};
+
+const HEIGHT: f64 = 12.0; // cm taken by the total
+const NODE_WIDTH: f64 = 0.3;
+const COLUMNS: [f64; 3] = [0.0, 5.0, 10.0];
+const CATEGORY_GAP: f64 = 0.25;
+const NOTE_GAP: f64 = 0.1;
+const MIN_SLOT: f64 = 0.3; // room for a label, however small the flow
+const MAX_CATEGORIES: usize = 8;
+const NOTES_PER_CATEGORY: usize = 3;
+const COLORS: [&str; 9] = [
+    "blue", "red", "orange", "teal", "violet", "olive", "cyan", "magenta", "brown",
+];
+
+// A box of a column, from `top` down to `top + height` (y grows downwards).
+struct Node {
+    label: String, // already escaped for TeX
+    value: i64,
+    top: f64,
+    height: f64,
+}
+
+fn band(buf: &mut Vec<u8>, color: &str, x1: f64, y1: f64, x2: f64, y2: f64, height: f64) {
+    let middle = (x1 + x2) / 2.0;
+    writeln!(
+        buf,
+        "    \\fill[{0}, opacity=0.3] ({1:.2},{2:.2}) .. controls ({3:.2},{2:.2}) and ({3:.2},{4:.2}) .. ({5:.2},{4:.2}) -- ({5:.2},{6:.2}) .. controls ({3:.2},{6:.2}) and ({3:.2},{7:.2}) .. ({1:.2},{7:.2}) -- cycle;",
+        color,
+        x1,
+        -y1,
+        middle,
+        -y2,
+        x2,
+        -(y2 + height),
+        -(y1 + height)
+    )
+    .unwrap();
+}
+
+fn node(buf: &mut Vec<u8>, color: &str, x: f64, n: &Node, label_left: bool, locale: Locale) {
+    writeln!(
+        buf,
+        "    \\fill[{}] ({:.2},{:.2}) rectangle ({:.2},{:.2});",
+        color,
+        x,
+        -n.top,
+        x + NODE_WIDTH,
+        -(n.top + n.height)
+    )
+    .unwrap();
+    let (label_x, side) = if label_left {
+        (x, "east")
+    } else {
+        (x + NODE_WIDTH, "west")
+    };
+    writeln!(
+        buf,
+        "    \\node[anchor={}, font=\\tiny, fill=white, fill opacity=0.7, text opacity=1, inner sep=1pt] at ({:.2},{:.2}) {{{} ({})}};",
+        side,
+        label_x,
+        -(n.top + n.height / 2.0),
+        n.label,
+        locale.number(n.value as f64 / 100.0, 2)
+    )
+    .unwrap();
+}
+
+// Total -> categories -> the biggest notes of each category, as a TikZ picture.
+// Only spending flows: categories netted to zero or below are left out.
+pub fn write_tikz(buf: &mut Vec<u8>, stats: &Stats, config: &Config, links: Links) {
+    let mut categories = stats
+        .by_category
+        .iter()
+        .filter(|(_, v)| *v > 0)
+        .map(|(c, v)| (c.to_string(), *v))
+        .collect::<Vec<_>>();
+    let total = categories.iter().map(|(_, v)| v).sum::<i64>();
+    if total == 0 {
+        return;
+    }
+    if categories.len() > MAX_CATEGORIES {
+        let rest = categories
+            .split_off(MAX_CATEGORIES - 1)
+            .iter()
+            .map(|(_, v)| v)
+            .sum();
+        categories.push((String::from("Other"), rest));
+    }
+    let scale = HEIGHT / total as f64;
+
+    let mut by_note: HashMap<(String, String), i64> = HashMap::new();
+    for t in stats.transactions.iter().filter(|t| !t.note.is_empty()) {
+        *by_note
+            .entry((t.category.to_string(), t.note.clone()))
+            .or_default() += t.value;
+    }
+
+    let mut category_nodes = vec![];
+    let mut note_nodes = vec![]; // with the index of their category
+    let mut top = 0.0;
+    let mut note_top = 0.0;
+    for (i, (category, value)) in categories.iter().enumerate() {
+        let height = *value as f64 * scale;
+        category_nodes.push(Node {
+            label: escape_string_for_tex(category),
+            value: *value,
+            top,
+            height,
+        });
+        top += height.max(MIN_SLOT) + CATEGORY_GAP;
+
+        let mut notes = by_note
+            .iter()
+            .filter(|((c, _), v)| c == category && **v > 0)
+            .map(|((_, note), v)| (note.clone(), *v))
+            .collect::<Vec<_>>();
+        notes.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        for (note, value) in notes.into_iter().take(NOTES_PER_CATEGORY) {
+            let height = value as f64 * scale;
+            note_nodes.push((
+                i,
+                Node {
+                    label: note_for_tex(&note, config, links),
+                    value,
+                    top: note_top,
+                    height,
+                },
+            ));
+            note_top += height.max(MIN_SLOT) + NOTE_GAP;
+        }
+    }
+    // Centre the other columns against the categories.
+    let offset = ((top - CATEGORY_GAP) - (note_top - NOTE_GAP)) / 2.0;
+    for (_, n) in note_nodes.iter_mut() {
+        n.top += offset;
+    }
+    let source = Node {
+        label: String::from("Total"),
+        value: total,
+        top: ((top - CATEGORY_GAP) - HEIGHT) / 2.0,
+        height: HEIGHT,
+    };
+
+    writeln!(buf, "  \\begin{{center}}").unwrap();
+    writeln!(buf, "  \\begin{{tikzpicture}}").unwrap();
+    let mut out_of_source = source.top;
+    for (i, n) in category_nodes.iter().enumerate() {
+        let color = COLORS[i % COLORS.len()];
+        band(
+            buf,
+            color,
+            COLUMNS[0] + NODE_WIDTH,
+            out_of_source,
+            COLUMNS[1],
+            n.top,
+            n.height,
+        );
+        out_of_source += n.height;
+
+        let mut out_of_category = n.top;
+        for (_, note) in note_nodes.iter().filter(|(c, _)| *c == i) {
+            band(
+                buf,
+                color,
+                COLUMNS[1] + NODE_WIDTH,
+                out_of_category,
+                COLUMNS[2],
+                note.top,
+                note.height,
+            );
+            out_of_category += note.height;
+        }
+    }
+    node(buf, "black", COLUMNS[0], &source, true, config.locale);
+    for (i, n) in category_nodes.iter().enumerate() {
+        node(
+            buf,
+            COLORS[i % COLORS.len()],
+            COLUMNS[1],
+            n,
+            false,
+            config.locale,
+        );
+    }
+    for (i, n) in note_nodes.iter() {
+        node(
+            buf,
+            COLORS[i % COLORS.len()],
+            COLUMNS[2],
+            n,
+            false,
+            config.locale,
+        );
+    }
+    writeln!(buf, "  \\end{{tikzpicture}}").unwrap();
+    writeln!(buf, "  \\end{{center}}").unwrap();
+}
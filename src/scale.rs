@@ -0,0 +1,55 @@
+// How report tables and charts show amounts, for large figures that would
+// otherwise overflow their columns.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum AmountScale {
+    #[default]
+    Units,
+    Thousands,
+    Millions,
+}
+
+impl std::fmt::Display for AmountScale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Units => write!(f, "1"),
+            Self::Thousands => write!(f, "k"),
+            Self::Millions => write!(f, "M"),
+        }
+    }
+}
+
+impl AmountScale {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "1" | "" => Some(Self::Units),
+            "k" | "K" | "1000" => Some(Self::Thousands),
+            "M" | "1000000" => Some(Self::Millions),
+            _ => None,
+        }
+    }
+
+    pub fn apply(&self, x: f64) -> f64 {
+        match self {
+            Self::Units => x,
+            Self::Thousands => x / 1e3,
+            Self::Millions => x / 1e6,
+        }
+    }
+
+    // Written after scaled amounts, e.g. `12.50k`.
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            Self::Units => "",
+            Self::Thousands => "k",
+            Self::Millions => "M",
+        }
+    }
+
+    // `Spent` -> `Spent (k)`, for table headers and chart axes.
+    pub fn label(&self, label: &str) -> String {
+        match self {
+            Self::Units => String::from(label),
+            _ => format!("{} ({})", label, self.suffix()),
+        }
+    }
+}
@@ -0,0 +1,269 @@
+use std::fs;
+
+use chrono::NaiveDate;
+use strum::IntoEnumIterator;
+
+use crate::{
+    config::{Config, Provenance},
+    source::{format_amount, CsvFormat, TransactionSource},
+    Category,
+};
+
+// Fields are read by position; the header line is only there for humans.
+pub const COLUMNS: [(&str, &str); 8] = [
+    ("Amount", "negative for refunds"),
+    ("Date", "in the date format below"),
+    (
+        "Category",
+        "one of the categories below, anything else is `Miscellaneous`",
+    ),
+    ("End Date", "last day the expense is for"),
+    ("Payment Method", "free text"),
+    ("Note", "free text"),
+    ("Id", "optional, defaults to a hash of the other fields"),
+    ("Status", "optional, `pending` or `cleared`"),
+];
+
+fn print_header_mapping(source: &dyn TransactionSource, format: &CsvFormat) {
+    let Some(path) = source.path() else {
+        return;
+    };
+    if path
+        .extension()
+        .is_some_and(|x| x.eq_ignore_ascii_case("json"))
+    {
+        println!("  `{}` is read as JSON (see the README).", source.name());
+        return;
+    }
+    let content = fs::read_to_string(path).unwrap_or_default();
+    let content = content.strip_prefix('\u{feff}').unwrap_or(&content);
+    let Some(header) = content
+        .lines()
+        .find(|line| !line.split(format.delimiter).all(|x| x.trim().is_empty()))
+    else {
+        println!("  `{}` is empty.", source.name());
+        return;
+    };
+
+    println!("  Columns of `{}`:", source.name());
+    let fields = header
+        .split(format.delimiter)
+        .map(str::trim)
+        .collect::<Vec<_>>();
+    let max_len = fields.iter().map(|x| x.len()).max().unwrap_or_default();
+    for (i, field) in fields.iter().enumerate() {
+        println!(
+            "    {}. {:<3$} -> {}",
+            i + 1,
+            format!("`{}`", field),
+            COLUMNS.get(i).map_or("(ignored)", |x| x.0),
+            max_len + 2
+        );
+    }
+}
+
+fn provenance(config: &Config, key: &str) -> String {
+    match config.provenance(key) {
+        Provenance::Default => String::from("default"),
+        Provenance::File(line) => format!("{}:{}", config.path.display(), line),
+        Provenance::Flag => format!("--{}", key.replace('_', "-")),
+    }
+}
+
+fn print_section<T>(name: &str, entries: &[T], describe: impl Fn(&T) -> String) {
+    if entries.is_empty() {
+        return;
+    }
+    println!("  [{}]", name);
+    for entry in entries {
+        println!("    {}", describe(entry));
+    }
+}
+
+pub fn run(sources: &[Box<dyn TransactionSource>], config: &Config) {
+    println!("FILE FORMAT");
+    println!("===========");
+    let format = &config.csv;
+    println!("  One transaction per line, after a header line.");
+    println!(
+        "  Delimiter `{}`, dates like `{}` ({}), amounts like `{}`.",
+        format.delimiter.escape_default(),
+        format.format_date(NaiveDate::from_ymd_opt(2024, 12, 31).unwrap()),
+        format.date_format,
+        format.format_amount(1250)
+    );
+    for (i, (name, description)) in COLUMNS.iter().enumerate() {
+        println!("    {}. {:<14}  {}", i + 1, name, description);
+    }
+    for source in sources.iter() {
+        print_header_mapping(source.as_ref(), format);
+    }
+    println!(
+        "  Categories: {}",
+        Category::iter()
+            .filter(|c| !matches!(c, Category::Miscellaneous(_)))
+            .map(|c| c.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    println!();
+
+    println!("CONFIGURATION");
+    println!("=============");
+    println!(
+        "  File: `{}`{}",
+        config.path.display(),
+        if config.path.is_file() {
+            ""
+        } else {
+            " (not found, using defaults)"
+        }
+    );
+    let optional = |x: Option<String>| x.unwrap_or(String::from("(none)"));
+    let settings = [
+        ("rounding", config.rounding.to_string()),
+        ("precision", config.precision.to_string()),
+        ("scale", config.scale.to_string()),
+        ("timezone", config.timezone.to_string()),
+        (
+            "refunds",
+            String::from(if config.net_refunds {
+                "net"
+            } else {
+                "separate"
+            }),
+        ),
+        (
+            "statement_day",
+            optional(config.statement_day.map(|x| x.to_string())),
+        ),
+        ("amortize", config.amortize.to_string()),
+        ("budget_tightening", config.budget_tightening.to_string()),
+        (
+            "daily_allowance",
+            optional(config.daily_allowance.map(format_amount)),
+        ),
+        (
+            "allowance_exclude",
+            optional(
+                Some(
+                    config
+                        .allowance_exclude
+                        .iter()
+                        .map(|c| c.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                )
+                .filter(|x| !x.is_empty()),
+            ),
+        ),
+        (
+            "profile",
+            optional(config.profile.as_ref().map(|x| x.name.clone())),
+        ),
+        ("note_length", config.note_length.to_string()),
+        (
+            "moving_average_window",
+            config.moving_average_window.to_string(),
+        ),
+        ("min_sample", config.min_sample.to_string()),
+        (
+            "category_order",
+            optional(
+                Some(
+                    config
+                        .category_order
+                        .iter()
+                        .map(|c| c.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                )
+                .filter(|x| !x.is_empty()),
+            ),
+        ),
+        (
+            "footer",
+            optional(Some(config.footer.join(", ")).filter(|x| !x.is_empty())),
+        ),
+        ("currency", optional(config.currency.clone())),
+    ];
+    let max_len = settings.iter().map(|x| x.0.len()).max().unwrap_or_default();
+    let max_value_len = settings.iter().map(|x| x.1.len()).max().unwrap_or_default();
+    for (key, value) in settings.iter() {
+        println!(
+            "  {:<3$} = {:<4$}  ({})",
+            key,
+            value,
+            provenance(config, key),
+            max_len,
+            max_value_len
+        );
+    }
+
+    print_section("budgets", &config.budgets, |(c, amount)| {
+        format!("{} = {}", c, format_amount(*amount))
+    });
+    print_section("shares", &config.share_targets, |(c, share)| {
+        format!("{} <= {}%", c, share)
+    });
+    print_section("aliases", &config.aliases, |(alias, c)| {
+        format!("{} -> {}", alias, c)
+    });
+    print_section("rules", &config.rules, |(pattern, c)| {
+        format!("note containing `{}` -> {}", pattern, c)
+    });
+    print_section("shortcuts", &config.shortcuts, |(name, s)| {
+        format!(
+            "@{} = {}, {}, {}, {}",
+            name,
+            format_amount(s.value),
+            s.category,
+            s.payment_method,
+            s.note
+        )
+    });
+    print_section(
+        "payment_groups",
+        &config.payment_groups,
+        |(method, group)| format!("{} -> {}", method, group),
+    );
+    print_section("note_groups", &config.note_groups, |(name, patterns)| {
+        format!("{} = {}", name, patterns.join(", "))
+    });
+    print_section("dates", &config.special_dates, |(name, dates)| {
+        let dates = dates.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+        format!("{} = {}", name, dates.join(", "))
+    });
+    print_section("durables", &config.durables, |(note, d)| {
+        let mut parts = vec![];
+        if let Some(months) = d.lifetime_months {
+            parts.push(format!("{} months", months));
+        }
+        if let Some(uses) = d.uses {
+            parts.push(format!("{} uses", uses));
+        }
+        format!("{} = {}", note, parts.join(", "))
+    });
+    print_section("metrics", &config.metrics, |(name, expr)| {
+        format!("{} = {}", name, expr)
+    });
+    if let Some(profile) = config.profile.as_ref().filter(|x| x.name == "custom") {
+        print_section(
+            "profile",
+            &profile.buckets,
+            |(bucket, share, categories)| {
+                format!(
+                    "{} = {}%: {}",
+                    bucket,
+                    share,
+                    categories
+                        .iter()
+                        .map(|c| c.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            },
+        );
+    }
+    println!("=============");
+}
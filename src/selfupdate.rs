@@ -0,0 +1,206 @@
+use std::{
+    env, fs,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use crate::{
+    diagnostics::{self, Diagnostic, Location},
+    files, hash,
+    json::{self, Value},
+};
+
+const LATEST_RELEASE: &str = "https://api.github.com/repos/MichaelObvious/battista/releases/latest";
+
+// There is no HTTP client among the dependencies, so downloads go through
+// whichever of these is installed, like the clipboard does.
+const DOWNLOADERS: [(&str, &[&str], &str); 2] = [
+    (
+        "curl",
+        &["-fsSL", "-H", "Accept: application/vnd.github+json"],
+        "-o",
+    ),
+    (
+        "wget",
+        &["-q", "--header", "Accept: application/vnd.github+json"],
+        "-O",
+    ),
+];
+
+// Fetches `url` into `output`, or returns its content when there is none.
+fn download(url: &str, output: Option<&Path>) -> Result<Vec<u8>, String> {
+    for (tool, args, output_flag) in DOWNLOADERS {
+        let mut command = Command::new(tool);
+        command
+            .args(args)
+            .stdin(Stdio::null())
+            .stderr(Stdio::piped());
+        match output {
+            Some(path) => command.arg(output_flag).arg(path),
+            None => command.arg(output_flag).arg("-"),
+        };
+        let Ok(result) = command.arg(url).output() else {
+            continue; // not installed
+        };
+        if !result.status.success() {
+            return Err(format!(
+                "`{}` failed: {}",
+                tool,
+                String::from_utf8_lossy(&result.stderr).trim()
+            ));
+        }
+        return Ok(result.stdout);
+    }
+    Err(String::from("neither `curl` nor `wget` is installed"))
+}
+
+// `v0.2.10` -> [0, 2, 10], so that versions compare as numbers.
+fn version_numbers(version: &str) -> Vec<u64> {
+    version
+        .trim_start_matches('v')
+        .split(['.', '-'])
+        .map_while(|x| x.parse().ok())
+        .collect()
+}
+
+// The release binary for this platform is named exactly e.g.
+// `battista-linux-x86_64` (`.exe` on Windows), so that archives, checksums
+// and signatures published next to it are never taken for it.
+fn asset_name() -> String {
+    format!(
+        "{}-{}-{}{}",
+        env!("CARGO_PKG_NAME"),
+        env::consts::OS,
+        env::consts::ARCH,
+        env::consts::EXE_SUFFIX
+    )
+}
+
+// Download URL of the release asset called `name`.
+fn asset_url<'a>(release: &'a Value, name: &str) -> Option<&'a str> {
+    release
+        .get("assets")?
+        .as_array()
+        .iter()
+        .filter(|asset| asset.get("name").and_then(|x| x.as_str()) == Some(name))
+        .find_map(|asset| asset.get("browser_download_url")?.as_str())
+}
+
+// The SHA-256 published for `name`, in `<name>.sha256` or as its line of
+// `SHA256SUMS`, both as `sha256sum` writes them (`<hex>  <name>`).
+fn published_checksum(release: &Value, name: &str) -> Result<String, String> {
+    let Some(url) = asset_url(release, &format!("{}.sha256", name))
+        .or_else(|| asset_url(release, "SHA256SUMS"))
+    else {
+        return Err(format!("no checksum is published for `{}`", name));
+    };
+    let content = download(url, None)?;
+    String::from_utf8_lossy(&content)
+        .lines()
+        .find_map(|line| {
+            let mut fields = line.split_whitespace();
+            let checksum = fields.next()?;
+            let file = fields.next().map(|x| x.trim_start_matches('*'));
+            let valid = checksum.len() == 64 && checksum.chars().all(|c| c.is_ascii_hexdigit());
+            (valid && file.is_none_or(|x| x == name)).then(|| checksum.to_lowercase())
+        })
+        .ok_or_else(|| format!("the published checksums have none for `{}`", name))
+}
+
+// `self-update [--check]`: asks GitHub for the latest release, only when run,
+// and (without `--check`) replaces the running binary with it.
+pub fn run(check_only: bool) -> Result<(), Diagnostic> {
+    let fail = |message: String| diagnostics::error("self-update", None, message);
+    let body = download(LATEST_RELEASE, None)
+        .map_err(|e| fail(format!("Could not ask for the latest release: {}", e)))?;
+    let release = json::parse(&String::from_utf8_lossy(&body))
+        .map_err(|e| fail(format!("Unexpected answer from GitHub: {}", e)))?;
+    let Some(latest) = release.get("tag_name").and_then(|x| x.as_str()) else {
+        return Err(fail(String::from(
+            "Unexpected answer from GitHub: no `tag_name`",
+        )));
+    };
+    let current = env!("CARGO_PKG_VERSION");
+    if version_numbers(latest) <= version_numbers(current) {
+        println!(
+            "battista {} is up to date (latest release: {}).",
+            current, latest
+        );
+        return Ok(());
+    }
+    let page = release
+        .get("html_url")
+        .and_then(|x| x.as_str())
+        .unwrap_or_default();
+    println!(
+        "battista {} is available, this is {}: {}",
+        latest, current, page
+    );
+    if check_only {
+        return Ok(());
+    }
+
+    let name = asset_name();
+    let Some(url) = asset_url(&release, &name) else {
+        return Err(fail(format!(
+            "Release {} has no `{}`; install it with cargo or from {}",
+            latest, name, page
+        )));
+    };
+    let exe = env::current_exe()
+        .map_err(|e| fail(format!("Could not find the running binary: {}", e)))?;
+    let location = Location::file(&exe.display().to_string());
+    if let Err(e) = files::check_writable() {
+        return Err(diagnostics::error(
+            "io-write",
+            Some(location),
+            format!("Could not update: {}", e),
+        ));
+    }
+    if files::is_dry_run() {
+        println!("Would download `{}` over `{}`.", name, exe.display());
+        return Ok(());
+    }
+
+    let checksum =
+        published_checksum(&release, &name).map_err(|e| fail(format!("Not updating: {}", e)))?;
+    // The running binary stays in place until the download matches the
+    // checksum; only then is it replaced, in one rename.
+    let temp_path = files::temp_path_for(&exe);
+    if let Err(e) = download(url, Some(&temp_path)) {
+        let _ = fs::remove_file(&temp_path);
+        return Err(fail(format!("Could not download `{}`: {}", name, e)));
+    }
+    let downloaded = fs::read(&temp_path).map(|x| hash::sha256(&x));
+    if downloaded.as_ref().ok() != Some(&checksum) {
+        let _ = fs::remove_file(&temp_path);
+        return Err(fail(match downloaded {
+            Ok(downloaded) => format!(
+                "Not updating: `{}` has SHA-256 {}, the release publishes {}",
+                name, downloaded, checksum
+            ),
+            Err(e) => format!("Could not read the download of `{}`: {}", name, e),
+        }));
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if let Err(e) = fs::set_permissions(&temp_path, fs::Permissions::from_mode(0o755)) {
+            let _ = fs::remove_file(&temp_path);
+            return Err(diagnostics::error(
+                "io-write",
+                Some(location),
+                format!("Could not update: {}", e),
+            ));
+        }
+    }
+    if let Err(e) = files::commit_temp_file(&temp_path, &exe) {
+        return Err(diagnostics::error(
+            "io-write",
+            Some(location),
+            format!("Could not update: {}", e),
+        ));
+    }
+    println!("Updated to battista {}.", latest);
+    Ok(())
+}
@@ -0,0 +1,833 @@
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    fs::{self, File},
+    io::{self, Read},
+    path::PathBuf,
+    sync::OnceLock,
+    thread,
+};
+
+use chrono::NaiveDate;
+
+use crate::{
+    diagnostics::{self, Diagnostic, Location},
+    hash,
+    json::{self, Value},
+    Category, Status, Transaction,
+};
+
+// Anything transactions can be read from. `get_stats` and the commands only
+// ever see the resulting `Vec<Transaction>`, so a new backend only has to
+// implement this trait. Sources are read in parallel, hence `Sync`.
+pub trait TransactionSource: Sync {
+    // Human readable name, used in messages and report titles.
+    fn name(&self) -> String;
+    // Short name telling sources apart in per-source statistics.
+    fn label(&self) -> String {
+        self.name()
+    }
+    // Path of the underlying file, if there is one (used for locking and to
+    // decide where outputs go).
+    fn path(&self) -> Option<&PathBuf> {
+        None
+    }
+    fn read_transactions(&self) -> Result<Vec<Transaction>, Diagnostic>;
+    // SHA-256 of the data, once `read_transactions` has read it.
+    fn sha256(&self) -> Option<String> {
+        None
+    }
+}
+
+// How fields are written in data files, read and written alike so that
+// files stay in the format of the spreadsheet they come from.
+#[derive(Clone, Debug)]
+pub struct CsvFormat {
+    pub delimiter: char,
+    pub date_format: String, // chrono format, e.g. `%Y-%m-%d`
+    pub decimal: char,       // `.` or `,`
+}
+
+impl Default for CsvFormat {
+    fn default() -> Self {
+        Self {
+            delimiter: ';',
+            date_format: String::from("%-d/%-m/%Y"),
+            decimal: '.',
+        }
+    }
+}
+
+impl CsvFormat {
+    // A `.` is always accepted, whatever the decimal separator.
+    pub fn parse_amount(&self, s: &str) -> Result<i64, String> {
+        parse_amount(&s.replace(self.decimal, "."))
+    }
+
+    pub fn format_amount(&self, value: i64) -> String {
+        format_amount(value).replace('.', &self.decimal.to_string())
+    }
+
+    pub fn parse_date(&self, s: &str) -> Option<NaiveDate> {
+        NaiveDate::parse_from_str(s, &self.date_format).ok()
+    }
+
+    pub fn format_date(&self, date: NaiveDate) -> String {
+        date.format(&self.date_format).to_string()
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ReadOptions {
+    pub skip_comments: bool, // ignore lines starting with `#`
+    pub format: CsvFormat,
+}
+
+pub struct CsvFileSource {
+    pub path: PathBuf,
+    pub options: ReadOptions,
+    pub sha256: OnceLock<String>,
+}
+
+impl TransactionSource for CsvFileSource {
+    fn name(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn label(&self) -> String {
+        self.path
+            .file_stem()
+            .map(|x| x.to_string_lossy().into_owned())
+            .unwrap_or_else(|| self.name())
+    }
+
+    fn path(&self) -> Option<&PathBuf> {
+        Some(&self.path)
+    }
+
+    fn read_transactions(&self) -> Result<Vec<Transaction>, Diagnostic> {
+        let file = File::open(&self.path).map_err(|e| {
+            diagnostics::error(
+                "io-read",
+                Some(Location::file(&self.name())),
+                format!("Could not read file: {}", e),
+            )
+        })?;
+        let (transactions, sha256) = read_csv(file, &self.name(), &self.options)?;
+        let _ = self.sha256.set(sha256);
+        Ok(transactions)
+    }
+
+    fn sha256(&self) -> Option<String> {
+        self.sha256.get().cloned()
+    }
+}
+
+// Same format as `CsvFileSource`, read from standard input (`-` on the command line).
+pub struct StdinSource {
+    pub options: ReadOptions,
+    pub sha256: OnceLock<String>,
+}
+
+impl TransactionSource for StdinSource {
+    fn name(&self) -> String {
+        String::from("<stdin>")
+    }
+
+    fn read_transactions(&self) -> Result<Vec<Transaction>, Diagnostic> {
+        let (transactions, sha256) = read_csv(io::stdin().lock(), &self.name(), &self.options)?;
+        let _ = self.sha256.set(sha256);
+        Ok(transactions)
+    }
+
+    fn sha256(&self) -> Option<String> {
+        self.sha256.get().cloned()
+    }
+}
+
+// A JSON export: an array of transactions (or an object with one under
+// `transactions`), each an object like
+//
+//     {"amount": 12.5, "date": "2024-05-03", "category": "Grocery",
+//      "payment_method": "Twint", "note": "Coop"}
+//
+// with optional `end_date`, `id` and `status`. Dates are `yyyy-mm-dd`, and
+// amounts numbers or strings in the CSV format.
+pub struct JsonFileSource {
+    pub path: PathBuf,
+    pub options: ReadOptions,
+    pub sha256: OnceLock<String>,
+}
+
+impl TransactionSource for JsonFileSource {
+    fn name(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn label(&self) -> String {
+        self.path
+            .file_stem()
+            .map(|x| x.to_string_lossy().into_owned())
+            .unwrap_or_else(|| self.name())
+    }
+
+    fn path(&self) -> Option<&PathBuf> {
+        Some(&self.path)
+    }
+
+    fn read_transactions(&self) -> Result<Vec<Transaction>, Diagnostic> {
+        let content = fs::read_to_string(&self.path).map_err(|e| {
+            diagnostics::error(
+                "io-read",
+                Some(Location::file(&self.name())),
+                format!("Could not read file: {}", e),
+            )
+        })?;
+        let _ = self.sha256.set(hash::sha256(content.as_bytes()));
+        parse_json(&content, &self.name(), &self.options)
+    }
+
+    fn sha256(&self) -> Option<String> {
+        self.sha256.get().cloned()
+    }
+}
+
+// Errors point at the transaction by its position, counting from 1, as
+// there is no line to point at.
+pub fn parse_json(
+    content: &str,
+    origin: &str,
+    options: &ReadOptions,
+) -> Result<Vec<Transaction>, Diagnostic> {
+    let value = json::parse(content).map_err(|e| {
+        diagnostics::error(
+            "parse-json",
+            Some(Location::file(origin)),
+            format!("Could not parse JSON: {}", e),
+        )
+    })?;
+    let entries = match &value {
+        Value::Array(entries) => entries.as_slice(),
+        Value::Object(_) => value
+            .get("transactions")
+            .map(|x| x.as_array())
+            .unwrap_or_default(),
+        _ => &[],
+    };
+
+    let mut ids = Ids::default();
+    let mut transactions = vec![];
+    for (i, entry) in entries.iter().enumerate() {
+        let n = i + 1;
+        let fail = |code: &'static str, message: String| {
+            diagnostics::error(
+                code,
+                Some(Location::file(origin)),
+                format!("Transaction {}: {}", n, message),
+            )
+        };
+        let text = |key: &str| entry.get(key).and_then(|x| x.as_str()).unwrap_or_default();
+        let parse_date = |key: &str| NaiveDate::parse_from_str(text(key).trim(), "%Y-%m-%d").ok();
+
+        let value = match entry.get("amount") {
+            Some(Value::Number(x)) => Ok((x * 100.0).round() as i64),
+            Some(Value::String(x)) => options.format.parse_amount(x),
+            _ => Err(String::new()),
+        };
+        let value = value
+            .map_err(|reason| fail("parse-amount", format!("Could not parse amount{}", reason)))?;
+        let date = parse_date("date").ok_or_else(|| {
+            fail(
+                "parse-date",
+                format!("Could not parse date `{}`", text("date")),
+            )
+        })?;
+        let end_date = match entry.get("end_date") {
+            None | Some(Value::Null) => date,
+            Some(_) => parse_date("end_date").ok_or_else(|| {
+                fail(
+                    "parse-date",
+                    format!("Could not parse date `{}`", text("end_date")),
+                )
+            })?,
+        };
+        if Ordering::is_gt(date.cmp(&end_date)) {
+            return Err(fail(
+                "date-order",
+                String::from("Date is later than end date"),
+            ));
+        }
+        let status = Status::parse(text("status").trim()).ok_or_else(|| {
+            fail(
+                "parse-status",
+                format!(
+                    "Unknown status `{}` (expected `pending` or `cleared`)",
+                    text("status")
+                ),
+            )
+        })?;
+
+        let mut transaction = Transaction {
+            value,
+            date,
+            category: Category::from(text("category").trim()),
+            end_date,
+            payment_method: String::from(text("payment_method").trim()),
+            note: String::from(text("note").trim()),
+            id: String::from(text("id").trim()),
+            status,
+            ..Transaction::default()
+        };
+        if let Err(first) = ids.assign(&mut transaction, n) {
+            return Err(fail(
+                "duplicate-id",
+                format!(
+                    "Duplicate transaction id `{}` (already used by transaction {})",
+                    transaction.id, first
+                ),
+            ));
+        }
+        transactions.push(transaction);
+    }
+
+    transactions.sort_by_key(|a| a.date);
+    Ok(transactions)
+}
+
+// Amount in cents, from `12`, `12.5`, `-12.05`, ... On failure the error is a
+// hint to append to the message (possibly empty).
+pub fn parse_amount(s: &str) -> Result<i64, String> {
+    let s = s.trim();
+    let negative = s.starts_with('-');
+    let mut parts = s.split('.');
+    let units = parts
+        .next()
+        .unwrap()
+        .trim()
+        .parse::<i64>()
+        .map_err(|_| String::new())?;
+    let cents = parts.next().unwrap_or("0").trim();
+    if parts.next().is_some() || !cents.chars().all(|c| c.is_ascii_digit()) {
+        return Err(String::new());
+    }
+    let cents = match cents.len() {
+        0 => 0,
+        1 => cents.parse::<i64>().unwrap() * 10,
+        2 => cents.parse::<i64>().unwrap(),
+        _ => return Err(String::from(" (cents seem to have too many digits)")),
+    };
+    Ok(units * 100 + if units < 0 || negative { -cents } else { cents })
+}
+
+// Inverse of `parse_amount`.
+pub fn format_amount(value: i64) -> String {
+    format!(
+        "{}{}.{:02}",
+        if value < 0 { "-" } else { "" },
+        value.abs() / 100,
+        value.abs() % 100
+    )
+}
+
+// Files are only split for parsing on several threads in chunks of at least
+// this many bytes.
+const MIN_CHUNK: usize = 1 << 20;
+
+// Reads the delimiter separated format described in the README, along with
+// the SHA-256 of its bytes. `origin` is only used to point at the offending
+// line in error messages.
+pub fn read_csv(
+    mut reader: impl Read,
+    origin: &str,
+    options: &ReadOptions,
+) -> Result<(Vec<Transaction>, String), Diagnostic> {
+    let mut content = vec![];
+    reader.read_to_end(&mut content).map_err(|e| {
+        diagnostics::error(
+            "io-read",
+            Some(Location::file(origin)),
+            format!("Could not read file: {}", e),
+        )
+    })?;
+    let chunks = thread::available_parallelism()
+        .map_or(1, |x| x.get())
+        .min(content.len() / MIN_CHUNK)
+        .max(1);
+    let transactions = parse_csv(&content, origin, options, chunks)?;
+    Ok((transactions, hash::sha256(&content)))
+}
+
+// Parses `content` in up to `chunks` pieces at once, split at line
+// boundaries after the header. Lines are parsed on their own, so only the
+// ids are left for afterwards: they are given in file order, as if the lines
+// were read one after another, for the same occurrence counts and the same
+// first duplicate (or the first error, whichever comes first).
+fn parse_csv(
+    content: &[u8],
+    origin: &str,
+    options: &ReadOptions,
+    chunks: usize,
+) -> Result<Vec<Transaction>, Diagnostic> {
+    // The header is the first line with something on it, usually the first.
+    let mut line_no = 0;
+    let mut body = content;
+    while !body.is_empty() {
+        let (line, rest) = next_line(body);
+        line_no += 1;
+        body = rest;
+        let line = decode_line(line, origin, line_no)?;
+        // Files saved by some editors on Windows start with a byte order mark.
+        let line = if line_no == 1 {
+            line.strip_prefix('\u{feff}').unwrap_or(line)
+        } else {
+            line
+        };
+        if !is_empty_line(line, options) {
+            break;
+        }
+    }
+
+    let mut pieces = vec![];
+    for left in (1..=chunks).rev() {
+        let end = body.len() / left;
+        let end = body[end..]
+            .iter()
+            .position(|x| *x == b'\n')
+            .map_or(body.len(), |i| end + i + 1);
+        let (piece, rest) = body.split_at(end);
+        pieces.push((line_no + 1, piece));
+        line_no += piece.iter().filter(|x| **x == b'\n').count();
+        body = rest;
+    }
+    let parsed = if pieces.len() == 1 {
+        vec![parse_lines(pieces[0].1, pieces[0].0, origin, options)]
+    } else {
+        thread::scope(|scope| {
+            let handles = pieces
+                .iter()
+                .map(|(first, piece)| scope.spawn(|| parse_lines(piece, *first, origin, options)))
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .map(|x| x.join().unwrap())
+                .collect::<Vec<_>>()
+        })
+    };
+
+    let mut ids = Ids::default();
+    let mut transactions = vec![];
+    for (piece, error) in parsed {
+        for (line_no, mut transaction) in piece {
+            if let Err(other_line) = ids.assign(&mut transaction, line_no) {
+                return Err(diagnostics::error(
+                    "duplicate-id",
+                    Some(Location::new(origin, line_no)),
+                    format!(
+                        "Duplicate transaction id `{}` (already used on line {})",
+                        transaction.id, other_line
+                    ),
+                ));
+            }
+            transactions.push(transaction);
+        }
+        if let Some(error) = error {
+            return Err(error);
+        }
+    }
+    transactions.sort_by_key(|a| a.date);
+    Ok(transactions)
+}
+
+// The first line of `content`, with its line ending, and what follows it.
+fn next_line(content: &[u8]) -> (&[u8], &[u8]) {
+    match content.iter().position(|x| *x == b'\n') {
+        Some(i) => content.split_at(i + 1),
+        None => (content, &[]),
+    }
+}
+
+// Like `str::lines`, which splits on both `\n` and `\r\n`, so mixed line
+// endings are fine.
+fn decode_line<'a>(line: &'a [u8], origin: &str, line_no: usize) -> Result<&'a str, Diagnostic> {
+    let line = std::str::from_utf8(line).map_err(|_| {
+        diagnostics::error(
+            "io-read",
+            Some(Location::new(origin, line_no)),
+            String::from("Could not read file: the line is not valid UTF-8"),
+        )
+    })?;
+    Ok(match line.strip_suffix('\n') {
+        Some(line) => line.strip_suffix('\r').unwrap_or(line),
+        None => line,
+    })
+}
+
+// Blank lines, and lines with only empty fields (spreadsheets like to leave a
+// few `;;;;;` at the end), carry no transaction, and neither do comments.
+fn is_empty_line(line: &str, options: &ReadOptions) -> bool {
+    line.split(options.format.delimiter)
+        .all(|x| x.trim().is_empty())
+        || (options.skip_comments && line.trim_start().starts_with('#'))
+}
+
+// The transactions of the lines of `piece`, the first of which is line
+// `line_no` of the file, by line and without ids yet. On an error, the ones
+// before it come with it.
+fn parse_lines(
+    mut piece: &[u8],
+    mut line_no: usize,
+    origin: &str,
+    options: &ReadOptions,
+) -> (Vec<(usize, Transaction)>, Option<Diagnostic>) {
+    let mut transactions = vec![];
+    while !piece.is_empty() {
+        let (line, rest) = next_line(piece);
+        piece = rest;
+        let parsed = decode_line(line, origin, line_no).and_then(|line| {
+            if is_empty_line(line, options) {
+                Ok(None)
+            } else {
+                parse_line(line, origin, line_no, &options.format).map(Some)
+            }
+        });
+        match parsed {
+            Ok(Some(transaction)) => transactions.push((line_no, transaction)),
+            Ok(None) => {}
+            Err(error) => return (transactions, Some(error)),
+        }
+        line_no += 1;
+    }
+    (transactions, None)
+}
+
+// The ids given so far in a file. Transactions without an explicit id get a
+// hash of their fields, counting repeats of the same fields, and no two may
+// share one.
+#[derive(Default)]
+struct Ids {
+    seen: HashMap<String, usize>, // and where
+    occurrences: HashMap<String, u32>,
+}
+
+impl Ids {
+    // On a duplicate, the error is where the id was seen first.
+    fn assign(&mut self, transaction: &mut Transaction, at: usize) -> Result<(), usize> {
+        if transaction.id.is_empty() {
+            let base_id = transaction.compute_id(0);
+            let occurrence = self.occurrences.entry(base_id).or_insert(0);
+            transaction.id = transaction.compute_id(*occurrence);
+            *occurrence += 1;
+        }
+        if let Some(first) = self.seen.get(&transaction.id) {
+            return Err(*first);
+        }
+        self.seen.insert(transaction.id.clone(), at);
+        Ok(())
+    }
+}
+
+// A transaction line, without its line ending.
+fn parse_line(
+    line: &str,
+    origin: &str,
+    line_no: usize,
+    format: &CsvFormat,
+) -> Result<Transaction, Diagnostic> {
+    let fields = line.split(format.delimiter);
+    let mut transaction = Transaction::default();
+    for (field_idx, field) in fields.enumerate() {
+        match field_idx {
+            0 => match format.parse_amount(field) {
+                Ok(value) => transaction.value = value,
+                Err(reason) => {
+                    return Err(diagnostics::error(
+                        "parse-amount",
+                        Some(Location::new(origin, line_no)),
+                        format!("Could not parse amount `{}`{}", field.trim(), reason),
+                    ))
+                }
+            },
+            1 => {
+                if let Some(date) = format.parse_date(field.trim()) {
+                    transaction.date = date;
+                } else {
+                    return Err(diagnostics::error(
+                        "parse-date",
+                        Some(Location::new(origin, line_no)),
+                        format!("Could not parse date `{}`", field.trim()),
+                    ));
+                }
+            }
+            2 => {
+                transaction.category = Category::from(field.trim());
+            }
+            3 => {
+                if let Some(date) = format.parse_date(field.trim()) {
+                    transaction.end_date = date;
+                } else {
+                    return Err(diagnostics::error(
+                        "parse-date",
+                        Some(Location::new(origin, line_no)),
+                        format!("Could not parse date `{}`", field.trim()),
+                    ));
+                }
+            }
+            4 => {
+                transaction.payment_method = String::from(field.trim());
+            }
+            5 => {
+                transaction.note = String::from(field.trim());
+            }
+            6 => {
+                transaction.id = String::from(field.trim());
+            }
+            7 => match Status::parse(field.trim()) {
+                Some(status) => transaction.status = status,
+                None => {
+                    return Err(diagnostics::error(
+                        "parse-status",
+                        Some(Location::new(origin, line_no)),
+                        format!(
+                            "Unknown status `{}` (expected `pending` or `cleared`)",
+                            field.trim()
+                        ),
+                    ))
+                }
+            },
+            _ => {}
+        }
+    }
+
+    if Ordering::is_gt(transaction.date.cmp(&transaction.end_date)) {
+        return Err(diagnostics::error(
+            "date-order",
+            Some(Location::new(origin, line_no)),
+            String::from("Date is later than end date"),
+        ));
+    }
+
+    Ok(transaction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = "Value;Date;Category;End Date;Payment Method;Note";
+
+    fn parse(content: &str, skip_comments: bool) -> Vec<Transaction> {
+        let options = ReadOptions {
+            skip_comments,
+            ..ReadOptions::default()
+        };
+        read_csv(content.as_bytes(), "test.csv", &options)
+            .unwrap()
+            .0
+    }
+
+    fn notes(transactions: &[Transaction]) -> Vec<&str> {
+        transactions.iter().map(|t| t.note.as_str()).collect()
+    }
+
+    #[test]
+    fn skips_byte_order_mark() {
+        let content = format!(
+            "\u{feff}{}\n1.50;1/2/2024;Grocery;1/2/2024;cash;bread\n",
+            HEADER
+        );
+        let transactions = parse(&content, false);
+        assert_eq!(notes(&transactions), ["bread"]);
+        assert_eq!(transactions[0].value, 150);
+    }
+
+    #[test]
+    fn accepts_crlf_and_lf() {
+        let content = format!(
+            "{}\r\n1;1/2/2024;Grocery;1/2/2024;cash;crlf\r\n2;2/2/2024;Grocery;2/2/2024;cash;lf\n",
+            HEADER
+        );
+        assert_eq!(notes(&parse(&content, false)), ["crlf", "lf"]);
+    }
+
+    #[test]
+    fn ignores_blank_and_empty_field_lines() {
+        let content = format!(
+            "\n{}\n\n1;1/2/2024;Grocery;1/2/2024;cash;a\n   \n;;;;;\n ; ;;\n2;2/2/2024;Rent;2/2/2024;bank;b\n;;;\n",
+            HEADER
+        );
+        assert_eq!(notes(&parse(&content, false)), ["a", "b"]);
+    }
+
+    #[test]
+    fn accepts_missing_trailing_newline() {
+        let content = format!("{}\n1;1/2/2024;Grocery;1/2/2024;cash;last", HEADER);
+        assert_eq!(notes(&parse(&content, false)), ["last"]);
+    }
+
+    #[test]
+    fn skips_comments_only_when_asked() {
+        let content = format!(
+            "# exported from the bank\n{}\n  # checked in March\n1;1/2/2024;Grocery;1/2/2024;cash;a\n",
+            HEADER
+        );
+        assert_eq!(notes(&parse(&content, true)), ["a"]);
+
+        // Without `skip_comments` a `#` is just text.
+        let content = format!(
+            "{}\n1;1/2/2024;Grocery;1/2/2024;cash;# not a comment\n",
+            HEADER
+        );
+        assert_eq!(notes(&parse(&content, false)), ["# not a comment"]);
+    }
+
+    #[test]
+    fn skips_only_the_first_line_as_header() {
+        let content = format!(
+            "{}\n1;1/2/2024;Grocery;1/2/2024;cash;a\n2;2/2/2024;Grocery;2/2/2024;cash;b\n",
+            HEADER
+        );
+        assert_eq!(notes(&parse(&content, false)), ["a", "b"]);
+        assert!(parse(HEADER, false).is_empty());
+        assert!(parse("", false).is_empty());
+    }
+
+    #[test]
+    fn hashes_what_it_reads() {
+        // Long enough for lines to straddle the 64 byte blocks of SHA-256.
+        let content = format!(
+            "{}\n{}",
+            HEADER,
+            "12.50;1/2/2024;Grocery;1/2/2024;cash;bread and some more words\n".repeat(7)
+        );
+        let (_, sha256) =
+            read_csv(content.as_bytes(), "test.csv", &ReadOptions::default()).unwrap();
+        assert_eq!(sha256, hash::sha256(content.as_bytes()));
+    }
+
+    #[test]
+    fn fails_on_invalid_utf8() {
+        let content = format!("{}\n1;1/2/2024;Grocery;1/2/2024;cash;caf\u{e9}\n", HEADER);
+        let latin1 = content.chars().map(|c| c as u8).collect::<Vec<_>>();
+        let error = read_csv(latin1.as_slice(), "test.csv", &ReadOptions::default()).unwrap_err();
+        assert_eq!(error.code, "io-read");
+    }
+
+    #[test]
+    fn parses_in_chunks_as_in_one_piece() {
+        let content = format!(
+            "\n{}\n{}\n;;;\n{}",
+            HEADER,
+            "1;1/2/2024;Grocery;1/2/2024;cash;same\n".repeat(5),
+            (1..=20)
+                .map(|i| format!("{};{}/2/2024;Rent;{}/2/2024;bank;n{}\n", i, i, i, i))
+                .collect::<String>()
+        );
+        let ids = |chunks| {
+            parse_csv(
+                content.as_bytes(),
+                "test.csv",
+                &ReadOptions::default(),
+                chunks,
+            )
+            .unwrap()
+            .into_iter()
+            .map(|t| (t.note, t.id))
+            .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(1).len(), 25);
+        assert_eq!(ids(4), ids(1));
+        assert_eq!(ids(100), ids(1));
+    }
+
+    #[test]
+    fn reports_the_first_error_of_any_chunk() {
+        let line = |i: usize| format!("1;1/2/2024;Grocery;1/2/2024;cash;n{};id{}\n", i, i);
+        let mut lines = (0..30).map(line).collect::<Vec<_>>();
+        lines[20] = String::from("x;1/2/2024;Grocery;1/2/2024;cash;bad\n");
+        lines[25] = line(3);
+        let content = format!("{}\n{}", HEADER, lines.concat());
+        for chunks in [1, 3, 8] {
+            let error = parse_csv(
+                content.as_bytes(),
+                "test.csv",
+                &ReadOptions::default(),
+                chunks,
+            )
+            .unwrap_err();
+            assert_eq!(error.code, "parse-amount");
+            assert_eq!(error.location.unwrap().line, Some(22));
+        }
+
+        lines[20] = line(20);
+        let content = format!("{}\n{}", HEADER, lines.concat());
+        for chunks in [1, 3, 8] {
+            let error = parse_csv(
+                content.as_bytes(),
+                "test.csv",
+                &ReadOptions::default(),
+                chunks,
+            )
+            .unwrap_err();
+            assert_eq!(error.code, "duplicate-id");
+            assert_eq!(error.location.unwrap().line, Some(27));
+        }
+    }
+
+    #[test]
+    fn reads_json_exports() {
+        let content = r#"[
+            {"amount": 12.5, "date": "2024-02-01", "category": "Grocery",
+             "payment_method": "cash", "note": "bread"},
+            {"amount": "3.05", "date": "2024-01-20", "end_date": "2024-01-31",
+             "category": "Transportation", "payment_method": "card", "note": "bus",
+             "status": "pending"}
+        ]"#;
+        let transactions = parse_json(content, "test.json", &ReadOptions::default()).unwrap();
+        assert_eq!(notes(&transactions), ["bus", "bread"]);
+        assert_eq!(transactions[0].value, 305);
+        assert_eq!(transactions[0].status, Status::Pending);
+        assert_eq!(transactions[1].value, 1250);
+        assert_eq!(transactions[1].end_date, transactions[1].date);
+
+        let wrapped = format!(r#"{{"transactions": {}}}"#, content);
+        let transactions = parse_json(&wrapped, "test.json", &ReadOptions::default()).unwrap();
+        assert_eq!(notes(&transactions), ["bus", "bread"]);
+    }
+
+    #[test]
+    fn rejects_invalid_json_transactions() {
+        let code = |content: &str| {
+            parse_json(content, "test.json", &ReadOptions::default())
+                .unwrap_err()
+                .code
+        };
+        assert_eq!(code("[{"), "parse-json");
+        assert_eq!(
+            code(r#"[{"amount": true, "date": "2024-01-01"}]"#),
+            "parse-amount"
+        );
+        assert_eq!(code(r#"[{"amount": 1, "date": "1/1/2024"}]"#), "parse-date");
+        assert_eq!(
+            code(r#"[{"amount": 1, "date": "2024-01-02", "end_date": "2024-01-01"}]"#),
+            "date-order"
+        );
+        assert_eq!(
+            code(
+                r#"[{"amount": 1, "date": "2024-01-01", "id": "a"}, {"amount": 2, "date": "2024-01-01", "id": "a"}]"#
+            ),
+            "duplicate-id"
+        );
+    }
+
+    #[test]
+    fn parses_cents_with_a_leading_zero() {
+        assert_eq!(parse_amount("10.05"), Ok(1005));
+        assert_eq!(parse_amount("-10.05"), Ok(-1005));
+        assert_eq!(parse_amount("-0.05"), Ok(-5));
+        assert_eq!(parse_amount("10.5"), Ok(1050));
+        assert_eq!(parse_amount("10.50"), Ok(1050));
+        assert_eq!(parse_amount("10"), Ok(1000));
+        assert!(parse_amount("10.005").is_err());
+        assert!(parse_amount("10.x").is_err());
+    }
+}
@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+
+use chrono::{Datelike, NaiveDate, TimeDelta};
+
+use crate::{config::Config, StatsCollection};
+
+// One of the dates of a `[dates]` entry.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SpecialDate {
+    Range(NaiveDate, NaiveDate), // a single day is a range of one
+    Monthly(u32),                // `every 25`, e.g. payday
+}
+
+impl SpecialDate {
+    // `24/12/2025`, `1/8/2025..15/8/2025` or `every 25`.
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        let date = |x: &str| NaiveDate::parse_from_str(x.trim(), "%d/%m/%Y").ok();
+        if let Some(day) = s.strip_prefix("every ") {
+            let day = day.trim().parse().ok().filter(|x| (1..=31).contains(x))?;
+            return Some(Self::Monthly(day));
+        }
+        if let Some((from, to)) = s.split_once("..") {
+            let (from, to) = (date(from)?, date(to)?);
+            return (from <= to).then_some(Self::Range(from, to));
+        }
+        date(s).map(|d| Self::Range(d, d))
+    }
+
+    // Days past the end of a shorter month fall on its last day.
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        match self {
+            Self::Range(from, to) => *from <= date && date <= *to,
+            Self::Monthly(day) => {
+                let last = (28..=31)
+                    .rev()
+                    .find(|d| date.with_day(*d).is_some())
+                    .unwrap();
+                date.day() == (*day).min(last)
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for SpecialDate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Range(from, to) if from == to => write!(f, "{}", from.format("%d/%m/%Y")),
+            Self::Range(from, to) => {
+                write!(f, "{}..{}", from.format("%d/%m/%Y"), to.format("%d/%m/%Y"))
+            }
+            Self::Monthly(day) => write!(f, "every {}", day),
+        }
+    }
+}
+
+// Spending on the days of a `[dates]` entry, or (with no name) on all the
+// other days, from the first transaction to today.
+#[derive(Debug)]
+pub struct Period {
+    pub name: Option<String>,
+    pub days: u32,
+    pub total: i64,
+}
+
+impl Period {
+    pub fn per_day(&self) -> f64 {
+        self.total as f64 / 100.0 / self.days as f64
+    }
+}
+
+// The entries that cover at least one day with data, followed by the normal
+// days (if there are any).
+pub fn compare(stats: &StatsCollection, config: &Config) -> Vec<Period> {
+    let mut by_day: HashMap<NaiveDate, i64> = HashMap::new();
+    for t in stats.yearly.iter().flat_map(|(_, x)| x.transactions.iter()) {
+        *by_day.entry(t.date).or_default() += t.value;
+    }
+    let Some(first) = by_day.keys().min().copied() else {
+        return vec![];
+    };
+
+    let mut periods = config
+        .special_dates
+        .iter()
+        .map(|(name, _)| Period {
+            name: Some(name.clone()),
+            days: 0,
+            total: 0,
+        })
+        .collect::<Vec<_>>();
+    let mut normal = Period {
+        name: None,
+        days: 0,
+        total: 0,
+    };
+    let mut day = first;
+    while day <= config.today() {
+        let spent = by_day.get(&day).copied().unwrap_or_default();
+        let mut special = false;
+        for ((_, dates), period) in config.special_dates.iter().zip(periods.iter_mut()) {
+            if dates.iter().any(|x| x.contains(day)) {
+                period.days += 1;
+                period.total += spent;
+                special = true;
+            }
+        }
+        if !special {
+            normal.days += 1;
+            normal.total += spent;
+        }
+        day += TimeDelta::days(1);
+    }
+
+    periods.retain(|x| x.days > 0);
+    if normal.days > 0 {
+        periods.push(normal);
+    }
+    periods
+}
+
+// Names of the ranges starting in each of `months`, to mark them on charts.
+// Monthly dates would mark every month, so they are left out.
+pub fn markers(months: &[(i32, u32)], config: &Config) -> Vec<((i32, u32), String)> {
+    let mut markers: Vec<((i32, u32), String)> = vec![];
+    for (name, dates) in config.special_dates.iter() {
+        for date in dates {
+            let SpecialDate::Range(from, _) = date else {
+                continue;
+            };
+            let month = (from.year(), from.month());
+            if !months.contains(&month) {
+                continue;
+            }
+            match markers.iter_mut().find(|(m, _)| *m == month) {
+                Some((_, names)) if !names.split(", ").any(|x| x == name) => {
+                    names.push_str(", ");
+                    names.push_str(name);
+                }
+                Some(_) => {}
+                None => markers.push((month, name.clone())),
+            }
+        }
+    }
+    markers
+}
@@ -0,0 +1,159 @@
+use std::{fs, path::Path};
+
+use crate::{
+    diagnostics::{self, Diagnostic, Location},
+    hash, json,
+};
+
+// Written at the top of TeX reports (as comments) and into JSON snapshots, so
+// that `verify` can tell which data a report was made from.
+const VERSION_TAG: &str = "% battista-version:";
+const DATA_TAG: &str = "% battista-data:";
+
+pub fn tex_header(fingerprints: &[(String, String)]) -> String {
+    let mut out = format!("{} {}\n", VERSION_TAG, env!("CARGO_PKG_VERSION"));
+    for (name, sha256) in fingerprints {
+        // The hash first, as names may contain spaces.
+        out.push_str(&format!("{} {} {}\n", DATA_TAG, sha256, name));
+    }
+    out
+}
+
+// Version and data files (name and SHA-256) a report says it was made from.
+type Recorded = (Option<String>, Vec<(String, String)>);
+
+fn read_report(path: &Path) -> Result<Recorded, Diagnostic> {
+    let origin = path.display().to_string();
+    let content = fs::read_to_string(path).map_err(|e| {
+        diagnostics::error(
+            "io-read",
+            Some(Location::file(&origin)),
+            format!("Could not read report: {}", e),
+        )
+    })?;
+
+    if path.extension().is_some_and(|x| x == "json") {
+        let value = json::parse(&content).map_err(|e| {
+            diagnostics::error(
+                "verify-invalid",
+                Some(Location::file(&origin)),
+                format!("Not a battista snapshot: {}", e),
+            )
+        })?;
+        let version = value
+            .get("version")
+            .and_then(|x| x.as_str())
+            .map(String::from);
+        let data = match value.get("data") {
+            Some(json::Value::Object(fields)) => fields
+                .iter()
+                .filter_map(|(name, sha256)| Some((name.clone(), String::from(sha256.as_str()?))))
+                .collect(),
+            _ => vec![],
+        };
+        return Ok((version, data));
+    }
+
+    let mut version = None;
+    let mut data = vec![];
+    for line in content.lines() {
+        if let Some(rest) = line.strip_prefix(VERSION_TAG) {
+            version = Some(String::from(rest.trim()));
+        } else if let Some((sha256, name)) = line
+            .strip_prefix(DATA_TAG)
+            .and_then(|rest| rest.trim().split_once(' '))
+        {
+            data.push((String::from(name), String::from(sha256)));
+        }
+    }
+    Ok((version, data))
+}
+
+// `verify`: whether each data file is still what the report was made from.
+// Files are matched by the name recorded in the report, or failing that by
+// their file name, so the check also works from another directory.
+pub fn run(report: &Path, inputs: &[String]) -> Result<(), Diagnostic> {
+    let origin = report.display().to_string();
+    let (version, recorded) = read_report(report)?;
+    if recorded.is_empty() {
+        return Err(diagnostics::error(
+            "verify-no-hash",
+            Some(Location::file(&origin)),
+            String::from("The report records no data hashes (made by an older battista?)"),
+        ));
+    }
+    let file_name = |x: &str| {
+        Path::new(x)
+            .file_name()
+            .map(|x| x.to_string_lossy().into_owned())
+    };
+
+    println!(
+        "Report `{}` (battista {}):",
+        origin,
+        version.as_deref().unwrap_or("?")
+    );
+    let mut stale = vec![];
+    let mut matched = vec![];
+    for input in inputs {
+        let content = fs::read(input).map_err(|e| {
+            diagnostics::error(
+                "io-read",
+                Some(Location::file(input)),
+                format!("Could not read file: {}", e),
+            )
+        })?;
+        let sha256 = hash::sha256(&content);
+        let entry = recorded.iter().find(|(name, _)| name == input).or_else(|| {
+            recorded
+                .iter()
+                .find(|(name, _)| file_name(name) == file_name(input))
+        });
+        let state = match entry {
+            Some((name, recorded)) => {
+                matched.push(name);
+                if *recorded == sha256 {
+                    "up to date"
+                } else {
+                    stale.push(input.clone());
+                    "changed since the report"
+                }
+            }
+            None => {
+                stale.push(input.clone());
+                "not part of the report"
+            }
+        };
+        println!("  - `{}`: {}", input, state);
+    }
+    for (name, _) in recorded.iter().filter(|(name, _)| !matched.contains(&name)) {
+        println!("  - `{}`: used by the report, not given", name);
+    }
+
+    if version
+        .as_deref()
+        .is_some_and(|x| x != env!("CARGO_PKG_VERSION"))
+    {
+        diagnostics::info(
+            "verify-version",
+            Some(Location::file(&origin)),
+            format!(
+                "Made by battista {}, this is {}: the layout may have changed.",
+                version.unwrap(),
+                env!("CARGO_PKG_VERSION")
+            ),
+        );
+    }
+    if !stale.is_empty() {
+        return Err(diagnostics::error(
+            "verify-stale",
+            Some(Location::file(&origin)),
+            format!(
+                "The report is stale: {} no longer match(es)",
+                stale.join(", ")
+            ),
+        ));
+    }
+    println!("The report is up to date.");
+    Ok(())
+}